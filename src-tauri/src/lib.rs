@@ -2,14 +2,14 @@
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{Emitter, Manager, State};
-use tonic::transport::Channel;
+use tonic::transport::{Channel, Endpoint};
 use validblock::anchor_service_client::AnchorServiceClient;
 use validblock::verify_service_client::VerifyServiceClient;
 use validblock::anchor_service_server::AnchorServiceServer;
 use validblock::verify_service_server::VerifyServiceServer;
-use validblock::{AnchorRequest, VerifyRequest, Policy};
-use std::process::{Command, Stdio};
-use std::path::PathBuf;
+use validblock::{AnchorRequest, VerifyRequest, Policy, ExportPsbtRequest, SubmitPsbtRequest};
+use std::process::{Child, Command, Stdio};
+use std::path::{Path, PathBuf};
 use std::net::TcpListener;
 
 mod settings;
@@ -22,14 +22,39 @@ use std::sync::Arc;
 
 use tonic::transport::Server;
 
-use validblock_core::{AnchorEngine, Digest256};
-use validblock_storage::AnchorRepo;
+use validblock_core::telemetry::{self, LogConfig, LogFormat};
+use validblock_core::{auth, status};
+use validblock_core::{AnchorEngine, Digest256, ErrorCode};
+use validblock_types::payload::MAX_MEMO_LEN;
+use validblock_storage::{AnchorRepo, AuditEntry};
+use validblock_wallet::esplora::{self, EsploraBackend};
+use validblock_wallet::{psbt, Keystore, KeystoreSession, Secret, Verdict};
+
+/// Unlock timeout used when the caller doesn't pass one
+const DEFAULT_UNLOCK_SECS: u64 = 300;
 
 #[derive(Default)]
 struct AppState {
     bearer_token: String,
     trinity_mode: Mutex<bool>,
     settings: Mutex<SettingsStore>,
+    keystore: Mutex<KeystoreSession>,
+    /// The proxy sidecar, when this app started it
+    proxy: Mutex<Option<Child>>,
+}
+
+fn data_path(app: &tauri::AppHandle, file: &str) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(file))
+}
+
+fn keystore_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    data_path(app, "keystore.json")
+}
+
+fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    data_path(app, "settings.json")
 }
 
 #[tauri::command]
@@ -43,15 +68,13 @@ async fn anchor_file(
         return Err("Trinity mode is enabled. Cannot perform on-chain operations.".into());
     }
 
-    if use_on_chain && memo.len() > 47 {
-        return Err("Memo too long for on-chain anchor (max 47 bytes).".into());
+    if use_on_chain && memo.len() > MAX_MEMO_LEN {
+        return Err(format!("Memo too long for on-chain anchor (max {} bytes).", MAX_MEMO_LEN));
     }
 
-    let mut client = AnchorServiceClient::connect("http://127.0.0.1:8080")
-        .await
-        .map_err(|e| format!("gRPC connection failed: {}", e))?;
+    let mut client = AnchorServiceClient::new(channel(&state).await?);
 
-    let req = AnchorRequest {
+    let req = authorized(AnchorRequest {
         file_content,
         memo,
         policy: if use_on_chain {
@@ -59,35 +82,162 @@ async fn anchor_file(
         } else {
             Policy::LocalOnly as i32
         },
-    };
+    }, &state)?;
 
     let res = client
         .anchor(req)
         .await
-        .map_err(|e| map_grpc_error(e.to_string()))?
+        .map_err(map_grpc_error)?
         .into_inner();
 
+    // a watch-only proxy leaves the signing to the unlocked keystore; while
+    // locked, the anchor waits for `sign_anchor`
+    let unlocked = state.keystore.lock().unwrap().is_unlocked();
+    if !res.psbt.is_empty() && unlocked {
+        let psbt = sign_with_keystore(&res.psbt, &state)?;
+        client
+            .submit_psbt(authorized(SubmitPsbtRequest { psbt }, &state)?)
+            .await
+            .map_err(map_grpc_error)?;
+    }
+
     Ok(res.digest)
 }
 
+/// Base64 PSBT of an anchor waiting for the offline signer
 #[tauri::command]
-async fn verify_file(
-    file_content: Vec<u8>,
-    state: State<'_, AppState>,
-) -> Result<bool, String> {
-    let mut client = VerifyServiceClient::connect("http://127.0.0.1:8080")
+async fn export_psbt(digest: String, state: State<'_, AppState>) -> Result<String, String> {
+    let mut client = AnchorServiceClient::new(channel(&state).await?);
+
+    let res = client
+        .export_psbt(authorized(ExportPsbtRequest { digest }, &state)?)
         .await
-        .map_err(|e| format!("gRPC connection failed: {}", e))?;
+        .map_err(map_grpc_error)?
+        .into_inner();
+
+    Ok(res.psbt)
+}
+
+/// Import a signed PSBT (base64), returning the broadcast txid
+#[tauri::command]
+async fn submit_psbt(psbt: String, state: State<'_, AppState>) -> Result<String, String> {
+    let mut client = AnchorServiceClient::new(channel(&state).await?);
+
+    let res = client
+        .submit_psbt(authorized(SubmitPsbtRequest { psbt }, &state)?)
+        .await
+        .map_err(map_grpc_error)?
+        .into_inner();
+
+    Ok(res.txid)
+}
 
-    let req = VerifyRequest { file_content };
+/// Sign the PSBT of an anchor awaiting signature with the unlocked keystore
+/// and submit it, returning the broadcast txid
+#[tauri::command]
+async fn sign_anchor(digest: String, state: State<'_, AppState>) -> Result<String, String> {
+    let mut client = AnchorServiceClient::new(channel(&state).await?);
 
+    let pending = client
+        .export_psbt(authorized(ExportPsbtRequest { digest }, &state)?)
+        .await
+        .map_err(map_grpc_error)?
+        .into_inner();
+    let psbt = sign_with_keystore(&pending.psbt, &state)?;
     let res = client
-        .verify(req)
+        .submit_psbt(authorized(SubmitPsbtRequest { psbt }, &state)?)
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(map_grpc_error)?
         .into_inner();
 
-    Ok(res.verified)
+    Ok(res.txid)
+}
+
+/// Sign a base64 PSBT with the key of the unlocked keystore session
+fn sign_with_keystore(psbt: &str, state: &AppState) -> Result<String, String> {
+    let network = validblock_wallet::parse_network(&state.settings.lock().unwrap().network)
+        .map_err(|e| e.to_string())?;
+    let mut psbt = psbt::from_base64(psbt).map_err(|e| e.to_string())?;
+    let mut session = state.keystore.lock().unwrap();
+    let secret = session.secret().ok_or("Unlock the keystore to sign anchors.")?;
+    let wallet = secret
+        .hd_wallet(network)
+        .map_err(|_| "Only a mnemonic or xprv keystore can sign anchors.".to_string())?;
+    if wallet.sign_psbt(&mut psbt).map_err(|e| e.to_string())? == 0 {
+        return Err("The keystore holds none of this anchor's keys.".into());
+    }
+    Ok(psbt::to_base64(&psbt))
+}
+
+#[tauri::command]
+async fn verify_file(
+    file_content: Vec<u8>,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let mut client = VerifyServiceClient::new(channel(&state).await?);
+
+    let req = authorized(VerifyRequest { file_content }, &state)?;
+
+    match client.verify(req).await {
+        Ok(res) => Ok(res.into_inner().verified),
+        Err(status) if status::error_code(&status) == Some(ErrorCode::NotFound) => Ok(false),
+        Err(status) => Err(map_grpc_error(status)),
+    }
+}
+
+#[derive(serde::Serialize)]
+struct OnChainVerification {
+    verified: bool,
+    verdict: String,
+    height: Option<u32>,
+    block_hash: Option<String>,
+    block_time: Option<u32>,
+}
+
+/// Check a file against `txid` using only the chain backend, never the local DB
+#[tauri::command]
+async fn verify_on_chain(
+    file_content: Vec<u8>,
+    txid: String,
+    state: State<'_, AppState>,
+) -> Result<OnChainVerification, String> {
+    let (network, chain_api) = {
+        let settings = state.settings.lock().unwrap();
+        (settings.network.clone(), settings.chain_api.clone())
+    };
+    let network = validblock_wallet::parse_network(&network).map_err(|e| e.to_string())?;
+    let url = match chain_api.trim() {
+        "" => esplora::default_url(network)
+            .ok_or("No public chain API for this network; set one in Settings.")?
+            .to_string(),
+        url => url.to_string(),
+    };
+
+    let verdict = tauri::async_runtime::spawn_blocking(move || {
+        let digest = validblock_hasher::hash_reader(&*file_content).map_err(|e| e.to_string())?;
+        let txid = txid.trim().parse().map_err(|_| format!("Invalid txid: {}", txid))?;
+        validblock_wallet::verify_anchor(&EsploraBackend::new(&url), network, &txid, &digest).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let (height, block_hash, block_time) = match &verdict {
+        Verdict::Confirmed { block_hash, height, time } => (Some(*height), Some(block_hash.to_string()), Some(*time)),
+        _ => (None, None, None),
+    };
+    Ok(OnChainVerification {
+        verified: verdict.is_verified(),
+        verdict: verdict.to_string(),
+        height,
+        block_hash,
+        block_time,
+    })
+}
+
+/// Bearer token for the web view's own gRPC-Web calls to the proxy
+#[tauri::command]
+fn get_bearer_token(state: State<'_, AppState>) -> String {
+    state.bearer_token.clone()
 }
 
 #[tauri::command]
@@ -109,28 +259,206 @@ fn get_trinity_mode(state: State<'_, AppState>) -> bool {
 //     Ok(exists)
 // }
 
+/// Encrypt a mnemonic, xprv or WIF key under `passphrase` and store it
+#[tauri::command]
+fn create_keystore(
+    secret: String,
+    passphrase: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let secret = Secret::new(&secret);
+    let network = validblock_wallet::parse_network(&state.settings.lock().unwrap().network)
+        .map_err(|e| e.to_string())?;
+    if secret.hd_wallet(network).is_err() && secret.wpkh_key(network).is_err() {
+        return Err("Not a mnemonic, xprv or WIF key for the configured network.".into());
+    }
+    let store = Keystore::encrypt(&secret, &passphrase, Default::default()).map_err(|e| e.to_string())?;
+    store.save(keystore_path(&app)?).map_err(|e| e.to_string())?;
+    state.keystore.lock().unwrap().lock();
+    Ok(())
+}
+
+/// Decrypt the keystore and keep it unlocked for `timeout_secs`, during which
+/// it signs the anchors the proxy's watch-only wallet leaves awaiting
+/// signature
+#[tauri::command]
+fn unlock_keystore(
+    passphrase: String,
+    timeout_secs: Option<u64>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let store = Keystore::load(keystore_path(&app)?).map_err(|e| e.to_string())?;
+    let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_UNLOCK_SECS));
+    state
+        .keystore
+        .lock()
+        .unwrap()
+        .unlock(&store, &passphrase, timeout)
+        .map_err(|e| e.to_string())?;
+    app.emit("keystore-unlocked", timeout.as_secs()).ok();
+    Ok(())
+}
+
+#[tauri::command]
+fn lock_keystore(app: tauri::AppHandle, state: State<'_, AppState>) {
+    state.keystore.lock().unwrap().lock();
+    app.emit("keystore-locked", ()).ok();
+}
+
+/// Seconds until the keystore locks itself, `None` when locked
+#[tauri::command]
+fn keystore_status(state: State<'_, AppState>) -> Option<u64> {
+    let mut session = state.keystore.lock().unwrap();
+    if session.is_unlocked() {
+        session.remaining().map(|d| d.as_secs())
+    } else {
+        None
+    }
+}
+
 #[tauri::command]
 fn get_settings(state: State<'_, AppState>) -> Result<SettingsStore, String> {
     Ok(state.settings.lock().unwrap().clone())
 }
 
+/// Save the settings, restarting the proxy when the network changes since it
+/// only reads the network at startup. Changes are recorded in the audit trail
 #[tauri::command]
-fn put_settings(new_settings: SettingsStore, state: State<'_, AppState>) -> Result<(), String> {
-    *state.settings.lock().unwrap() = new_settings;
+fn put_settings(new_settings: SettingsStore, app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let old_settings = state.settings.lock().unwrap().clone();
+    let result = match validblock_wallet::parse_network(&new_settings.network) {
+        Err(e) => Err(("InvalidArgument", e.to_string())),
+        Ok(_) => save_settings(&old_settings, &new_settings, &app, &state).map_err(|e| ("Internal", e)),
+    };
+    let changed = changed_settings(&old_settings, &new_settings);
+    match &result {
+        Ok(()) if !changed.is_empty() => audit_settings(&changed, "OK"),
+        Ok(()) => {}
+        Err((outcome, _)) => audit_settings(&changed, outcome),
+    }
+    result.map_err(|(_, e)| e)
+}
+
+fn save_settings(
+    old_settings: &SettingsStore,
+    new_settings: &SettingsStore,
+    app: &tauri::AppHandle,
+    state: &AppState,
+) -> Result<(), String> {
+    if new_settings.network != old_settings.network {
+        restart_proxy(&new_settings.network, app, state)?;
+    }
+    new_settings.save(&settings_path(app)?)?;
+    *state.settings.lock().unwrap() = new_settings.clone();
     Ok(())
 }
 
-fn map_grpc_error(msg: String) -> String {
-    if msg.contains("DuplicateDigest") {
-        "Already anchored. Try again with a different file.".into()
-    } else if msg.contains("InvalidMemo") {
-        "Memo too long for on-chain anchor (max 47 bytes).".into()
-    } else {
-        msg
+/// Comma-separated names of the settings that differ
+fn changed_settings(old_settings: &SettingsStore, new_settings: &SettingsStore) -> String {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old_settings), serde_json::to_value(new_settings))
+    else {
+        return String::new();
+    };
+    new.iter()
+        .filter(|(name, value)| old.get(*name) != Some(value))
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Append a `Settings/Put` entry to the audit trail in the proxy's database,
+/// which the app shares: `VALIDBLOCK_DB`, else ./anchors.db, as the proxy
+/// it spawns resolves it
+fn audit_settings(changed: &str, outcome: &str) {
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or_default();
+    let entry = AuditEntry {
+        seq: 0,
+        ts,
+        actor: validblock_core::DEFAULT_OWNER.into(),
+        credential: "token".into(),
+        source: "gui".into(),
+        action: "Settings/Put".into(),
+        target: changed.into(),
+        outcome: outcome.into(),
+        hash: Digest256([0; 32]),
+    };
+    let db = std::env::var(validblock_core::config::DB_ENV).ok();
+    // the settings have changed either way; a lost entry shows in the logs
+    if let Err(e) = AnchorRepo::new(db.as_deref()).and_then(|repo| repo.append_audit(&entry)) {
+        tracing::error!(action = %entry.action, error = %e, "Could not write the audit trail");
     }
 }
 
-fn spawn_proxy()  {
+/// Replace the proxy this app started with one on `network`. A proxy started
+/// elsewhere can't be restarted from here, so the change is refused
+fn restart_proxy(network: &str, app: &tauri::AppHandle, state: &AppState) -> Result<(), String> {
+    let mut proxy = state.proxy.lock().unwrap();
+    match proxy.take() {
+        Some(mut child) => {
+            child.kill().ok();
+            child.wait().map_err(|e| format!("Failed to stop the proxy: {}", e))?;
+        }
+        None if TcpListener::bind("127.0.0.1:8080").is_err() => {
+            return Err("Another proxy is running on port 8080; restart it on the new network instead.".into());
+        }
+        None => {}
+    }
+    let log_dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    *proxy = spawn_proxy(network, &state.bearer_token, &log_dir);
+    if proxy.is_none() {
+        return Err("Failed to restart the proxy on the new network.".into());
+    }
+    Ok(())
+}
+
+/// Connection to the configured RPC endpoint, over TLS for `https://`
+async fn channel(state: &AppState) -> Result<Channel, String> {
+    let settings = state.settings.lock().unwrap().clone();
+    let mut endpoint = Endpoint::from_shared(settings.rpc_endpoint.clone()).map_err(|e| e.to_string())?;
+    if settings.rpc_endpoint.starts_with("https://") {
+        let ca = match settings.tls_ca.trim() {
+            "" => return Err("Set a CA certificate in Settings to use an https:// endpoint.".into()),
+            ca => Path::new(ca),
+        };
+        let identity = match (settings.tls_client_cert.trim(), settings.tls_client_key.trim()) {
+            ("", "") => None,
+            (cert, key) => Some((Path::new(cert), Path::new(key))),
+        };
+        let config = validblock_core::tls::client_config(ca, identity).map_err(|e| e.to_string())?;
+        endpoint = endpoint.tls_config(config).map_err(|e| e.to_string())?;
+    }
+    endpoint
+        .connect()
+        .await
+        .map_err(|e| format!("gRPC connection failed: {}", e))
+}
+
+/// Wrap `message` in a request carrying the app's bearer token
+fn authorized<T>(message: T, state: &AppState) -> Result<tonic::Request<T>, String> {
+    let mut request = tonic::Request::new(message);
+    let value = format!("Bearer {}", state.bearer_token)
+        .parse()
+        .map_err(|_| "Invalid bearer token".to_string())?;
+    request.metadata_mut().insert(auth::AUTHORIZATION_HEADER, value);
+    Ok(request)
+}
+
+/// User-facing message for a failed call, keyed on the server's error code
+fn map_grpc_error(status: tonic::Status) -> String {
+    match status::error_code(&status) {
+        Some(ErrorCode::DuplicateDigest) => "Already anchored. Try again with a different file.".into(),
+        Some(ErrorCode::InvalidDigest) => "Invalid digest.".into(),
+        Some(ErrorCode::NotFound) => "No anchor found.".into(),
+        Some(ErrorCode::Unauthenticated) => "The proxy rejected the app's credentials; restart ValidBlock.".into(),
+        Some(ErrorCode::Wallet) => format!("Wallet error: {}", status.message()),
+        _ => status.message().to_string(),
+    }
+}
+
+fn spawn_proxy(network: &str, bearer_token: &str, log_dir: &Path) -> Option<Child> {
     // let mut path = std::env::current_exe().ok()?;
     // path.pop(); // remove `gui` binary name
     // path.push("proxy"); // add proxy binary
@@ -141,14 +469,25 @@ fn spawn_proxy()  {
 
     if TcpListener::bind("127.0.0.1:8080").is_ok() {
         let proxy_path = "./target/debug/proxy";
-        println!("Spawning proxy binary at: {:?}", proxy_path);
-        match Command::new(proxy_path).spawn() {
-            Ok(_) => println!("✅ Proxy sidecar started"),
-            Err(e) => println!("❌ Failed to start proxy sidecar: {}", e),
+        tracing::info!(path = proxy_path, "Spawning proxy sidecar");
+        // Passed in the environment rather than argv, which other users can read
+        match Command::new(proxy_path)
+            .env(validblock_core::NETWORK_ENV, network)
+            .env(auth::AUTH_TOKEN_ENV, bearer_token)
+            .env(telemetry::LOG_DIR_ENV, log_dir)
+            .env(telemetry::LOG_FORMAT_ENV, "json")
+            .spawn()
+        {
+            Ok(child) => {
+                tracing::info!("Proxy sidecar started");
+                return Some(child);
+            }
+            Err(e) => tracing::error!(error = %e, "Failed to start proxy sidecar"),
         }
     } else {
-        println!("Proxy already running or port 8080 is in use.");
+        tracing::warn!("Proxy already running or port 8080 is in use");
     }
+    None
 
     // println!("Spawning proxy binary at: {:?}", path);
 
@@ -161,33 +500,48 @@ fn spawn_proxy()  {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // 🔥 Launch proxy sidecar (gRPC-Web server)
-    #[cfg(not(mobile))]
-    {
-        spawn_proxy();
-        // if spawn_proxy().is_none() {
-        //     eprintln!("❌ Failed to start proxy sidecar");
-        // } else {
-        //     println!("✅ Proxy sidecar started");
-        // }
-    }
- 
     let bearer_token = uuid::Uuid::new_v4().to_string();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            // JSON lines, rotated daily, next to the app's other data
+            let log_dir = app.path().app_log_dir()?;
+            let log = LogConfig { format: LogFormat::Json, dir: Some(log_dir.clone()), ..Default::default() };
+            app.manage(telemetry::init(&log, "validblock-gui")?);
+
+            let state = app.state::<AppState>();
+            *state.settings.lock().unwrap() = SettingsStore::load(&settings_path(app.handle())?)?;
+
+            // Launch proxy sidecar (gRPC-Web server)
+            #[cfg(not(mobile))]
+            {
+                let network = state.settings.lock().unwrap().network.clone();
+                *state.proxy.lock().unwrap() = spawn_proxy(&network, &state.bearer_token, &log_dir);
+            }
+            Ok(())
+        })
         .manage(AppState {
             bearer_token,
             ..Default::default()
         })
         .invoke_handler(tauri::generate_handler![
             anchor_file,
+            export_psbt,
+            submit_psbt,
+            sign_anchor,
             verify_file,
+            verify_on_chain,
+            get_bearer_token,
             toggle_trinity_mode,
             get_trinity_mode,
             get_settings,
             // digest_exists,
             put_settings,
+            create_keystore,
+            unlock_keystore,
+            lock_keystore,
+            keystore_status,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
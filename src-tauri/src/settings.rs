@@ -1,4 +1,5 @@
 use serde::{Serialize, Deserialize};
+use std::path::Path;
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct SettingsStore {
@@ -6,6 +7,20 @@ pub struct SettingsStore {
     pub default_policy: String, // "OnChain" or "LocalOnly"
     pub wallet_id: String,
     pub trinity_mode: bool,
+    #[serde(default = "default_network")]
+    pub network: String, // "bitcoin", "testnet", "signet" or "regtest"
+    #[serde(default)]
+    pub chain_api: String, // Esplora base URL; empty for the network's public instance
+    #[serde(default)]
+    pub tls_ca: String, // PEM CA for an https:// endpoint; empty for plaintext
+    #[serde(default)]
+    pub tls_client_cert: String, // PEM client certificate when the server requires mTLS
+    #[serde(default)]
+    pub tls_client_key: String,
+}
+
+fn default_network() -> String {
+    "bitcoin".into()
 }
 
 impl Default for SettingsStore {
@@ -15,6 +30,27 @@ impl Default for SettingsStore {
             default_policy: "OnChain".into(),
             wallet_id: "".into(),
             trinity_mode: false,
+            network: default_network(),
+            chain_api: String::new(),
+            tls_ca: String::new(),
+            tls_client_cert: String::new(),
+            tls_client_key: String::new(),
+        }
+    }
+}
+
+impl SettingsStore {
+    /// Settings saved at `path`, or the defaults before any were saved
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid settings file: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.to_string()),
         }
     }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
 }
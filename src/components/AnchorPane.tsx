@@ -2,14 +2,19 @@ import { useState } from 'react';
 import { invoke } from "@tauri-apps/api/core";
 import { AnchorRequest, Policy } from "../gen/proto/validblock_pb";
 import { anchorClient } from "../lib/client";
+import { errorMessage } from "../lib/errors";
 import { sha256 } from "@noble/hashes/sha256";
 import { Buffer } from 'buffer';
 
+// OP_RETURN payload v1: 80 bytes minus 6 header bytes and a 32-byte digest
+const MAX_MEMO_BYTES = 42;
+
 export default function AnchorPane() {
   const [file, setFile] = useState<File | null>(null);
   const [memo, setMemo] = useState('');
   const [digest, setDigest] = useState<string | null>(null);
-  const isMemoTooLong = memo.length > 47;
+  const memoBytes = new TextEncoder().encode(memo).length;
+  const isMemoTooLong = memoBytes > MAX_MEMO_BYTES;
 
   const handleDrop = (e: React.DragEvent<HTMLDivElement>) => {
     e.preventDefault();
@@ -43,7 +48,7 @@ export default function AnchorPane() {
       const res = await anchorClient.anchor(req);
       setDigest(res.digest);
     } catch (err) {
-      alert(`Anchor failed: ${err}`);
+      alert(`Anchor failed: ${errorMessage(err)}`);
     }
   };
 
@@ -68,10 +73,10 @@ export default function AnchorPane() {
           value={memo}
           onChange={(e) => setMemo(e.target.value)}
           className="w-full p-3 rounded-md text-black border-1 border-solid border-black"
-          placeholder="Max 47 bytes"
+          placeholder={`Max ${MAX_MEMO_BYTES} bytes`}
         />
         <p className={`text-sm mt-1 ${isMemoTooLong ? 'text-red-500' : 'text-gray-400'}`}>
-          {memo.length} / 47 bytes
+          {memoBytes} / {MAX_MEMO_BYTES} bytes
         </p>
       </div>
 
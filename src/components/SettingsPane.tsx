@@ -10,6 +10,12 @@ export default function SettingsPane() {
   );
     
   const [walletId, setWalletId] = useState('');
+  const [network, setNetwork] = useState('bitcoin');
+  const [chainApi, setChainApi] = useState('');
+  const [tlsCa, setTlsCa] = useState('');
+  const [tlsClientCert, setTlsClientCert] = useState('');
+  const [tlsClientKey, setTlsClientKey] = useState('');
+  const [error, setError] = useState('');
 
   useEffect(() => {
     const getSetting = async() => {
@@ -19,6 +25,11 @@ export default function SettingsPane() {
         setAnchorPolicy(res.default_policy);
         setTrinity(res.trinity_mode);
         setWalletId(res.wallet_id);
+        setNetwork(res.network);
+        setChainApi(res.chain_api);
+        setTlsCa(res.tls_ca);
+        setTlsClientCert(res.tls_client_cert);
+        setTlsClientKey(res.tls_client_key);
       });
     }
     getSetting();
@@ -32,8 +43,15 @@ export default function SettingsPane() {
         wallet_id: walletId,
         dark_mode: darkMode,
         trinity_mode: trinity,
+        network,
+        chain_api: chainApi,
+        tls_ca: tlsCa,
+        tls_client_cert: tlsClientCert,
+        tls_client_key: tlsClientKey,
       }
-    });
+    })
+      .then(() => setError(''))
+      .catch((e) => setError(String(e)));
   };
 
   const toggleTrinity = (checked: boolean) => {
@@ -44,6 +62,8 @@ export default function SettingsPane() {
 
   return (
     <section className="space-y-6">
+      {error && <p className="text-sm text-red-600">{error}</p>}
+
       <div>
         <label className="block mb-1 text-sm font-semibold text-black">Default Anchor Policy</label>
         <select
@@ -78,6 +98,65 @@ export default function SettingsPane() {
         />
       </div>
 
+      <div>
+        <label className="block mb-1 text-sm font-semibold text-black">TLS CA Certificate</label>
+        <input
+          type="text"
+          value={tlsCa}
+          placeholder="PEM file trusted for https:// endpoints"
+          onChange={(e) => setTlsCa(e.target.value)}
+          onBlur={updateSettings}
+          className="w-full p-2 rounded-md text-black border-1 border-solid border-black"
+        />
+      </div>
+
+      <div>
+        <label className="block mb-1 text-sm font-semibold text-black">TLS Client Certificate &amp; Key</label>
+        <input
+          type="text"
+          value={tlsClientCert}
+          placeholder="PEM certificate, only if the server requires mutual TLS"
+          onChange={(e) => setTlsClientCert(e.target.value)}
+          onBlur={updateSettings}
+          className="w-full p-2 rounded-md text-black border-1 border-solid border-black"
+        />
+        <input
+          type="text"
+          value={tlsClientKey}
+          placeholder="PEM private key"
+          onChange={(e) => setTlsClientKey(e.target.value)}
+          onBlur={updateSettings}
+          className="w-full mt-2 p-2 rounded-md text-black border-1 border-solid border-black"
+        />
+      </div>
+
+      <div>
+        <label className="block mb-1 text-sm font-semibold text-black">Network</label>
+        <select
+          value={network}
+          onChange={(e) => setNetwork(e.target.value)}
+          onBlur={updateSettings}
+          className="w-full p-2 rounded-md text-black border-1 border-solid border-black"
+        >
+          <option value="bitcoin">Mainnet</option>
+          <option value="testnet">Testnet</option>
+          <option value="signet">Signet</option>
+          <option value="regtest">Regtest</option>
+        </select>
+      </div>
+
+      <div>
+        <label className="block mb-1 text-sm font-semibold text-black">Chain API (Esplora)</label>
+        <input
+          type="text"
+          value={chainApi}
+          placeholder="Public instance for the network"
+          onChange={(e) => setChainApi(e.target.value)}
+          onBlur={updateSettings}
+          className="w-full p-2 rounded-md text-black border-1 border-solid border-black"
+        />
+      </div>
+
       <div>
         <label className="block mb-1 text-sm font-semibold text-black">Wallet&nbsp;ID</label>
         <input
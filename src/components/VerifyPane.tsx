@@ -1,11 +1,13 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { verifyClient } from "../lib/client";
+import type { AnchorEvent } from "../gen/proto/validblock_pb";
+import { errorCode, errorMessage } from "../lib/errors";
 import { listen } from "@tauri-apps/api/event";
 
 export default function VerifyPane() {
   const [file, setFile] = useState<File | null>(null);
-  const [result, setResult] = useState<{ verified: boolean } | null>(null);
+  const [result, setResult] = useState<{ verified: boolean; events: AnchorEvent[] } | null>(null);
   const [trinityMode, setTrinityMode] = useState(false);
 
   const handleVerify = async () => {
@@ -15,9 +17,13 @@ export default function VerifyPane() {
 
     try {
       const res = await verifyClient.verify({ fileContent: content });
-      setResult({ verified: res.verified });
+      setResult({ verified: res.verified, events: res.events });
     } catch (err) {
-      alert(`Verify failed: ${err}`);
+      if (errorCode(err) === "NOT_FOUND") {
+        setResult({ verified: false, events: [] });
+        return;
+      }
+      alert(`Verify failed: ${errorMessage(err)}`);
     }
   };
 
@@ -67,6 +73,17 @@ export default function VerifyPane() {
         </div>
       )}
 
+      {result && result.events.length > 0 && (
+        <ul className="space-y-1 text-sm">
+          {result.events.map((ev, i) => (
+            <li key={i} className="bg-panel p-2 rounded">
+              {new Date(Number(ev.timestamp) * 1000).toLocaleString()} · {ev.owner} · {ev.status}
+              {ev.memo.length > 0 && <> · {new TextDecoder().decode(ev.memo)}</>}
+            </li>
+          ))}
+        </ul>
+      )}
+
       <button
         disabled={trinityMode}
         title={trinityMode ? "Disabled in Trinity Mode" : ""}
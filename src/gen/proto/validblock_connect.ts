@@ -3,7 +3,7 @@
 /* eslint-disable */
 // @ts-nocheck
 
-import { AnchorRequest, AnchorResponse, ExistDigestRequest, ExistDigestResponse, VerifyRequest, VerifyResponse } from "./validblock_pb.js";
+import { AnchorRequest, AnchorResponse, CreateKeyRequest, CreateKeyResponse, CreateTenantKeyRequest, CreateTenantKeyResponse, CreateTenantRequest, DisableTenantRequest, EnableTenantRequest, EventNotice, ExistDigestRequest, ExistDigestResponse, ExportAuditChunk, ExportAuditRequest, ExportPsbtRequest, ExportPsbtResponse, GetQuotaRequest, GetQuotaResponse, IntegrityCheckResponse, ListAnchorsRequest, ListAnchorsResponse, ListAuditRequest, ListAuditResponse, ListKeysRequest, ListKeysResponse, ListTenantsRequest, ListTenantsResponse, MaintenanceRequest, MaintenanceResponse, ReloadConfigRequest, ReloadConfigResponse, RevokeKeyRequest, RevokeKeyResponse, SubmitPsbtRequest, SubscribeEventsRequest, Tenant, VerifyAuditRequest, VerifyAuditResponse, VerifyRequest, VerifyResponse, WalletStatusRequest, WalletStatusResponse } from "./validblock_pb.js";
 import { MethodKind } from "@bufbuild/protobuf";
 
 /**
@@ -21,6 +21,33 @@ export const AnchorService = {
       O: AnchorResponse,
       kind: MethodKind.Unary,
     },
+    /**
+     * @generated from rpc validblock.AnchorService.ExportPsbt
+     */
+    exportPsbt: {
+      name: "ExportPsbt",
+      I: ExportPsbtRequest,
+      O: ExportPsbtResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AnchorService.SubmitPsbt
+     */
+    submitPsbt: {
+      name: "SubmitPsbt",
+      I: SubmitPsbtRequest,
+      O: AnchorResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AnchorService.GetQuota
+     */
+    getQuota: {
+      name: "GetQuota",
+      I: GetQuotaRequest,
+      O: GetQuotaResponse,
+      kind: MethodKind.Unary,
+    },
   }
 } as const;
 
@@ -50,6 +77,190 @@ export const VerifyService = {
       O: ExistDigestResponse,
       kind: MethodKind.Unary,
     },
+    /**
+     * @generated from rpc validblock.VerifyService.ListAnchors
+     */
+    listAnchors: {
+      name: "ListAnchors",
+      I: ListAnchorsRequest,
+      O: ListAnchorsResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.VerifyService.SubscribeEvents
+     */
+    subscribeEvents: {
+      name: "SubscribeEvents",
+      I: SubscribeEventsRequest,
+      O: EventNotice,
+      kind: MethodKind.ServerStreaming,
+    },
+  }
+} as const;
+
+/**
+ * Tenant administration; takes the admin role
+ *
+ * @generated from service validblock.TenantService
+ */
+export const TenantService = {
+  typeName: "validblock.TenantService",
+  methods: {
+    /**
+     * @generated from rpc validblock.TenantService.CreateTenant
+     */
+    createTenant: {
+      name: "CreateTenant",
+      I: CreateTenantRequest,
+      O: Tenant,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.TenantService.DisableTenant
+     */
+    disableTenant: {
+      name: "DisableTenant",
+      I: DisableTenantRequest,
+      O: Tenant,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.TenantService.EnableTenant
+     */
+    enableTenant: {
+      name: "EnableTenant",
+      I: EnableTenantRequest,
+      O: Tenant,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.TenantService.ListTenants
+     */
+    listTenants: {
+      name: "ListTenants",
+      I: ListTenantsRequest,
+      O: ListTenantsResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.TenantService.CreateTenantKey
+     */
+    createTenantKey: {
+      name: "CreateTenantKey",
+      I: CreateTenantKeyRequest,
+      O: CreateTenantKeyResponse,
+      kind: MethodKind.Unary,
+    },
+  }
+} as const;
+
+/**
+ * Server administration; takes the admin role
+ *
+ * @generated from service validblock.AdminService
+ */
+export const AdminService = {
+  typeName: "validblock.AdminService",
+  methods: {
+    /**
+     * @generated from rpc validblock.AdminService.CreateKey
+     */
+    createKey: {
+      name: "CreateKey",
+      I: CreateKeyRequest,
+      O: CreateKeyResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.ListKeys
+     */
+    listKeys: {
+      name: "ListKeys",
+      I: ListKeysRequest,
+      O: ListKeysResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.RevokeKey
+     */
+    revokeKey: {
+      name: "RevokeKey",
+      I: RevokeKeyRequest,
+      O: RevokeKeyResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.WalletStatus
+     */
+    walletStatus: {
+      name: "WalletStatus",
+      I: WalletStatusRequest,
+      O: WalletStatusResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.Checkpoint
+     */
+    checkpoint: {
+      name: "Checkpoint",
+      I: MaintenanceRequest,
+      O: MaintenanceResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.Vacuum
+     */
+    vacuum: {
+      name: "Vacuum",
+      I: MaintenanceRequest,
+      O: MaintenanceResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.IntegrityCheck
+     */
+    integrityCheck: {
+      name: "IntegrityCheck",
+      I: MaintenanceRequest,
+      O: IntegrityCheckResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.ReloadConfig
+     */
+    reloadConfig: {
+      name: "ReloadConfig",
+      I: ReloadConfigRequest,
+      O: ReloadConfigResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.ListAudit
+     */
+    listAudit: {
+      name: "ListAudit",
+      I: ListAuditRequest,
+      O: ListAuditResponse,
+      kind: MethodKind.Unary,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.ExportAudit
+     */
+    exportAudit: {
+      name: "ExportAudit",
+      I: ExportAuditRequest,
+      O: ExportAuditChunk,
+      kind: MethodKind.ServerStreaming,
+    },
+    /**
+     * @generated from rpc validblock.AdminService.VerifyAudit
+     */
+    verifyAudit: {
+      name: "VerifyAudit",
+      I: VerifyAuditRequest,
+      O: VerifyAuditResponse,
+      kind: MethodKind.Unary,
+    },
   }
 } as const;
 
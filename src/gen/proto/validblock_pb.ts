@@ -100,6 +100,27 @@ export class AnchorResponse extends Message<AnchorResponse> {
    */
   txid = "";
 
+  /**
+   * "local", "awaiting_signature", "broadcast" or "confirmed"
+   *
+   * @generated from field: string status = 4;
+   */
+  status = "";
+
+  /**
+   * base64 PSBT when status is "awaiting_signature"
+   *
+   * @generated from field: string psbt = 5;
+   */
+  psbt = "";
+
+  /**
+   * already anchored; nothing new was written or spent
+   *
+   * @generated from field: bool existing = 6;
+   */
+  existing = false;
+
   constructor(data?: PartialMessage<AnchorResponse>) {
     super();
     proto3.util.initPartial(data, this);
@@ -111,6 +132,9 @@ export class AnchorResponse extends Message<AnchorResponse> {
     { no: 1, name: "digest", kind: "scalar", T: 9 /* ScalarType.STRING */ },
     { no: 2, name: "timestamp", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
     { no: 3, name: "txid", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 4, name: "status", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 5, name: "psbt", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 6, name: "existing", kind: "scalar", T: 8 /* ScalarType.BOOL */ },
   ]);
 
   static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): AnchorResponse {
@@ -168,6 +192,77 @@ export class VerifyRequest extends Message<VerifyRequest> {
 }
 
 /**
+ * One anchoring of a digest by one owner
+ *
+ * @generated from message validblock.AnchorEvent
+ */
+export class AnchorEvent extends Message<AnchorEvent> {
+  /**
+   * @generated from field: string owner = 1;
+   */
+  owner = "";
+
+  /**
+   * @generated from field: int64 timestamp = 2;
+   */
+  timestamp = protoInt64.zero;
+
+  /**
+   * @generated from field: bytes memo = 3;
+   */
+  memo = new Uint8Array(0);
+
+  /**
+   * @generated from field: string txid = 4;
+   */
+  txid = "";
+
+  /**
+   * @generated from field: string status = 5;
+   */
+  status = "";
+
+  /**
+   * @generated from field: string digest = 6;
+   */
+  digest = "";
+
+  constructor(data?: PartialMessage<AnchorEvent>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.AnchorEvent";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "owner", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "timestamp", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+    { no: 3, name: "memo", kind: "scalar", T: 12 /* ScalarType.BYTES */ },
+    { no: 4, name: "txid", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 5, name: "status", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 6, name: "digest", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): AnchorEvent {
+    return new AnchorEvent().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): AnchorEvent {
+    return new AnchorEvent().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): AnchorEvent {
+    return new AnchorEvent().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: AnchorEvent | PlainMessage<AnchorEvent> | undefined, b: AnchorEvent | PlainMessage<AnchorEvent> | undefined): boolean {
+    return proto3.util.equals(AnchorEvent, a, b);
+  }
+}
+
+/**
+ * Top-level fields describe the earliest anchor event visible to the caller
+ *
  * @generated from message validblock.VerifyResponse
  */
 export class VerifyResponse extends Message<VerifyResponse> {
@@ -191,6 +286,18 @@ export class VerifyResponse extends Message<VerifyResponse> {
    */
   txid = "";
 
+  /**
+   * @generated from field: string status = 5;
+   */
+  status = "";
+
+  /**
+   * every visible event, earliest first
+   *
+   * @generated from field: repeated validblock.AnchorEvent events = 6;
+   */
+  events: AnchorEvent[] = [];
+
   constructor(data?: PartialMessage<VerifyResponse>) {
     super();
     proto3.util.initPartial(data, this);
@@ -203,6 +310,8 @@ export class VerifyResponse extends Message<VerifyResponse> {
     { no: 2, name: "digest", kind: "scalar", T: 9 /* ScalarType.STRING */ },
     { no: 3, name: "timestamp", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
     { no: 4, name: "txid", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 5, name: "status", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 6, name: "events", kind: "message", T: AnchorEvent, repeated: true },
   ]);
 
   static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): VerifyResponse {
@@ -298,3 +407,1782 @@ export class ExistDigestResponse extends Message<ExistDigestResponse> {
   }
 }
 
+/**
+ * @generated from message validblock.ListAnchorsRequest
+ */
+export class ListAnchorsRequest extends Message<ListAnchorsRequest> {
+  /**
+   * at most this many; 0 for the server's default
+   *
+   * @generated from field: uint32 limit = 1;
+   */
+  limit = 0;
+
+  /**
+   * @generated from field: uint32 offset = 2;
+   */
+  offset = 0;
+
+  constructor(data?: PartialMessage<ListAnchorsRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ListAnchorsRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "limit", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+    { no: 2, name: "offset", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ListAnchorsRequest {
+    return new ListAnchorsRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ListAnchorsRequest {
+    return new ListAnchorsRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ListAnchorsRequest {
+    return new ListAnchorsRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ListAnchorsRequest | PlainMessage<ListAnchorsRequest> | undefined, b: ListAnchorsRequest | PlainMessage<ListAnchorsRequest> | undefined): boolean {
+    return proto3.util.equals(ListAnchorsRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ListAnchorsResponse
+ */
+export class ListAnchorsResponse extends Message<ListAnchorsResponse> {
+  /**
+   * earliest first
+   *
+   * @generated from field: repeated validblock.AnchorEvent events = 1;
+   */
+  events: AnchorEvent[] = [];
+
+  constructor(data?: PartialMessage<ListAnchorsResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ListAnchorsResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "events", kind: "message", T: AnchorEvent, repeated: true },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ListAnchorsResponse {
+    return new ListAnchorsResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ListAnchorsResponse {
+    return new ListAnchorsResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ListAnchorsResponse {
+    return new ListAnchorsResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ListAnchorsResponse | PlainMessage<ListAnchorsResponse> | undefined, b: ListAnchorsResponse | PlainMessage<ListAnchorsResponse> | undefined): boolean {
+    return proto3.util.equals(ListAnchorsResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.SubscribeEventsRequest
+ */
+export class SubscribeEventsRequest extends Message<SubscribeEventsRequest> {
+  /**
+   * only these kinds; empty for all
+   *
+   * @generated from field: repeated string kinds = 1;
+   */
+  kinds: string[] = [];
+
+  constructor(data?: PartialMessage<SubscribeEventsRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.SubscribeEventsRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "kinds", kind: "scalar", T: 9 /* ScalarType.STRING */, repeated: true },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): SubscribeEventsRequest {
+    return new SubscribeEventsRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): SubscribeEventsRequest {
+    return new SubscribeEventsRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): SubscribeEventsRequest {
+    return new SubscribeEventsRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: SubscribeEventsRequest | PlainMessage<SubscribeEventsRequest> | undefined, b: SubscribeEventsRequest | PlainMessage<SubscribeEventsRequest> | undefined): boolean {
+    return proto3.util.equals(SubscribeEventsRequest, a, b);
+  }
+}
+
+/**
+ * Change in an anchor's lifecycle
+ *
+ * @generated from message validblock.EventNotice
+ */
+export class EventNotice extends Message<EventNotice> {
+  /**
+   * "anchored", "broadcast", "confirmed" or "failed"
+   *
+   * @generated from field: string kind = 1;
+   */
+  kind = "";
+
+  /**
+   * @generated from field: string digest = 2;
+   */
+  digest = "";
+
+  /**
+   * @generated from field: string owner = 3;
+   */
+  owner = "";
+
+  /**
+   * @generated from field: int64 timestamp = 4;
+   */
+  timestamp = protoInt64.zero;
+
+  /**
+   * @generated from field: string txid = 5;
+   */
+  txid = "";
+
+  /**
+   * block height of a "confirmed" event
+   *
+   * @generated from field: uint32 height = 6;
+   */
+  height = 0;
+
+  /**
+   * why a "failed" event failed
+   *
+   * @generated from field: string error = 7;
+   */
+  error = "";
+
+  constructor(data?: PartialMessage<EventNotice>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.EventNotice";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "kind", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "digest", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 3, name: "owner", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 4, name: "timestamp", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+    { no: 5, name: "txid", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 6, name: "height", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+    { no: 7, name: "error", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): EventNotice {
+    return new EventNotice().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): EventNotice {
+    return new EventNotice().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): EventNotice {
+    return new EventNotice().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: EventNotice | PlainMessage<EventNotice> | undefined, b: EventNotice | PlainMessage<EventNotice> | undefined): boolean {
+    return proto3.util.equals(EventNotice, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ExportPsbtRequest
+ */
+export class ExportPsbtRequest extends Message<ExportPsbtRequest> {
+  /**
+   * hex Digest256 of an anchor awaiting signature
+   *
+   * @generated from field: string digest = 1;
+   */
+  digest = "";
+
+  constructor(data?: PartialMessage<ExportPsbtRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ExportPsbtRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "digest", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ExportPsbtRequest {
+    return new ExportPsbtRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ExportPsbtRequest {
+    return new ExportPsbtRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ExportPsbtRequest {
+    return new ExportPsbtRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ExportPsbtRequest | PlainMessage<ExportPsbtRequest> | undefined, b: ExportPsbtRequest | PlainMessage<ExportPsbtRequest> | undefined): boolean {
+    return proto3.util.equals(ExportPsbtRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ExportPsbtResponse
+ */
+export class ExportPsbtResponse extends Message<ExportPsbtResponse> {
+  /**
+   * base64
+   *
+   * @generated from field: string psbt = 1;
+   */
+  psbt = "";
+
+  constructor(data?: PartialMessage<ExportPsbtResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ExportPsbtResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "psbt", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ExportPsbtResponse {
+    return new ExportPsbtResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ExportPsbtResponse {
+    return new ExportPsbtResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ExportPsbtResponse {
+    return new ExportPsbtResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ExportPsbtResponse | PlainMessage<ExportPsbtResponse> | undefined, b: ExportPsbtResponse | PlainMessage<ExportPsbtResponse> | undefined): boolean {
+    return proto3.util.equals(ExportPsbtResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.SubmitPsbtRequest
+ */
+export class SubmitPsbtRequest extends Message<SubmitPsbtRequest> {
+  /**
+   * base64, signed
+   *
+   * @generated from field: string psbt = 1;
+   */
+  psbt = "";
+
+  constructor(data?: PartialMessage<SubmitPsbtRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.SubmitPsbtRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "psbt", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): SubmitPsbtRequest {
+    return new SubmitPsbtRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): SubmitPsbtRequest {
+    return new SubmitPsbtRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): SubmitPsbtRequest {
+    return new SubmitPsbtRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: SubmitPsbtRequest | PlainMessage<SubmitPsbtRequest> | undefined, b: SubmitPsbtRequest | PlainMessage<SubmitPsbtRequest> | undefined): boolean {
+    return proto3.util.equals(SubmitPsbtRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.GetQuotaRequest
+ */
+export class GetQuotaRequest extends Message<GetQuotaRequest> {
+  constructor(data?: PartialMessage<GetQuotaRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.GetQuotaRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): GetQuotaRequest {
+    return new GetQuotaRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): GetQuotaRequest {
+    return new GetQuotaRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): GetQuotaRequest {
+    return new GetQuotaRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: GetQuotaRequest | PlainMessage<GetQuotaRequest> | undefined, b: GetQuotaRequest | PlainMessage<GetQuotaRequest> | undefined): boolean {
+    return proto3.util.equals(GetQuotaRequest, a, b);
+  }
+}
+
+/**
+ * The caller's on-chain anchoring allowance for the current UTC day
+ *
+ * @generated from message validblock.GetQuotaResponse
+ */
+export class GetQuotaResponse extends Message<GetQuotaResponse> {
+  /**
+   * @generated from field: uint32 used = 1;
+   */
+  used = 0;
+
+  /**
+   * 0 when unlimited
+   *
+   * @generated from field: uint32 limit = 2;
+   */
+  limit = 0;
+
+  /**
+   * 0 when unlimited
+   *
+   * @generated from field: uint32 remaining = 3;
+   */
+  remaining = 0;
+
+  /**
+   * Unix time the day ends
+   *
+   * @generated from field: int64 resets_at = 4;
+   */
+  resetsAt = protoInt64.zero;
+
+  constructor(data?: PartialMessage<GetQuotaResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.GetQuotaResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "used", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+    { no: 2, name: "limit", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+    { no: 3, name: "remaining", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+    { no: 4, name: "resets_at", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): GetQuotaResponse {
+    return new GetQuotaResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): GetQuotaResponse {
+    return new GetQuotaResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): GetQuotaResponse {
+    return new GetQuotaResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: GetQuotaResponse | PlainMessage<GetQuotaResponse> | undefined, b: GetQuotaResponse | PlainMessage<GetQuotaResponse> | undefined): boolean {
+    return proto3.util.equals(GetQuotaResponse, a, b);
+  }
+}
+
+/**
+ * A department or customer sharing the server; its id owns its anchors
+ *
+ * @generated from message validblock.Tenant
+ */
+export class Tenant extends Message<Tenant> {
+  /**
+   * @generated from field: string id = 1;
+   */
+  id = "";
+
+  /**
+   * @generated from field: string name = 2;
+   */
+  name = "";
+
+  /**
+   * @generated from field: bool disabled = 3;
+   */
+  disabled = false;
+
+  /**
+   * sat/vB of its anchor transactions; 0 for the wallet's own
+   *
+   * @generated from field: uint32 fee_rate = 4;
+   */
+  feeRate = 0;
+
+  /**
+   * @generated from field: int64 created_at = 5;
+   */
+  createdAt = protoInt64.zero;
+
+  constructor(data?: PartialMessage<Tenant>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.Tenant";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "id", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "name", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 3, name: "disabled", kind: "scalar", T: 8 /* ScalarType.BOOL */ },
+    { no: 4, name: "fee_rate", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+    { no: 5, name: "created_at", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): Tenant {
+    return new Tenant().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): Tenant {
+    return new Tenant().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): Tenant {
+    return new Tenant().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: Tenant | PlainMessage<Tenant> | undefined, b: Tenant | PlainMessage<Tenant> | undefined): boolean {
+    return proto3.util.equals(Tenant, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.CreateTenantRequest
+ */
+export class CreateTenantRequest extends Message<CreateTenantRequest> {
+  /**
+   * lowercase letters, digits, '-' and '_'
+   *
+   * @generated from field: string id = 1;
+   */
+  id = "";
+
+  /**
+   * @generated from field: string name = 2;
+   */
+  name = "";
+
+  /**
+   * 0 for the wallet's own
+   *
+   * @generated from field: uint32 fee_rate = 3;
+   */
+  feeRate = 0;
+
+  constructor(data?: PartialMessage<CreateTenantRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.CreateTenantRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "id", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "name", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 3, name: "fee_rate", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): CreateTenantRequest {
+    return new CreateTenantRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): CreateTenantRequest {
+    return new CreateTenantRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): CreateTenantRequest {
+    return new CreateTenantRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: CreateTenantRequest | PlainMessage<CreateTenantRequest> | undefined, b: CreateTenantRequest | PlainMessage<CreateTenantRequest> | undefined): boolean {
+    return proto3.util.equals(CreateTenantRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.DisableTenantRequest
+ */
+export class DisableTenantRequest extends Message<DisableTenantRequest> {
+  /**
+   * @generated from field: string id = 1;
+   */
+  id = "";
+
+  constructor(data?: PartialMessage<DisableTenantRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.DisableTenantRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "id", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): DisableTenantRequest {
+    return new DisableTenantRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): DisableTenantRequest {
+    return new DisableTenantRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): DisableTenantRequest {
+    return new DisableTenantRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: DisableTenantRequest | PlainMessage<DisableTenantRequest> | undefined, b: DisableTenantRequest | PlainMessage<DisableTenantRequest> | undefined): boolean {
+    return proto3.util.equals(DisableTenantRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.EnableTenantRequest
+ */
+export class EnableTenantRequest extends Message<EnableTenantRequest> {
+  /**
+   * @generated from field: string id = 1;
+   */
+  id = "";
+
+  constructor(data?: PartialMessage<EnableTenantRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.EnableTenantRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "id", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): EnableTenantRequest {
+    return new EnableTenantRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): EnableTenantRequest {
+    return new EnableTenantRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): EnableTenantRequest {
+    return new EnableTenantRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: EnableTenantRequest | PlainMessage<EnableTenantRequest> | undefined, b: EnableTenantRequest | PlainMessage<EnableTenantRequest> | undefined): boolean {
+    return proto3.util.equals(EnableTenantRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ListTenantsRequest
+ */
+export class ListTenantsRequest extends Message<ListTenantsRequest> {
+  constructor(data?: PartialMessage<ListTenantsRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ListTenantsRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ListTenantsRequest {
+    return new ListTenantsRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ListTenantsRequest {
+    return new ListTenantsRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ListTenantsRequest {
+    return new ListTenantsRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ListTenantsRequest | PlainMessage<ListTenantsRequest> | undefined, b: ListTenantsRequest | PlainMessage<ListTenantsRequest> | undefined): boolean {
+    return proto3.util.equals(ListTenantsRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ListTenantsResponse
+ */
+export class ListTenantsResponse extends Message<ListTenantsResponse> {
+  /**
+   * @generated from field: repeated validblock.Tenant tenants = 1;
+   */
+  tenants: Tenant[] = [];
+
+  constructor(data?: PartialMessage<ListTenantsResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ListTenantsResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "tenants", kind: "message", T: Tenant, repeated: true },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ListTenantsResponse {
+    return new ListTenantsResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ListTenantsResponse {
+    return new ListTenantsResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ListTenantsResponse {
+    return new ListTenantsResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ListTenantsResponse | PlainMessage<ListTenantsResponse> | undefined, b: ListTenantsResponse | PlainMessage<ListTenantsResponse> | undefined): boolean {
+    return proto3.util.equals(ListTenantsResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.CreateTenantKeyRequest
+ */
+export class CreateTenantKeyRequest extends Message<CreateTenantKeyRequest> {
+  /**
+   * @generated from field: string tenant = 1;
+   */
+  tenant = "";
+
+  /**
+   * "viewer" or "anchorer"
+   *
+   * @generated from field: string role = 3;
+   */
+  role = "";
+
+  constructor(data?: PartialMessage<CreateTenantKeyRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.CreateTenantKeyRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "tenant", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 3, name: "role", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): CreateTenantKeyRequest {
+    return new CreateTenantKeyRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): CreateTenantKeyRequest {
+    return new CreateTenantKeyRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): CreateTenantKeyRequest {
+    return new CreateTenantKeyRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: CreateTenantKeyRequest | PlainMessage<CreateTenantKeyRequest> | undefined, b: CreateTenantKeyRequest | PlainMessage<CreateTenantKeyRequest> | undefined): boolean {
+    return proto3.util.equals(CreateTenantKeyRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.CreateTenantKeyResponse
+ */
+export class CreateTenantKeyResponse extends Message<CreateTenantKeyResponse> {
+  /**
+   * shown only this once
+   *
+   * @generated from field: string secret = 1;
+   */
+  secret = "";
+
+  constructor(data?: PartialMessage<CreateTenantKeyResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.CreateTenantKeyResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "secret", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): CreateTenantKeyResponse {
+    return new CreateTenantKeyResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): CreateTenantKeyResponse {
+    return new CreateTenantKeyResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): CreateTenantKeyResponse {
+    return new CreateTenantKeyResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: CreateTenantKeyResponse | PlainMessage<CreateTenantKeyResponse> | undefined, b: CreateTenantKeyResponse | PlainMessage<CreateTenantKeyResponse> | undefined): boolean {
+    return proto3.util.equals(CreateTenantKeyResponse, a, b);
+  }
+}
+
+/**
+ * An issued API key; the secret itself is never stored
+ *
+ * @generated from message validblock.ApiKeyInfo
+ */
+export class ApiKeyInfo extends Message<ApiKeyInfo> {
+  /**
+   * hex SHA-256 of the secret
+   *
+   * @generated from field: string id = 1;
+   */
+  id = "";
+
+  /**
+   * @generated from field: string owner = 2;
+   */
+  owner = "";
+
+  /**
+   * "viewer", "anchorer" or "admin"
+   *
+   * @generated from field: string role = 3;
+   */
+  role = "";
+
+  /**
+   * @generated from field: int64 created_at = 4;
+   */
+  createdAt = protoInt64.zero;
+
+  constructor(data?: PartialMessage<ApiKeyInfo>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ApiKeyInfo";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "id", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "owner", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 3, name: "role", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 4, name: "created_at", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ApiKeyInfo {
+    return new ApiKeyInfo().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ApiKeyInfo {
+    return new ApiKeyInfo().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ApiKeyInfo {
+    return new ApiKeyInfo().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ApiKeyInfo | PlainMessage<ApiKeyInfo> | undefined, b: ApiKeyInfo | PlainMessage<ApiKeyInfo> | undefined): boolean {
+    return proto3.util.equals(ApiKeyInfo, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.CreateKeyRequest
+ */
+export class CreateKeyRequest extends Message<CreateKeyRequest> {
+  /**
+   * empty for the default owner
+   *
+   * @generated from field: string owner = 1;
+   */
+  owner = "";
+
+  /**
+   * @generated from field: string role = 2;
+   */
+  role = "";
+
+  constructor(data?: PartialMessage<CreateKeyRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.CreateKeyRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "owner", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "role", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): CreateKeyRequest {
+    return new CreateKeyRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): CreateKeyRequest {
+    return new CreateKeyRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): CreateKeyRequest {
+    return new CreateKeyRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: CreateKeyRequest | PlainMessage<CreateKeyRequest> | undefined, b: CreateKeyRequest | PlainMessage<CreateKeyRequest> | undefined): boolean {
+    return proto3.util.equals(CreateKeyRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.CreateKeyResponse
+ */
+export class CreateKeyResponse extends Message<CreateKeyResponse> {
+  /**
+   * shown only this once
+   *
+   * @generated from field: string secret = 1;
+   */
+  secret = "";
+
+  /**
+   * @generated from field: validblock.ApiKeyInfo key = 2;
+   */
+  key?: ApiKeyInfo;
+
+  constructor(data?: PartialMessage<CreateKeyResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.CreateKeyResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "secret", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "key", kind: "message", T: ApiKeyInfo },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): CreateKeyResponse {
+    return new CreateKeyResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): CreateKeyResponse {
+    return new CreateKeyResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): CreateKeyResponse {
+    return new CreateKeyResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: CreateKeyResponse | PlainMessage<CreateKeyResponse> | undefined, b: CreateKeyResponse | PlainMessage<CreateKeyResponse> | undefined): boolean {
+    return proto3.util.equals(CreateKeyResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ListKeysRequest
+ */
+export class ListKeysRequest extends Message<ListKeysRequest> {
+  constructor(data?: PartialMessage<ListKeysRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ListKeysRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ListKeysRequest {
+    return new ListKeysRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ListKeysRequest {
+    return new ListKeysRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ListKeysRequest {
+    return new ListKeysRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ListKeysRequest | PlainMessage<ListKeysRequest> | undefined, b: ListKeysRequest | PlainMessage<ListKeysRequest> | undefined): boolean {
+    return proto3.util.equals(ListKeysRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ListKeysResponse
+ */
+export class ListKeysResponse extends Message<ListKeysResponse> {
+  /**
+   * @generated from field: repeated validblock.ApiKeyInfo keys = 1;
+   */
+  keys: ApiKeyInfo[] = [];
+
+  constructor(data?: PartialMessage<ListKeysResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ListKeysResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "keys", kind: "message", T: ApiKeyInfo, repeated: true },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ListKeysResponse {
+    return new ListKeysResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ListKeysResponse {
+    return new ListKeysResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ListKeysResponse {
+    return new ListKeysResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ListKeysResponse | PlainMessage<ListKeysResponse> | undefined, b: ListKeysResponse | PlainMessage<ListKeysResponse> | undefined): boolean {
+    return proto3.util.equals(ListKeysResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.RevokeKeyRequest
+ */
+export class RevokeKeyRequest extends Message<RevokeKeyRequest> {
+  /**
+   * @generated from field: string id = 1;
+   */
+  id = "";
+
+  constructor(data?: PartialMessage<RevokeKeyRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.RevokeKeyRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "id", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): RevokeKeyRequest {
+    return new RevokeKeyRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): RevokeKeyRequest {
+    return new RevokeKeyRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): RevokeKeyRequest {
+    return new RevokeKeyRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: RevokeKeyRequest | PlainMessage<RevokeKeyRequest> | undefined, b: RevokeKeyRequest | PlainMessage<RevokeKeyRequest> | undefined): boolean {
+    return proto3.util.equals(RevokeKeyRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.RevokeKeyResponse
+ */
+export class RevokeKeyResponse extends Message<RevokeKeyResponse> {
+  constructor(data?: PartialMessage<RevokeKeyResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.RevokeKeyResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): RevokeKeyResponse {
+    return new RevokeKeyResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): RevokeKeyResponse {
+    return new RevokeKeyResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): RevokeKeyResponse {
+    return new RevokeKeyResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: RevokeKeyResponse | PlainMessage<RevokeKeyResponse> | undefined, b: RevokeKeyResponse | PlainMessage<RevokeKeyResponse> | undefined): boolean {
+    return proto3.util.equals(RevokeKeyResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.WalletStatusRequest
+ */
+export class WalletStatusRequest extends Message<WalletStatusRequest> {
+  /**
+   * empty for the server's wallet
+   *
+   * @generated from field: string tenant = 1;
+   */
+  tenant = "";
+
+  constructor(data?: PartialMessage<WalletStatusRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.WalletStatusRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "tenant", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): WalletStatusRequest {
+    return new WalletStatusRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): WalletStatusRequest {
+    return new WalletStatusRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): WalletStatusRequest {
+    return new WalletStatusRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: WalletStatusRequest | PlainMessage<WalletStatusRequest> | undefined, b: WalletStatusRequest | PlainMessage<WalletStatusRequest> | undefined): boolean {
+    return proto3.util.equals(WalletStatusRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.WalletStatusResponse
+ */
+export class WalletStatusResponse extends Message<WalletStatusResponse> {
+  /**
+   * @generated from field: string network = 1;
+   */
+  network = "";
+
+  /**
+   * @generated from field: bool reachable = 2;
+   */
+  reachable = false;
+
+  /**
+   * why the backend didn't answer
+   *
+   * @generated from field: string error = 3;
+   */
+  error = "";
+
+  /**
+   * false for wallets without funds
+   *
+   * @generated from field: bool has_balance = 4;
+   */
+  hasBalance = false;
+
+  /**
+   * sats
+   *
+   * @generated from field: uint64 balance = 5;
+   */
+  balance = protoInt64.zero;
+
+  /**
+   * @generated from field: uint32 awaiting_signature = 6;
+   */
+  awaitingSignature = 0;
+
+  /**
+   * broadcast, not yet confirmed
+   *
+   * @generated from field: uint32 unconfirmed = 7;
+   */
+  unconfirmed = 0;
+
+  constructor(data?: PartialMessage<WalletStatusResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.WalletStatusResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "network", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "reachable", kind: "scalar", T: 8 /* ScalarType.BOOL */ },
+    { no: 3, name: "error", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 4, name: "has_balance", kind: "scalar", T: 8 /* ScalarType.BOOL */ },
+    { no: 5, name: "balance", kind: "scalar", T: 4 /* ScalarType.UINT64 */ },
+    { no: 6, name: "awaiting_signature", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+    { no: 7, name: "unconfirmed", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): WalletStatusResponse {
+    return new WalletStatusResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): WalletStatusResponse {
+    return new WalletStatusResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): WalletStatusResponse {
+    return new WalletStatusResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: WalletStatusResponse | PlainMessage<WalletStatusResponse> | undefined, b: WalletStatusResponse | PlainMessage<WalletStatusResponse> | undefined): boolean {
+    return proto3.util.equals(WalletStatusResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.MaintenanceRequest
+ */
+export class MaintenanceRequest extends Message<MaintenanceRequest> {
+  constructor(data?: PartialMessage<MaintenanceRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.MaintenanceRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): MaintenanceRequest {
+    return new MaintenanceRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): MaintenanceRequest {
+    return new MaintenanceRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): MaintenanceRequest {
+    return new MaintenanceRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: MaintenanceRequest | PlainMessage<MaintenanceRequest> | undefined, b: MaintenanceRequest | PlainMessage<MaintenanceRequest> | undefined): boolean {
+    return proto3.util.equals(MaintenanceRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.MaintenanceResponse
+ */
+export class MaintenanceResponse extends Message<MaintenanceResponse> {
+  /**
+   * database size afterwards
+   *
+   * @generated from field: uint64 size_bytes = 1;
+   */
+  sizeBytes = protoInt64.zero;
+
+  constructor(data?: PartialMessage<MaintenanceResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.MaintenanceResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "size_bytes", kind: "scalar", T: 4 /* ScalarType.UINT64 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): MaintenanceResponse {
+    return new MaintenanceResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): MaintenanceResponse {
+    return new MaintenanceResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): MaintenanceResponse {
+    return new MaintenanceResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: MaintenanceResponse | PlainMessage<MaintenanceResponse> | undefined, b: MaintenanceResponse | PlainMessage<MaintenanceResponse> | undefined): boolean {
+    return proto3.util.equals(MaintenanceResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.IntegrityCheckResponse
+ */
+export class IntegrityCheckResponse extends Message<IntegrityCheckResponse> {
+  /**
+   * @generated from field: bool ok = 1;
+   */
+  ok = false;
+
+  /**
+   * @generated from field: repeated string problems = 2;
+   */
+  problems: string[] = [];
+
+  constructor(data?: PartialMessage<IntegrityCheckResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.IntegrityCheckResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "ok", kind: "scalar", T: 8 /* ScalarType.BOOL */ },
+    { no: 2, name: "problems", kind: "scalar", T: 9 /* ScalarType.STRING */, repeated: true },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): IntegrityCheckResponse {
+    return new IntegrityCheckResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): IntegrityCheckResponse {
+    return new IntegrityCheckResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): IntegrityCheckResponse {
+    return new IntegrityCheckResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: IntegrityCheckResponse | PlainMessage<IntegrityCheckResponse> | undefined, b: IntegrityCheckResponse | PlainMessage<IntegrityCheckResponse> | undefined): boolean {
+    return proto3.util.equals(IntegrityCheckResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ReloadConfigRequest
+ */
+export class ReloadConfigRequest extends Message<ReloadConfigRequest> {
+  constructor(data?: PartialMessage<ReloadConfigRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ReloadConfigRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ReloadConfigRequest {
+    return new ReloadConfigRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ReloadConfigRequest {
+    return new ReloadConfigRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ReloadConfigRequest {
+    return new ReloadConfigRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ReloadConfigRequest | PlainMessage<ReloadConfigRequest> | undefined, b: ReloadConfigRequest | PlainMessage<ReloadConfigRequest> | undefined): boolean {
+    return proto3.util.equals(ReloadConfigRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ReloadConfigResponse
+ */
+export class ReloadConfigResponse extends Message<ReloadConfigResponse> {
+  /**
+   * keys accepted after the reload
+   *
+   * @generated from field: uint32 api_keys = 1;
+   */
+  apiKeys = 0;
+
+  constructor(data?: PartialMessage<ReloadConfigResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ReloadConfigResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "api_keys", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ReloadConfigResponse {
+    return new ReloadConfigResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ReloadConfigResponse {
+    return new ReloadConfigResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ReloadConfigResponse {
+    return new ReloadConfigResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ReloadConfigResponse | PlainMessage<ReloadConfigResponse> | undefined, b: ReloadConfigResponse | PlainMessage<ReloadConfigResponse> | undefined): boolean {
+    return proto3.util.equals(ReloadConfigResponse, a, b);
+  }
+}
+
+/**
+ * One entry of the audit trail
+ *
+ * @generated from message validblock.AuditEntry
+ */
+export class AuditEntry extends Message<AuditEntry> {
+  /**
+   * @generated from field: int64 seq = 1;
+   */
+  seq = protoInt64.zero;
+
+  /**
+   * @generated from field: int64 timestamp = 2;
+   */
+  timestamp = protoInt64.zero;
+
+  /**
+   * owner the caller acted as; empty when unknown
+   *
+   * @generated from field: string actor = 3;
+   */
+  actor = "";
+
+  /**
+   * "token" or "key:<id prefix>"
+   *
+   * @generated from field: string credential = 4;
+   */
+  credential = "";
+
+  /**
+   * peer address
+   *
+   * @generated from field: string source = 5;
+   */
+  source = "";
+
+  /**
+   * e.g. "AnchorService/Anchor"
+   *
+   * @generated from field: string action = 6;
+   */
+  action = "";
+
+  /**
+   * digest, tenant or key acted on, when known
+   *
+   * @generated from field: string target = 7;
+   */
+  target = "";
+
+  /**
+   * "OK" or the gRPC status code name
+   *
+   * @generated from field: string outcome = 8;
+   */
+  outcome = "";
+
+  /**
+   * hex chain hash over the previous entry's
+   *
+   * @generated from field: string hash = 9;
+   */
+  hash = "";
+
+  constructor(data?: PartialMessage<AuditEntry>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.AuditEntry";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "seq", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+    { no: 2, name: "timestamp", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+    { no: 3, name: "actor", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 4, name: "credential", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 5, name: "source", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 6, name: "action", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 7, name: "target", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 8, name: "outcome", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 9, name: "hash", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): AuditEntry {
+    return new AuditEntry().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): AuditEntry {
+    return new AuditEntry().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): AuditEntry {
+    return new AuditEntry().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: AuditEntry | PlainMessage<AuditEntry> | undefined, b: AuditEntry | PlainMessage<AuditEntry> | undefined): boolean {
+    return proto3.util.equals(AuditEntry, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ListAuditRequest
+ */
+export class ListAuditRequest extends Message<ListAuditRequest> {
+  /**
+   * @generated from field: string actor = 1;
+   */
+  actor = "";
+
+  /**
+   * @generated from field: string action = 2;
+   */
+  action = "";
+
+  /**
+   * unix seconds, inclusive; 0 for no bound
+   *
+   * @generated from field: int64 since = 3;
+   */
+  since = protoInt64.zero;
+
+  /**
+   * unix seconds, exclusive; 0 for no bound
+   *
+   * @generated from field: int64 until = 4;
+   */
+  until = protoInt64.zero;
+
+  /**
+   * 0 for the default page size
+   *
+   * @generated from field: uint32 limit = 5;
+   */
+  limit = 0;
+
+  /**
+   * @generated from field: uint32 offset = 6;
+   */
+  offset = 0;
+
+  constructor(data?: PartialMessage<ListAuditRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ListAuditRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "actor", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "action", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 3, name: "since", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+    { no: 4, name: "until", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+    { no: 5, name: "limit", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+    { no: 6, name: "offset", kind: "scalar", T: 13 /* ScalarType.UINT32 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ListAuditRequest {
+    return new ListAuditRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ListAuditRequest {
+    return new ListAuditRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ListAuditRequest {
+    return new ListAuditRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ListAuditRequest | PlainMessage<ListAuditRequest> | undefined, b: ListAuditRequest | PlainMessage<ListAuditRequest> | undefined): boolean {
+    return proto3.util.equals(ListAuditRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ListAuditResponse
+ */
+export class ListAuditResponse extends Message<ListAuditResponse> {
+  /**
+   * @generated from field: repeated validblock.AuditEntry entries = 1;
+   */
+  entries: AuditEntry[] = [];
+
+  constructor(data?: PartialMessage<ListAuditResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ListAuditResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "entries", kind: "message", T: AuditEntry, repeated: true },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ListAuditResponse {
+    return new ListAuditResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ListAuditResponse {
+    return new ListAuditResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ListAuditResponse {
+    return new ListAuditResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ListAuditResponse | PlainMessage<ListAuditResponse> | undefined, b: ListAuditResponse | PlainMessage<ListAuditResponse> | undefined): boolean {
+    return proto3.util.equals(ListAuditResponse, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.ExportAuditRequest
+ */
+export class ExportAuditRequest extends Message<ExportAuditRequest> {
+  /**
+   * @generated from field: int64 since = 1;
+   */
+  since = protoInt64.zero;
+
+  /**
+   * @generated from field: int64 until = 2;
+   */
+  until = protoInt64.zero;
+
+  constructor(data?: PartialMessage<ExportAuditRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ExportAuditRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "since", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+    { no: 2, name: "until", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ExportAuditRequest {
+    return new ExportAuditRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ExportAuditRequest {
+    return new ExportAuditRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ExportAuditRequest {
+    return new ExportAuditRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ExportAuditRequest | PlainMessage<ExportAuditRequest> | undefined, b: ExportAuditRequest | PlainMessage<ExportAuditRequest> | undefined): boolean {
+    return proto3.util.equals(ExportAuditRequest, a, b);
+  }
+}
+
+/**
+ * A piece of the export: JSON lines, one entry per line
+ *
+ * @generated from message validblock.ExportAuditChunk
+ */
+export class ExportAuditChunk extends Message<ExportAuditChunk> {
+  /**
+   * @generated from field: bytes data = 1;
+   */
+  data = new Uint8Array(0);
+
+  constructor(data?: PartialMessage<ExportAuditChunk>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ExportAuditChunk";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "data", kind: "scalar", T: 12 /* ScalarType.BYTES */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ExportAuditChunk {
+    return new ExportAuditChunk().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ExportAuditChunk {
+    return new ExportAuditChunk().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ExportAuditChunk {
+    return new ExportAuditChunk().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ExportAuditChunk | PlainMessage<ExportAuditChunk> | undefined, b: ExportAuditChunk | PlainMessage<ExportAuditChunk> | undefined): boolean {
+    return proto3.util.equals(ExportAuditChunk, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.VerifyAuditRequest
+ */
+export class VerifyAuditRequest extends Message<VerifyAuditRequest> {
+  constructor(data?: PartialMessage<VerifyAuditRequest>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.VerifyAuditRequest";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): VerifyAuditRequest {
+    return new VerifyAuditRequest().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): VerifyAuditRequest {
+    return new VerifyAuditRequest().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): VerifyAuditRequest {
+    return new VerifyAuditRequest().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: VerifyAuditRequest | PlainMessage<VerifyAuditRequest> | undefined, b: VerifyAuditRequest | PlainMessage<VerifyAuditRequest> | undefined): boolean {
+    return proto3.util.equals(VerifyAuditRequest, a, b);
+  }
+}
+
+/**
+ * @generated from message validblock.VerifyAuditResponse
+ */
+export class VerifyAuditResponse extends Message<VerifyAuditResponse> {
+  /**
+   * @generated from field: bool ok = 1;
+   */
+  ok = false;
+
+  /**
+   * @generated from field: uint64 entries = 2;
+   */
+  entries = protoInt64.zero;
+
+  /**
+   * 0 when ok
+   *
+   * @generated from field: int64 first_bad_seq = 3;
+   */
+  firstBadSeq = protoInt64.zero;
+
+  /**
+   * hash of the latest entry, to record elsewhere
+   *
+   * @generated from field: string head = 4;
+   */
+  head = "";
+
+  constructor(data?: PartialMessage<VerifyAuditResponse>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.VerifyAuditResponse";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "ok", kind: "scalar", T: 8 /* ScalarType.BOOL */ },
+    { no: 2, name: "entries", kind: "scalar", T: 4 /* ScalarType.UINT64 */ },
+    { no: 3, name: "first_bad_seq", kind: "scalar", T: 3 /* ScalarType.INT64 */ },
+    { no: 4, name: "head", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): VerifyAuditResponse {
+    return new VerifyAuditResponse().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): VerifyAuditResponse {
+    return new VerifyAuditResponse().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): VerifyAuditResponse {
+    return new VerifyAuditResponse().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: VerifyAuditResponse | PlainMessage<VerifyAuditResponse> | undefined, b: VerifyAuditResponse | PlainMessage<VerifyAuditResponse> | undefined): boolean {
+    return proto3.util.equals(VerifyAuditResponse, a, b);
+  }
+}
+
+/**
+ * Carried in grpc-status-details-bin on every error status
+ *
+ * @generated from message validblock.ErrorDetail
+ */
+export class ErrorDetail extends Message<ErrorDetail> {
+  /**
+   * stable code, e.g. "DUPLICATE_DIGEST"
+   *
+   * @generated from field: string code = 1;
+   */
+  code = "";
+
+  /**
+   * @generated from field: string message = 2;
+   */
+  message = "";
+
+  constructor(data?: PartialMessage<ErrorDetail>) {
+    super();
+    proto3.util.initPartial(data, this);
+  }
+
+  static readonly runtime: typeof proto3 = proto3;
+  static readonly typeName = "validblock.ErrorDetail";
+  static readonly fields: FieldList = proto3.util.newFieldList(() => [
+    { no: 1, name: "code", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+    { no: 2, name: "message", kind: "scalar", T: 9 /* ScalarType.STRING */ },
+  ]);
+
+  static fromBinary(bytes: Uint8Array, options?: Partial<BinaryReadOptions>): ErrorDetail {
+    return new ErrorDetail().fromBinary(bytes, options);
+  }
+
+  static fromJson(jsonValue: JsonValue, options?: Partial<JsonReadOptions>): ErrorDetail {
+    return new ErrorDetail().fromJson(jsonValue, options);
+  }
+
+  static fromJsonString(jsonString: string, options?: Partial<JsonReadOptions>): ErrorDetail {
+    return new ErrorDetail().fromJsonString(jsonString, options);
+  }
+
+  static equals(a: ErrorDetail | PlainMessage<ErrorDetail> | undefined, b: ErrorDetail | PlainMessage<ErrorDetail> | undefined): boolean {
+    return proto3.util.equals(ErrorDetail, a, b);
+  }
+}
+
@@ -1,9 +1,19 @@
 import { createConnectTransport } from "@connectrpc/connect-web";
-import { createPromiseClient } from "@connectrpc/connect";
+import { createPromiseClient, type Interceptor } from "@connectrpc/connect";
+import { invoke } from "@tauri-apps/api/core";
 import { AnchorService, VerifyService } from "../gen/proto/validblock_connect";
 import { RPC_BASE_URL } from "./config";
 
-const transport = createConnectTransport({ baseUrl: RPC_BASE_URL });
+// The proxy only answers calls carrying the token the app spawned it with
+let bearerToken: Promise<string> | undefined;
+
+const authorize: Interceptor = (next) => async (req) => {
+  bearerToken ??= invoke<string>("get_bearer_token");
+  req.header.set("Authorization", `Bearer ${await bearerToken}`);
+  return next(req);
+};
+
+const transport = createConnectTransport({ baseUrl: RPC_BASE_URL, interceptors: [authorize] });
 
 export const anchorClient = createPromiseClient(AnchorService, transport);
 export const verifyClient = createPromiseClient(VerifyService, transport);
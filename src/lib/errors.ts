@@ -0,0 +1,41 @@
+import { ConnectError } from "@connectrpc/connect";
+import { ErrorDetail } from "../gen/proto/validblock_pb";
+
+// Stable codes sent by the server in ErrorDetail.code
+export type ErrorCode =
+  | "IO"
+  | "INVALID_DIGEST"
+  | "WALLET"
+  | "DATABASE"
+  | "DUPLICATE_DIGEST"
+  | "INVALID_PAYLOAD"
+  | "INVALID_ARGUMENT"
+  | "NOT_FOUND"
+  | "UNAUTHENTICATED"
+  | "PERMISSION_DENIED"
+  | "RESOURCE_EXHAUSTED"
+  | "INTERNAL";
+
+export function errorCode(err: unknown): ErrorCode | undefined {
+  const [detail] = ConnectError.from(err).findDetails(ErrorDetail);
+  return detail?.code as ErrorCode | undefined;
+}
+
+export function errorMessage(err: unknown): string {
+  switch (errorCode(err)) {
+    case "DUPLICATE_DIGEST":
+      return "Already anchored. Try again with a different file.";
+    case "INVALID_DIGEST":
+      return "Invalid digest.";
+    case "NOT_FOUND":
+      return "No anchor found.";
+    case "UNAUTHENTICATED":
+      return "Not signed in to the ValidBlock server.";
+    case "PERMISSION_DENIED":
+      return "This API key may not perform that action.";
+    case "RESOURCE_EXHAUSTED":
+      return "Rate limit or quota reached. Try again later.";
+    default:
+      return ConnectError.from(err).rawMessage;
+  }
+}
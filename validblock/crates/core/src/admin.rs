@@ -0,0 +1,100 @@
+//! Server administration behind `AdminService`, which takes the admin role:
+//! API key management, wallet status, database maintenance and reloading the
+//! configuration.
+//!
+//! A reload re-reads the configuration the server started from (its file,
+//! environment and flags) and applies what can change while serving: the
+//! bearer token, the API keys file and the rate limits and quotas. Listen
+//! addresses, TLS, the wallet and the upload cap take a restart.
+
+use validblock_storage::AnchorRepo;
+use validblock_types::{AnchorStatus, VBError};
+use validblock_wallet::WalletAdapter;
+
+use crate::auth::Authenticator;
+use crate::config::ServerConfig;
+use crate::limits::Limits;
+use crate::AnchorEngine;
+
+/// A wallet backend's health and the anchors it has in flight
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WalletStatus {
+  /// Why the backend didn't answer, if it didn't
+  pub error: Option<String>,
+  /// Satoshis held, `None` for wallets without funds
+  pub balance: Option<u64>,
+  pub awaiting_signature: u32,
+  /// Broadcast, not yet confirmed
+  pub unconfirmed: u32,
+}
+
+/// Status of the wallet funding `owner`'s anchors. Chain lookups block
+pub fn wallet_status<W: WalletAdapter>(engine: &AnchorEngine<W>, owner: &str) -> Result<WalletStatus, VBError> {
+  // the server's wallet funds everyone without a wallet of their own
+  let own_wallet = engine.has_own_wallet(owner);
+  let funded_by = |rec_owner: &str| match own_wallet {
+    true => rec_owner == owner,
+    false => !engine.has_own_wallet(rec_owner),
+  };
+  let count = |status| -> Result<u32, VBError> {
+    Ok(engine.repo.with_status(status)?.iter().filter(|rec| funded_by(&rec.owner)).count() as u32)
+  };
+  let awaiting_signature = count(AnchorStatus::AwaitingSignature)?;
+  let unconfirmed = count(AnchorStatus::Broadcast)?;
+
+  let mut wallet = engine.wallet_of(owner);
+  let (error, balance) = match wallet.ping().and_then(|()| wallet.balance()) {
+    Ok(balance) => (None, balance),
+    Err(e) => (Some(e.to_string()), None),
+  };
+  Ok(WalletStatus { error, balance, awaiting_signature, unconfirmed })
+}
+
+/// Re-read the configuration from `args`, the environment and the file they
+/// name, and apply its credentials and limits
+pub fn reload(args: &[String], auth: &Authenticator, limits: &Limits, repo: &AnchorRepo) -> Result<(), VBError> {
+  let config = ServerConfig::default().load(args.iter().cloned())?;
+  auth.reload(&config.auth, repo)?;
+  limits.reload(config.limits);
+  Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use validblock_types::{AnchorRecord, Digest256};
+  use validblock_wallet::mock::MockWallet;
+
+  #[test]
+  fn test_wallet_status_counts_its_own_anchors() {
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet).with_tenant_wallet("hr", MockWallet);
+    for (i, (owner, status)) in [
+      ("acme", AnchorStatus::Broadcast),
+      ("acme", AnchorStatus::AwaitingSignature),
+      ("hr", AnchorStatus::Broadcast),
+      ("acme", AnchorStatus::Confirmed),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+      engine.repo.insert(&AnchorRecord {
+        digest: Digest256([i as u8; 32]),
+        owner: owner.into(),
+        ts: 1,
+        memo: None,
+        txid: Some(format!("tx{}", i)),
+        status,
+        txid_history: vec![],
+      }).unwrap();
+    }
+
+    let status = wallet_status(&engine, "acme").unwrap();
+    assert_eq!((status.awaiting_signature, status.unconfirmed, status.error), (1, 1, None));
+    let status = wallet_status(&engine, "hr").unwrap();
+    assert_eq!((status.awaiting_signature, status.unconfirmed), (0, 1));
+  }
+}
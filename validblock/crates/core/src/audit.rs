@@ -0,0 +1,260 @@
+//! Audit trail: who did what, from where, and how it went
+//!
+//! [`AuditLayer`] wraps the whole server, so every gRPC, gRPC-Web and REST
+//! call to a ValidBlock service is recorded once it completes: anchors,
+//! verifications and existence checks, tenant changes, config reloads and
+//! every other admin action. Health checks and the OpenAPI document are not.
+//! Each entry names the owner and credential the caller presented, its peer
+//! address, the action, what it acted on when the handler says so (see
+//! [`targeted`]), the outcome and the time.
+//!
+//! Entries live in the database, each hashed together with the hash of the
+//! one before it (see [`validblock_storage::audit_hash`]), so altering,
+//! inserting or removing an entry breaks the chain from there on.
+//! `AdminService` lists, exports and verifies the trail.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::{Method, StatusCode};
+use serde_json::json;
+use tonic::body::BoxBody;
+use tonic::codegen::{BoxFuture, StdError};
+use tonic::metadata::MetadataMap;
+use tonic::transport::server::TcpConnectInfo;
+use tonic::transport::Body;
+use tonic::Code;
+use tower::{Layer, Service};
+use tracing::error;
+use validblock_types::Digest256;
+use validblock_wallet::WalletAdapter;
+
+use crate::auth::Authenticator;
+use crate::AsyncEngine;
+
+pub use validblock_storage::{audit_hash, AuditEntry, AuditFilter};
+
+/// Response extension naming what a call acted on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditTarget(pub String);
+
+/// `message` as a response whose audit entry names `target`
+pub fn targeted<T>(message: T, target: impl ToString) -> tonic::Response<T> {
+  let mut response = tonic::Response::new(message);
+  response.extensions_mut().insert(AuditTarget(target.to_string()));
+  response
+}
+
+/// Action a request is audited as, with the target its path names; `None`
+/// for requests that aren't audited. REST routes are recorded as the RPC
+/// they stand for
+pub fn action_of(method: &Method, path: &str) -> Option<(String, Option<String>)> {
+  if let Some(rpc) = path.strip_prefix("/validblock.") {
+    return Some((rpc.to_string(), None));
+  }
+  let rpc = match (method, path) {
+    (&Method::POST, "/anchors") => "AnchorService/Anchor",
+    (&Method::GET, "/anchors") => "VerifyService/ListAnchors",
+    (&Method::POST, "/verify") => "VerifyService/Verify",
+    (&Method::GET, _) => {
+      let digest = path.strip_prefix("/anchors/")?;
+      return Some(("VerifyService/ExistDigest".to_string(), Some(digest.to_string())));
+    }
+    _ => return None,
+  };
+  Some((rpc.to_string(), None))
+}
+
+/// Canonical name of a gRPC status code
+pub fn code_name(code: Code) -> &'static str {
+  match code {
+    Code::Ok => "OK",
+    Code::Cancelled => "CANCELLED",
+    Code::Unknown => "UNKNOWN",
+    Code::InvalidArgument => "INVALID_ARGUMENT",
+    Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+    Code::NotFound => "NOT_FOUND",
+    Code::AlreadyExists => "ALREADY_EXISTS",
+    Code::PermissionDenied => "PERMISSION_DENIED",
+    Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+    Code::FailedPrecondition => "FAILED_PRECONDITION",
+    Code::Aborted => "ABORTED",
+    Code::OutOfRange => "OUT_OF_RANGE",
+    Code::Unimplemented => "UNIMPLEMENTED",
+    Code::Internal => "INTERNAL",
+    Code::Unavailable => "UNAVAILABLE",
+    Code::DataLoss => "DATA_LOSS",
+    Code::Unauthenticated => "UNAUTHENTICATED",
+  }
+}
+
+/// Outcome of a call from its response head: the `grpc-status` of a gRPC
+/// reply, which errors carry in the headers, or the gRPC code matching a
+/// REST reply's HTTP status
+fn outcome<B>(response: &http::Response<B>, grpc: bool) -> &'static str {
+  if grpc {
+    let status = response.headers().get("grpc-status").and_then(|value| value.to_str().ok()?.parse().ok());
+    return code_name(status.map_or(Code::Ok, Code::from_i32));
+  }
+  code_name(match response.status() {
+    status if status.is_success() => Code::Ok,
+    StatusCode::BAD_REQUEST => Code::InvalidArgument,
+    StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+    StatusCode::FORBIDDEN => Code::PermissionDenied,
+    StatusCode::NOT_FOUND => Code::NotFound,
+    StatusCode::CONFLICT => Code::AlreadyExists,
+    StatusCode::PAYLOAD_TOO_LARGE | StatusCode::TOO_MANY_REQUESTS => Code::ResourceExhausted,
+    StatusCode::SERVICE_UNAVAILABLE => Code::Unavailable,
+    _ => Code::Internal,
+  })
+}
+
+/// One exported entry, as a line of JSON
+pub fn export_line(entry: &AuditEntry) -> String {
+  json!({
+    "seq": entry.seq,
+    "ts": entry.ts,
+    "actor": entry.actor,
+    "credential": entry.credential,
+    "source": entry.source,
+    "action": entry.action,
+    "target": entry.target,
+    "outcome": entry.outcome,
+    "hash": entry.hash.to_string(),
+  })
+  .to_string()
+}
+
+// ============================================================================
+// Layer
+// ============================================================================
+
+/// Records every audited call in `engine`'s database
+pub struct AuditLayer<W: WalletAdapter> {
+  engine: AsyncEngine<W>,
+  auth: Arc<Authenticator>,
+}
+
+impl<W: WalletAdapter> AuditLayer<W> {
+  pub fn new(engine: AsyncEngine<W>, auth: Arc<Authenticator>) -> Self {
+    AuditLayer { engine, auth }
+  }
+}
+
+impl<W: WalletAdapter> Clone for AuditLayer<W> {
+  fn clone(&self) -> Self {
+    AuditLayer { engine: self.engine.clone(), auth: self.auth.clone() }
+  }
+}
+
+impl<S, W: WalletAdapter> Layer<S> for AuditLayer<W> {
+  type Service = AuditService<S, W>;
+
+  fn layer(&self, inner: S) -> Self::Service {
+    AuditService { inner, layer: self.clone() }
+  }
+}
+
+pub struct AuditService<S, W: WalletAdapter> {
+  inner: S,
+  layer: AuditLayer<W>,
+}
+
+impl<S: Clone, W: WalletAdapter> Clone for AuditService<S, W> {
+  fn clone(&self) -> Self {
+    AuditService { inner: self.inner.clone(), layer: self.layer.clone() }
+  }
+}
+
+impl<S, W> Service<http::Request<Body>> for AuditService<S, W>
+where
+  S: Service<http::Request<Body>, Response = http::Response<BoxBody>> + Send + 'static,
+  S::Future: Send + 'static,
+  S::Error: Into<StdError>,
+  W: WalletAdapter + Send + 'static,
+{
+  type Response = http::Response<BoxBody>;
+  type Error = StdError;
+  type Future = BoxFuture<Self::Response, StdError>;
+
+  fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready(cx).map_err(Into::into)
+  }
+
+  fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+    let Some((action, path_target)) = action_of(request.method(), request.uri().path()) else {
+      let response = self.inner.call(request);
+      return Box::pin(async move { response.await.map_err(Into::into) });
+    };
+    let ts = chrono::Utc::now().timestamp();
+    let source = request
+      .extensions()
+      .get::<TcpConnectInfo>()
+      .and_then(TcpConnectInfo::remote_addr)
+      .map(|addr| addr.to_string())
+      .unwrap_or_default();
+    let (actor, credential) =
+      self.layer.auth.identify(&MetadataMap::from_headers(request.headers().clone())).unwrap_or_default();
+    let grpc = request
+      .headers()
+      .get(http::header::CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .is_some_and(|value| value.starts_with("application/grpc"));
+
+    let response = self.inner.call(request);
+    let engine = self.layer.engine.clone();
+    Box::pin(async move {
+      let response = response.await.map_err(Into::into)?;
+      let target = response.extensions().get::<AuditTarget>().map(|target| target.0.clone()).or(path_target);
+      let entry = AuditEntry {
+        seq: 0,
+        ts,
+        actor,
+        credential,
+        source,
+        action,
+        target: target.unwrap_or_default(),
+        outcome: outcome(&response, grpc).to_string(),
+        hash: Digest256([0; 32]),
+      };
+      // the call has happened either way; a lost entry shows in the logs
+      let action = entry.action.clone();
+      if let Err(e) = engine.run(move |engine| engine.repo.append_audit(&entry)).await {
+        error!(action = %action, error = %e, "Could not write the audit trail");
+      }
+      Ok(response)
+    })
+  }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_action_of() {
+    let action = |method: Method, path: &str| action_of(&method, path);
+    assert_eq!(action(Method::POST, "/validblock.AnchorService/Anchor"), Some(("AnchorService/Anchor".into(), None)));
+    assert_eq!(action(Method::POST, "/anchors"), Some(("AnchorService/Anchor".into(), None)));
+    assert_eq!(
+      action(Method::GET, "/anchors/ab12"),
+      Some(("VerifyService/ExistDigest".into(), Some("ab12".into())))
+    );
+    assert_eq!(action(Method::POST, "/grpc.health.v1.Health/Check"), None);
+    assert_eq!(action(Method::GET, "/openapi.json"), None);
+    assert_eq!(action(Method::OPTIONS, "/anchors"), None);
+  }
+
+  #[test]
+  fn test_outcome() {
+    let grpc = http::Response::builder().header("grpc-status", "7").body(()).unwrap();
+    assert_eq!(outcome(&grpc, true), "PERMISSION_DENIED");
+    assert_eq!(outcome(&http::Response::new(()), true), "OK");
+    let rest = http::Response::builder().status(StatusCode::NOT_FOUND).body(()).unwrap();
+    assert_eq!(outcome(&rest, false), "NOT_FOUND");
+  }
+}
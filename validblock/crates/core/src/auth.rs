@@ -0,0 +1,451 @@
+//! Bearer-token and API-key authentication and role checks for the gRPC
+//! services
+//!
+//! Every call must carry `authorization: Bearer <secret>`. The secret is
+//! either the server's bearer token, which acts for `DEFAULT_OWNER` as an
+//! admin, or an API key. Each key has a role, and each service requires one:
+//! viewers may call `VerifyService`, anchorers `AnchorService` as well, and
+//! admins everything, including `TenantService` and `AdminService`. Keys
+//! listed in the keys file are stored as SHA-256 digests, one per line:
+//!
+//! ```text
+//! # digest                                                          owner  role
+//! 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08  acme   anchorer
+//! ```
+//!
+//! The scope lists of earlier releases (`anchor,verify`, `verify`) still read
+//! as the matching role. Keys issued through `TenantService` or `AdminService`
+//! live in the database; a disabled tenant's keys are refused.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use rand::RngCore;
+use tonic::metadata::MetadataMap;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use validblock_hasher::hash_reader;
+use validblock_storage::AnchorRepo;
+use validblock_types::{Digest256, VBError, DEFAULT_OWNER};
+
+use crate::config::AuthConfig;
+use crate::services::Owner;
+use crate::status::to_status;
+
+pub use validblock_storage::KeyRecord;
+
+/// Environment variable holding the server's bearer token
+pub const AUTH_TOKEN_ENV: &str = "VALIDBLOCK_AUTH_TOKEN";
+
+/// Environment variable naming the API keys file
+pub const API_KEYS_ENV: &str = "VALIDBLOCK_API_KEYS";
+
+/// Request header carrying the credentials
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+
+/// Random bytes in an issued API key
+const SECRET_BYTES: usize = 32;
+
+/// Hex digits of a key's digest naming it, see [`key_id`]
+const KEY_ID_PREFIX: usize = 16;
+
+/// What a credential may do; each role includes the ones before it
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum Role {
+  /// Look anchors up: `VerifyService`
+  Viewer,
+  /// Anchor files and handle their PSBTs: `AnchorService`
+  Anchorer,
+  /// Administer the server: `TenantService` and `AdminService`
+  Admin,
+}
+
+impl Role {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Role::Viewer => "viewer",
+      Role::Anchorer => "anchorer",
+      Role::Admin => "admin",
+    }
+  }
+}
+
+impl fmt::Display for Role {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for Role {
+  type Err = VBError;
+  /// A role name, or a legacy comma-separated scope list
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "viewer" => return Ok(Role::Viewer),
+      "anchorer" => return Ok(Role::Anchorer),
+      "admin" => return Ok(Role::Admin),
+      _ => {}
+    }
+    s.split(',')
+      .map(|scope| match scope {
+        "verify" => Ok(Role::Viewer),
+        "anchor" => Ok(Role::Anchorer),
+        other => Err(VBError::InvalidArgument(format!("Unknown role: {}", other))),
+      })
+      .try_fold(Role::Viewer, |role, scope| scope.map(|scope| role.max(scope)))
+  }
+}
+
+/// An API key as the server knows it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApiKey {
+  pub owner: String,
+  pub role: Role,
+}
+
+/// A fresh API key secret
+pub fn generate_secret() -> String {
+  let mut bytes = [0u8; SECRET_BYTES];
+  rand::rngs::OsRng.fill_bytes(&mut bytes);
+  format!("vb_{}", bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+/// How a key is named in the audit trail: a prefix of its digest
+pub fn key_id(digest: &Digest256) -> String {
+  format!("key:{}", &digest.to_string()[..KEY_ID_PREFIX])
+}
+
+/// Digest under which a secret is stored and looked up
+pub fn hash_secret(secret: &str) -> Digest256 {
+  hash_reader(secret.as_bytes()).expect("hashing a byte slice cannot fail")
+}
+
+/// Credentials the server accepts
+#[derive(Debug, Default)]
+pub struct Authenticator {
+  token: RwLock<Option<Digest256>>,
+  keys: RwLock<HashMap<Digest256, ApiKey>>,
+  /// Tenants whose keys are refused
+  disabled: RwLock<HashSet<String>>,
+}
+
+impl Authenticator {
+  /// Accept `token` as the bearer token
+  pub fn with_token(self, token: &str) -> Self {
+    *self.token.write().unwrap() = Some(hash_secret(token));
+    self
+  }
+
+  /// Accept the secret hashing to `digest` as an API key
+  pub fn with_key(self, digest: Digest256, key: ApiKey) -> Self {
+    self.add_key(digest, key);
+    self
+  }
+
+  /// Accept every key listed in a keys file
+  pub fn with_keys_file<P: AsRef<Path>>(self, path: P) -> Result<Self, VBError> {
+    let text = std::fs::read_to_string(path)?;
+    for (n, line) in text.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let bad_line = || VBError::InvalidArgument(format!("API keys file line {}: expected digest, owner, role", n + 1));
+      let mut fields = line.split_whitespace();
+      let (Some(digest), Some(owner), Some(role), None) = (fields.next(), fields.next(), fields.next(), fields.next())
+      else {
+        return Err(bad_line());
+      };
+      self.add_key(digest.parse()?, ApiKey { owner: owner.to_string(), role: role.parse()? });
+    }
+    Ok(self)
+  }
+
+  /// Accept the keys stored in `repo`, refusing disabled tenants'
+  pub fn with_repo(self, repo: &AnchorRepo) -> Result<Self, VBError> {
+    for tenant in repo.tenants()? {
+      self.set_disabled(&tenant.id, tenant.disabled);
+    }
+    for key in repo.keys()? {
+      self.add_key(key.digest, ApiKey { owner: key.owner, role: key.role.parse()? });
+    }
+    Ok(self)
+  }
+
+  /// Start accepting the secret hashing to `digest`
+  pub fn add_key(&self, digest: Digest256, key: ApiKey) {
+    self.keys.write().unwrap().insert(digest, key);
+  }
+
+  /// Stop accepting the secret hashing to `digest`; false if it wasn't
+  pub fn remove_key(&self, digest: &Digest256) -> bool {
+    self.keys.write().unwrap().remove(digest).is_some()
+  }
+
+  /// Issue a key acting for `owner` as `role`: store it in `repo` and accept
+  /// it at once. Returns the secret, which is not kept anywhere
+  pub fn issue_key(&self, repo: &AnchorRepo, owner: &str, role: Role) -> Result<(String, KeyRecord), VBError> {
+    let secret = generate_secret();
+    let record = KeyRecord {
+      digest: hash_secret(&secret),
+      owner: owner.to_string(),
+      role: role.as_str().to_string(),
+      created_at: chrono::Utc::now().timestamp(),
+    };
+    repo.insert_key(&record)?;
+    self.add_key(record.digest.clone(), ApiKey { owner: record.owner.clone(), role });
+    Ok((secret, record))
+  }
+
+  /// Revoke a key issued through `repo`; false if there is no such key. Keys
+  /// from the keys file are removed by editing it and reloading
+  pub fn revoke_key(&self, repo: &AnchorRepo, digest: &Digest256) -> Result<bool, VBError> {
+    if !repo.delete_key(digest)? {
+      return Ok(false);
+    }
+    self.remove_key(digest);
+    Ok(true)
+  }
+
+  /// API keys accepted
+  pub fn key_count(&self) -> usize {
+    self.keys.read().unwrap().len()
+  }
+
+  /// Refuse or accept again the keys of `tenant`
+  pub fn set_disabled(&self, tenant: &str, disabled: bool) {
+    let mut set = self.disabled.write().unwrap();
+    if disabled {
+      set.insert(tenant.to_string());
+    } else {
+      set.remove(tenant);
+    }
+  }
+
+  /// Credentials from the server configuration. At least one must be set:
+  /// a server nobody can call is a misconfiguration
+  pub fn from_config(config: &AuthConfig) -> Result<Self, VBError> {
+    let mut auth = Authenticator::default();
+    if let Some(token) = &config.token {
+      auth = auth.with_token(token);
+    }
+    if let Some(path) = &config.api_keys {
+      auth = auth.with_keys_file(path)?;
+    }
+    if auth.token.read().unwrap().is_none() && auth.key_count() == 0 {
+      return Err(VBError::InvalidArgument(format!("Set {} or {}", AUTH_TOKEN_ENV, API_KEYS_ENV)));
+    }
+    Ok(auth)
+  }
+
+  /// Replace every credential with those of `config` and `repo`. Nothing
+  /// changes if they don't load
+  pub fn reload(&self, config: &AuthConfig, repo: &AnchorRepo) -> Result<(), VBError> {
+    let fresh = Authenticator::from_config(config)?.with_repo(repo)?;
+    *self.token.write().unwrap() = fresh.token.into_inner().unwrap();
+    *self.keys.write().unwrap() = fresh.keys.into_inner().unwrap();
+    *self.disabled.write().unwrap() = fresh.disabled.into_inner().unwrap();
+    Ok(())
+  }
+
+  /// Owner the request's credentials act for, if their role includes `role`
+  pub fn authenticate(&self, metadata: &MetadataMap, role: Role) -> Result<Owner, VBError> {
+    let digest = secret_digest(metadata)?;
+    if self.token.read().unwrap().as_ref() == Some(&digest) {
+      return Ok(Owner(DEFAULT_OWNER.to_string()));
+    }
+    let keys = self.keys.read().unwrap();
+    let key = keys
+      .get(&digest)
+      .ok_or_else(|| VBError::Unauthenticated("Unknown bearer token or API key".into()))?;
+    if self.disabled.read().unwrap().contains(&key.owner) {
+      return Err(VBError::PermissionDenied(format!("Tenant {} is disabled", key.owner)));
+    }
+    if key.role < role {
+      return Err(VBError::PermissionDenied(format!("This takes the {} role; the API key is {}", role, key.role)));
+    }
+    Ok(Owner(key.owner.clone()))
+  }
+
+  /// Who the request's credentials name, whatever they may do: the owner and
+  /// `token` or `key:<digest prefix>`. `None` when they name nobody
+  pub fn identify(&self, metadata: &MetadataMap) -> Option<(String, String)> {
+    let digest = secret_digest(metadata).ok()?;
+    if self.token.read().unwrap().as_ref() == Some(&digest) {
+      return Some((DEFAULT_OWNER.to_string(), "token".to_string()));
+    }
+    let key = self.keys.read().unwrap().get(&digest)?.owner.clone();
+    Some((key, key_id(&digest)))
+  }
+
+  /// Interceptor guarding a service that needs `role`
+  pub fn interceptor(self: &Arc<Self>, role: Role) -> AuthInterceptor {
+    AuthInterceptor { auth: self.clone(), role }
+  }
+}
+
+fn secret_digest(metadata: &MetadataMap) -> Result<Digest256, VBError> {
+  let secret = metadata
+    .get(AUTHORIZATION_HEADER)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .ok_or_else(|| VBError::Unauthenticated("Missing bearer credentials".into()))?;
+  Ok(hash_secret(secret.trim()))
+}
+
+/// Rejects unauthenticated or unauthorized calls and tags the rest with their
+/// `Owner`
+#[derive(Clone)]
+pub struct AuthInterceptor {
+  auth: Arc<Authenticator>,
+  role: Role,
+}
+
+impl Interceptor for AuthInterceptor {
+  fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+    let owner = self.auth.authenticate(request.metadata(), self.role).map_err(to_status)?;
+    request.extensions_mut().insert(owner);
+    Ok(request)
+  }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tonic::Code;
+  use validblock_storage::{KeyRecord, Tenant};
+
+  fn call(auth: &Arc<Authenticator>, role: Role, secret: Option<&str>) -> Result<Request<()>, Code> {
+    let mut request = Request::new(());
+    if let Some(secret) = secret {
+      request
+        .metadata_mut()
+        .insert(AUTHORIZATION_HEADER, format!("Bearer {}", secret).parse().unwrap());
+    }
+    auth.interceptor(role).call(request).map_err(|status| status.code())
+  }
+
+  #[test]
+  fn test_bearer_and_api_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let keys = dir.path().join("keys");
+    std::fs::write(&keys, format!("# ci runner\n{} acme viewer\n", hash_secret("ci-key"))).unwrap();
+    let auth = Arc::new(Authenticator::default().with_token("app-token").with_keys_file(&keys).unwrap());
+
+    let request = call(&auth, Role::Admin, Some("app-token")).unwrap();
+    assert_eq!(request.extensions().get::<Owner>(), Some(&Owner(DEFAULT_OWNER.to_string())));
+    let request = call(&auth, Role::Viewer, Some("ci-key")).unwrap();
+    assert_eq!(request.extensions().get::<Owner>(), Some(&Owner("acme".to_string())));
+
+    assert_eq!(call(&auth, Role::Anchorer, Some("ci-key")).unwrap_err(), Code::PermissionDenied);
+    assert_eq!(call(&auth, Role::Viewer, Some("guess")).unwrap_err(), Code::Unauthenticated);
+    assert_eq!(call(&auth, Role::Viewer, None).unwrap_err(), Code::Unauthenticated);
+
+    let identify = |secret: &str| {
+      let mut metadata = MetadataMap::new();
+      metadata.insert(AUTHORIZATION_HEADER, format!("Bearer {}", secret).parse().unwrap());
+      auth.identify(&metadata)
+    };
+    assert_eq!(identify("app-token"), Some((DEFAULT_OWNER.to_string(), "token".to_string())));
+    assert_eq!(identify("ci-key"), Some(("acme".to_string(), key_id(&hash_secret("ci-key")))));
+    assert_eq!(identify("guess"), None);
+  }
+
+  #[test]
+  fn test_roles_include_lesser_roles() {
+    let roles = [("viewer-key", Role::Viewer), ("anchorer-key", Role::Anchorer), ("admin-key", Role::Admin)];
+    let auth = roles.iter().fold(Authenticator::default(), |auth, (secret, role)| {
+      auth.with_key(hash_secret(secret), ApiKey { owner: "acme".into(), role: *role })
+    });
+    let auth = Arc::new(auth);
+    for (secret, held) in roles {
+      for (_, needed) in roles {
+        assert_eq!(call(&auth, needed, Some(secret)).is_ok(), held >= needed, "{} calling as {}", held, needed);
+      }
+    }
+  }
+
+  #[test]
+  fn test_legacy_scopes_read_as_roles() {
+    assert_eq!("verify".parse::<Role>().unwrap(), Role::Viewer);
+    assert_eq!("anchor".parse::<Role>().unwrap(), Role::Anchorer);
+    assert_eq!("anchor,verify".parse::<Role>().unwrap(), Role::Anchorer);
+    assert_eq!("admin".parse::<Role>().unwrap(), Role::Admin);
+    assert!("root".parse::<Role>().is_err());
+    assert!("verify,root".parse::<Role>().is_err());
+  }
+
+  #[test]
+  fn test_stored_keys_and_disabled_tenants() {
+    let repo = AnchorRepo::memory().unwrap();
+    for id in ["hr", "legal"] {
+      let tenant = Tenant { id: id.into(), name: id.into(), disabled: id == "legal", fee_rate: None, created_at: 0 };
+      repo.insert_tenant(&tenant).unwrap();
+      let digest = hash_secret(&format!("{}-key", id));
+      repo.insert_key(&KeyRecord { digest, owner: id.into(), role: "anchorer".into(), created_at: 0 }).unwrap();
+    }
+    let auth = Arc::new(Authenticator::default().with_token("app-token").with_repo(&repo).unwrap());
+
+    let request = call(&auth, Role::Anchorer, Some("hr-key")).unwrap();
+    assert_eq!(request.extensions().get::<Owner>(), Some(&Owner("hr".to_string())));
+    assert_eq!(call(&auth, Role::Admin, Some("hr-key")).unwrap_err(), Code::PermissionDenied);
+    assert_eq!(call(&auth, Role::Viewer, Some("legal-key")).unwrap_err(), Code::PermissionDenied);
+    auth.set_disabled("legal", false);
+    assert!(call(&auth, Role::Viewer, Some("legal-key")).is_ok());
+
+    assert!(auth.remove_key(&hash_secret("hr-key")));
+    assert_eq!(call(&auth, Role::Viewer, Some("hr-key")).unwrap_err(), Code::Unauthenticated);
+  }
+
+  #[test]
+  fn test_reload_replaces_credentials() {
+    let dir = tempfile::tempdir().unwrap();
+    let keys = dir.path().join("keys");
+    std::fs::write(&keys, format!("{} acme viewer\n", hash_secret("old-key"))).unwrap();
+    let config = AuthConfig { token: Some("app-token".into()), api_keys: Some(keys.clone()) };
+    let repo = AnchorRepo::memory().unwrap();
+    let auth = Arc::new(Authenticator::from_config(&config).unwrap());
+
+    std::fs::write(&keys, format!("{} acme anchorer\n", hash_secret("new-key"))).unwrap();
+    auth.reload(&config, &repo).unwrap();
+    assert_eq!(call(&auth, Role::Viewer, Some("old-key")).unwrap_err(), Code::Unauthenticated);
+    assert!(call(&auth, Role::Anchorer, Some("new-key")).is_ok());
+
+    // a broken file leaves the credentials as they were
+    std::fs::write(&keys, "not a key line\n").unwrap();
+    assert!(auth.reload(&config, &repo).is_err());
+    assert!(call(&auth, Role::Anchorer, Some("new-key")).is_ok());
+  }
+
+  #[test]
+  fn test_issue_and_revoke_key() {
+    let repo = AnchorRepo::memory().unwrap();
+    let auth = Arc::new(Authenticator::default());
+    let (secret, record) = auth.issue_key(&repo, "acme", Role::Anchorer).unwrap();
+    assert_eq!(secret.len(), 3 + 2 * SECRET_BYTES);
+    assert_eq!(repo.keys().unwrap(), vec![record.clone()]);
+    assert!(call(&auth, Role::Anchorer, Some(&secret)).is_ok());
+
+    assert!(auth.revoke_key(&repo, &record.digest).unwrap());
+    assert!(!auth.revoke_key(&repo, &record.digest).unwrap());
+    assert_eq!(call(&auth, Role::Viewer, Some(&secret)).unwrap_err(), Code::Unauthenticated);
+    assert_ne!(generate_secret(), generate_secret());
+  }
+
+  #[test]
+  fn test_keys_file_rejects_bad_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let keys = dir.path().join("keys");
+    std::fs::write(&keys, format!("{} acme\n", hash_secret("k"))).unwrap();
+    assert!(Authenticator::default().with_keys_file(&keys).is_err());
+    std::fs::write(&keys, format!("{} acme superuser\n", hash_secret("k"))).unwrap();
+    assert!(Authenticator::default().with_keys_file(&keys).is_err());
+  }
+}
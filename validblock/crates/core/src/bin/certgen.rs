@@ -0,0 +1,63 @@
+use std::process::ExitCode;
+
+use validblock_core::tls::SelfSigned;
+use validblock_types::VBError;
+
+const USAGE: &str = "\
+Generate a self-signed certificate for a local ValidBlock server
+
+Usage:
+  validblock-certgen [options] [NAME...]
+
+Names are DNS names or IP addresses the certificate is valid for
+(default: localhost 127.0.0.1).
+
+Options:
+  --out <DIR>     directory to write into (default: .)
+  --stem <NAME>   file name stem, giving <NAME>.pem and <NAME>.key (default: server)";
+
+struct Args {
+  out: String,
+  stem: String,
+  names: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, VBError> {
+  let mut out = ".".to_string();
+  let mut stem = "server".to_string();
+  let mut names = Vec::new();
+  while let Some(arg) = args.next() {
+    let mut value = || args.next().ok_or_else(|| VBError::Other(format!("{} needs a value", arg)));
+    match arg.as_str() {
+      "--out" => out = value()?,
+      "--stem" => stem = value()?,
+      flag if flag.starts_with("--") => return Err(VBError::Other(format!("Unknown argument: {}", flag))),
+      name => names.push(name.to_string()),
+    }
+  }
+  if names.is_empty() {
+    names = vec!["localhost".into(), "127.0.0.1".into()];
+  }
+  Ok(Args { out, stem, names })
+}
+
+fn main() -> ExitCode {
+  let args = match parse_args(std::env::args().skip(1)) {
+    Ok(args) => args,
+    Err(e) => {
+      eprintln!("{}\n\n{}", e, USAGE);
+      return ExitCode::from(2);
+    }
+  };
+  let names: Vec<&str> = args.names.iter().map(String::as_str).collect();
+  match SelfSigned::generate(&names).and_then(|pem| pem.write(&args.out, &args.stem)) {
+    Ok((cert, key)) => {
+      println!("Wrote {} and {}", cert.display(), key.display());
+      ExitCode::SUCCESS
+    }
+    Err(e) => {
+      eprintln!("Certificate generation failed: {}", e);
+      ExitCode::FAILURE
+    }
+  }
+}
@@ -0,0 +1,96 @@
+use std::process::ExitCode;
+
+use validblock_core::rescan::{rescan_blocks, rescan_wallet, RescanReport};
+use validblock_storage::AnchorRepo;
+use validblock_wallet::esplora::{self, EsploraBackend};
+use validblock_wallet::{parse_network, HdWallet, Network};
+use validblock_types::VBError;
+
+const USAGE: &str = "\
+Rebuild anchors.db from anchors already on chain
+
+Usage:
+  validblock-rescan --xpub <XPUB> [options]       scan the wallet's address history
+  validblock-rescan --blocks <FROM>-<TO> [options] scan every transaction in a block range
+
+Options:
+  --db <PATH>          anchors database (default: ./anchors.db)
+  --network <NET>      bitcoin, testnet, signet or regtest (default: bitcoin)
+  --chain-api <URL>    Esplora base URL (default: the public one for the network)";
+
+enum Source {
+  Wallet(String),
+  Blocks(u32, u32),
+}
+
+struct Args {
+  source: Source,
+  db: Option<String>,
+  network: Network,
+  chain_api: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, VBError> {
+  let mut source = None;
+  let mut db = None;
+  let mut network = Network::Bitcoin;
+  let mut chain_api = None;
+  while let Some(flag) = args.next() {
+    let mut value = || args.next().ok_or_else(|| VBError::Other(format!("{} needs a value", flag)));
+    match flag.as_str() {
+      "--xpub" => source = Some(Source::Wallet(value()?)),
+      "--blocks" => {
+        let range = value()?;
+        let (from, to) = range
+          .split_once('-')
+          .and_then(|(a, b)| Some((a.parse().ok()?, b.parse().ok()?)))
+          .ok_or_else(|| VBError::Other(format!("Invalid block range: {}", range)))?;
+        source = Some(Source::Blocks(from, to));
+      }
+      "--db" => db = Some(value()?),
+      "--network" => network = parse_network(&value()?)?,
+      "--chain-api" => chain_api = Some(value()?),
+      other => return Err(VBError::Other(format!("Unknown argument: {}", other))),
+    }
+  }
+  let source = source.ok_or_else(|| VBError::Other("Pass --xpub or --blocks".into()))?;
+  Ok(Args { source, db, network, chain_api })
+}
+
+fn run(args: Args) -> Result<RescanReport, VBError> {
+  let url = match args.chain_api {
+    Some(url) => url,
+    None => esplora::default_url(args.network)
+      .ok_or_else(|| VBError::Other("No public chain API for this network; pass --chain-api".into()))?
+      .to_string(),
+  };
+  let chain = EsploraBackend::new(&url);
+  let repo = AnchorRepo::new(args.db.as_deref())?;
+  match args.source {
+    Source::Wallet(xpub) => rescan_wallet(&repo, &HdWallet::watch_only(&xpub, args.network)?, &chain),
+    Source::Blocks(from, to) => rescan_blocks(&repo, &chain, from..=to),
+  }
+}
+
+fn main() -> ExitCode {
+  let args = match parse_args(std::env::args().skip(1)) {
+    Ok(args) => args,
+    Err(e) => {
+      eprintln!("{}\n\n{}", e, USAGE);
+      return ExitCode::from(2);
+    }
+  };
+  match run(args) {
+    Ok(report) => {
+      println!(
+        "Scanned {} transactions, found {} anchors, restored {}",
+        report.scanned, report.found, report.restored
+      );
+      ExitCode::SUCCESS
+    }
+    Err(e) => {
+      eprintln!("Rescan failed: {}", e);
+      ExitCode::FAILURE
+    }
+  }
+}
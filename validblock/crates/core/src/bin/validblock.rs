@@ -0,0 +1,529 @@
+use std::process::ExitCode;
+
+use serde::Serialize;
+use serde_json::json;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+use validblock_core::auth::{AUTHORIZATION_HEADER, AUTH_TOKEN_ENV};
+use validblock_core::proto::anchor_service_client::AnchorServiceClient;
+use validblock_core::proto::verify_service_client::VerifyServiceClient;
+use validblock_core::proto::{
+  AnchorEvent, AnchorRequest, ExistDigestRequest, ExportPsbtRequest, ListAnchorsRequest, Policy, VerifyRequest,
+};
+use validblock_core::services::IDEMPOTENCY_KEY_HEADER;
+use validblock_core::status::error_code;
+use validblock_core::{tls, AnchorEngine, AnchorRecord, Digest256, ErrorCode, MemoPolicy, VBError, DEFAULT_OWNER};
+use validblock_hasher::{hash_file, hash_reader};
+use validblock_storage::AnchorRepo;
+use validblock_wallet::esplora::{self, EsploraBackend};
+use validblock_wallet::mock::MockWallet;
+use validblock_wallet::{parse_network, psbt, verify_anchor, ChainBackend, Network, Verdict};
+
+const USAGE: &str = "\
+Anchor and verify files from scripts and CI
+
+Usage:
+  validblock [options] hash <FILE>              (- for stdin)
+  validblock [options] anchor <FILE> [--memo <TEXT>] [--on-chain] [--idempotency-key <KEY>]
+  validblock [options] verify <FILE>
+  validblock [options] exists <DIGEST>
+  validblock [options] list [--limit <N>] [--offset <N>]
+  validblock [options] export <DIGEST> [--out <FILE>]
+  validblock [options] receipt <FILE> [--out <FILE>]
+  validblock help                               (or --help, -h)
+
+Without --server the local anchors database is used directly; local anchors
+never touch the chain. `export` writes the PSBT awaiting signature for a
+digest; `receipt` writes a JSON proof of inclusion fetched from the chain API.
+
+Options:
+  --server <URL>       ValidBlock server, http:// or https:// (env: VALIDBLOCK_SERVER)
+  --token <TOKEN>      bearer token or API key (env: VALIDBLOCK_AUTH_TOKEN)
+  --ca <PEM>           CA certificate for an https:// server
+  --cert <PEM>         client certificate for mutual TLS, with --key <PEM>
+  --db <PATH>          local anchors database (default: ./anchors.db)
+  --network <NET>      network for `receipt` (default: bitcoin)
+  --chain-api <URL>    Esplora base URL for `receipt`
+  --json               machine-readable output
+
+Exit codes: 0 success, 1 not found or not verified, 2 bad usage or input,
+3 failure, 4 already anchored.";
+
+/// Environment variable naming the server for remote mode
+const SERVER_ENV: &str = "VALIDBLOCK_SERVER";
+
+const EXIT_NEGATIVE: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_FAILURE: u8 = 3;
+const EXIT_DUPLICATE: u8 = 4;
+
+#[derive(Default)]
+struct Options {
+  server: Option<String>,
+  token: Option<String>,
+  ca: Option<String>,
+  cert: Option<String>,
+  key: Option<String>,
+  db: Option<String>,
+  network: Option<String>,
+  chain_api: Option<String>,
+  memo: Option<String>,
+  on_chain: bool,
+  idempotency_key: Option<String>,
+  limit: u32,
+  offset: u32,
+  out: Option<String>,
+  json: bool,
+}
+
+enum Command {
+  Hash(String),
+  Anchor(String),
+  Verify(String),
+  Exists(String),
+  List,
+  Export(String),
+  Receipt(String),
+  Help,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<(Command, Options), VBError> {
+  let mut opts = Options { server: std::env::var(SERVER_ENV).ok(), token: std::env::var(AUTH_TOKEN_ENV).ok(), ..Default::default() };
+  let mut positional = Vec::new();
+  let mut help = false;
+  while let Some(arg) = args.next() {
+    let mut value = || args.next().ok_or_else(|| VBError::InvalidArgument(format!("{} needs a value", arg)));
+    match arg.as_str() {
+      "--server" => opts.server = Some(value()?),
+      "--token" => opts.token = Some(value()?),
+      "--ca" => opts.ca = Some(value()?),
+      "--cert" => opts.cert = Some(value()?),
+      "--key" => opts.key = Some(value()?),
+      "--db" => opts.db = Some(value()?),
+      "--network" => opts.network = Some(value()?),
+      "--chain-api" => opts.chain_api = Some(value()?),
+      "--memo" => opts.memo = Some(value()?),
+      "--on-chain" => opts.on_chain = true,
+      "--idempotency-key" => opts.idempotency_key = Some(value()?),
+      "--limit" => opts.limit = parse_number(&value()?)?,
+      "--offset" => opts.offset = parse_number(&value()?)?,
+      "--out" => opts.out = Some(value()?),
+      "--json" => opts.json = true,
+      "--help" | "-h" => help = true,
+      flag if flag.starts_with("--") => return Err(VBError::InvalidArgument(format!("Unknown argument: {}", flag))),
+      _ => positional.push(arg),
+    }
+  }
+  if help || positional.first().is_some_and(|name| name == "help") {
+    return Ok((Command::Help, opts));
+  }
+  let mut positional = positional.into_iter();
+  let name = positional.next().ok_or_else(|| VBError::InvalidArgument("Missing command".into()))?;
+  let mut operand = || positional.next().ok_or_else(|| VBError::InvalidArgument(format!("{} needs an argument", name)));
+  let command = match name.as_str() {
+    "hash" => Command::Hash(operand()?),
+    "anchor" => Command::Anchor(operand()?),
+    "verify" => Command::Verify(operand()?),
+    "exists" => Command::Exists(operand()?),
+    "list" => Command::List,
+    "export" => Command::Export(operand()?),
+    "receipt" => Command::Receipt(operand()?),
+    other => return Err(VBError::InvalidArgument(format!("Unknown command: {}", other))),
+  };
+  if let Some(extra) = positional.next() {
+    return Err(VBError::InvalidArgument(format!("Unexpected argument: {}", extra)));
+  }
+  Ok((command, opts))
+}
+
+fn parse_number(s: &str) -> Result<u32, VBError> {
+  s.parse().map_err(|_| VBError::InvalidArgument(format!("Not a number: {}", s)))
+}
+
+/// An anchor event as printed
+#[derive(Serialize)]
+struct Event {
+  digest: String,
+  /// Unknown for a record just anchored through a server
+  #[serde(skip_serializing_if = "String::is_empty")]
+  owner: String,
+  timestamp: i64,
+  memo: String,
+  txid: String,
+  status: String,
+}
+
+impl From<AnchorRecord> for Event {
+  fn from(rec: AnchorRecord) -> Self {
+    Event {
+      digest: rec.digest.to_string(),
+      owner: rec.owner,
+      timestamp: rec.ts,
+      memo: String::from_utf8_lossy(rec.memo.as_deref().unwrap_or_default()).into_owned(),
+      txid: rec.txid.unwrap_or_default(),
+      status: rec.status.to_string(),
+    }
+  }
+}
+
+impl From<AnchorEvent> for Event {
+  fn from(ev: AnchorEvent) -> Self {
+    Event {
+      digest: ev.digest,
+      owner: ev.owner,
+      timestamp: ev.timestamp,
+      memo: String::from_utf8_lossy(&ev.memo).into_owned(),
+      txid: ev.txid,
+      status: ev.status,
+    }
+  }
+}
+
+impl Event {
+  fn line(&self) -> String {
+    let mut line = format!("{}  {}  {}  {}", self.digest, self.timestamp, self.owner, self.status);
+    if !self.txid.is_empty() {
+      line += &format!("  {}", self.txid);
+    }
+    if !self.memo.is_empty() {
+      line += &format!("  {:?}", self.memo);
+    }
+    line
+  }
+}
+
+/// Where anchors live: the local database, or a server
+enum Backend {
+  Local(AnchorEngine<MockWallet>),
+  Remote { channel: Channel, token: Option<String> },
+}
+
+impl Backend {
+  async fn open(opts: &Options) -> Result<Self, Failure> {
+    let Some(server) = &opts.server else {
+      return Ok(Backend::Local(AnchorEngine::new(AnchorRepo::new(opts.db.as_deref())?, MockWallet)));
+    };
+    let mut endpoint = Endpoint::from_shared(server.clone())
+      .map_err(|_| VBError::InvalidArgument(format!("Invalid server URL: {}", server)))?;
+    if server.starts_with("https://") {
+      let ca = opts.ca.as_deref().ok_or_else(|| VBError::InvalidArgument("An https:// server needs --ca".into()))?;
+      let identity = match (&opts.cert, &opts.key) {
+        (Some(cert), Some(key)) => Some((cert.as_ref(), key.as_ref())),
+        (None, None) => None,
+        _ => return Err(VBError::InvalidArgument("Pass both --cert and --key".into()).into()),
+      };
+      endpoint = endpoint.tls_config(tls::client_config(ca.as_ref(), identity)?).map_err(transport_err)?;
+    }
+    let channel = endpoint.connect().await.map_err(transport_err)?;
+    Ok(Backend::Remote { channel, token: opts.token.clone() })
+  }
+
+  fn request<T>(token: &Option<String>, message: T) -> Result<Request<T>, VBError> {
+    let mut request = Request::new(message);
+    if let Some(token) = token {
+      let value = format!("Bearer {}", token)
+        .parse()
+        .map_err(|_| VBError::InvalidArgument("Invalid token".into()))?;
+      request.metadata_mut().insert(AUTHORIZATION_HEADER, value);
+    }
+    Ok(request)
+  }
+
+  async fn anchor(&mut self, file: &str, opts: &Options) -> Result<(Event, bool, String), Failure> {
+    match self {
+      Backend::Local(engine) => {
+        if opts.on_chain {
+          return Err(VBError::InvalidArgument("On-chain anchoring needs --server".into()).into());
+        }
+        let memo = opts.memo.as_deref().map(str::as_bytes);
+        let anchored = match &opts.idempotency_key {
+          Some(key) => engine.anchor_with_key(file, MemoPolicy::LocalOnly, DEFAULT_OWNER, memo, key)?,
+          None => engine.anchor(file, MemoPolicy::LocalOnly, DEFAULT_OWNER, memo)?,
+        };
+        Ok((anchored.record.into(), anchored.existing, String::new()))
+      }
+      Backend::Remote { channel, token } => {
+        let policy = if opts.on_chain { Policy::OnChain } else { Policy::LocalOnly };
+        let message = AnchorRequest {
+          file_content: std::fs::read(file)?,
+          memo: opts.memo.clone().unwrap_or_default(),
+          policy: policy as i32,
+        };
+        let mut request = Self::request(token, message)?;
+        if let Some(key) = &opts.idempotency_key {
+          let value = key.parse().map_err(|_| VBError::InvalidArgument("Invalid idempotency key".into()))?;
+          request.metadata_mut().insert(IDEMPOTENCY_KEY_HEADER, value);
+        }
+        let res = AnchorServiceClient::new(channel.clone()).anchor(request).await.map_err(status_err)?.into_inner();
+        let event = Event {
+          digest: res.digest,
+          owner: String::new(),
+          timestamp: res.timestamp,
+          memo: opts.memo.clone().unwrap_or_default(),
+          txid: res.txid,
+          status: res.status,
+        };
+        Ok((event, res.existing, res.psbt))
+      }
+    }
+  }
+
+  async fn events(&mut self, file: &str) -> Result<Vec<Event>, Failure> {
+    match self {
+      Backend::Local(engine) => Ok(engine.verify_events(file, None)?.into_iter().map(Event::from).collect()),
+      Backend::Remote { channel, token } => {
+        let request = Self::request(token, VerifyRequest { file_content: std::fs::read(file)? })?;
+        match VerifyServiceClient::new(channel.clone()).verify(request).await {
+          Ok(res) => Ok(res.into_inner().events.into_iter().map(Event::from).collect()),
+          Err(status) if error_code(&status) == Some(ErrorCode::NotFound) => Ok(vec![]),
+          Err(status) => Err(status_err(status)),
+        }
+      }
+    }
+  }
+
+  async fn exists(&mut self, digest: &Digest256) -> Result<bool, Failure> {
+    match self {
+      Backend::Local(engine) => Ok(engine.repo.exists_digest(digest)?),
+      Backend::Remote { channel, token } => {
+        let request = Self::request(token, ExistDigestRequest { digest: digest.to_string() })?;
+        let res = VerifyServiceClient::new(channel.clone()).exist_digest(request).await.map_err(status_err)?;
+        Ok(res.into_inner().exists)
+      }
+    }
+  }
+
+  async fn list(&mut self, limit: u32, offset: u32) -> Result<Vec<Event>, Failure> {
+    match self {
+      Backend::Local(engine) => {
+        let limit = if limit == 0 { u32::MAX } else { limit };
+        Ok(engine.repo.list(None, limit, offset)?.into_iter().map(Event::from).collect())
+      }
+      Backend::Remote { channel, token } => {
+        let request = Self::request(token, ListAnchorsRequest { limit, offset })?;
+        let res = VerifyServiceClient::new(channel.clone()).list_anchors(request).await.map_err(status_err)?;
+        Ok(res.into_inner().events.into_iter().map(Event::from).collect())
+      }
+    }
+  }
+
+  async fn export(&mut self, digest: &Digest256) -> Result<String, Failure> {
+    match self {
+      Backend::Local(engine) => engine
+        .pending_psbt(digest, DEFAULT_OWNER)?
+        .map(|p| psbt::to_base64(&p))
+        .ok_or_else(|| VBError::NotFound(format!("No PSBT awaiting signature for {}", digest)).into()),
+      Backend::Remote { channel, token } => {
+        let request = Self::request(token, ExportPsbtRequest { digest: digest.to_string() })?;
+        let res = AnchorServiceClient::new(channel.clone()).export_psbt(request).await.map_err(status_err)?;
+        Ok(res.into_inner().psbt)
+      }
+    }
+  }
+}
+
+fn transport_err(e: tonic::transport::Error) -> VBError {
+  VBError::Other(format!("Connection failed: {}", e))
+}
+
+/// Why a command failed, locally or on the server
+struct Failure {
+  code: ErrorCode,
+  message: String,
+}
+
+impl From<VBError> for Failure {
+  fn from(e: VBError) -> Self {
+    Failure { code: e.code(), message: e.to_string() }
+  }
+}
+
+impl From<std::io::Error> for Failure {
+  fn from(e: std::io::Error) -> Self {
+    VBError::from(e).into()
+  }
+}
+
+/// A server error, by the code it attached
+fn status_err(status: Status) -> Failure {
+  Failure {
+    code: error_code(&status).unwrap_or(ErrorCode::Internal),
+    message: status.message().to_string(),
+  }
+}
+
+/// What a command produced: JSON for `--json`, text otherwise, and whether
+/// the answer was positive
+struct Output {
+  json: serde_json::Value,
+  text: String,
+  found: bool,
+}
+
+impl Output {
+  fn found(json: serde_json::Value, text: String) -> Self {
+    Output { json, text, found: true }
+  }
+}
+
+async fn run(command: Command, opts: &Options) -> Result<Output, Failure> {
+  if let Command::Hash(file) = &command {
+    let digest = match file.as_str() {
+      "-" => hash_reader(std::io::stdin().lock())?,
+      path => hash_file(path)?,
+    };
+    return Ok(Output::found(json!({ "digest": digest.to_string() }), digest.to_string()));
+  }
+  let mut backend = Backend::open(opts).await?;
+  match command {
+    Command::Hash(_) => unreachable!("handled above"),
+    Command::Help => unreachable!("handled in main"),
+    Command::Anchor(file) => {
+      let (event, existing, psbt) = backend.anchor(&file, opts).await?;
+      let text = match (existing, psbt.is_empty()) {
+        (true, _) => format!("Already anchored {} ({})", event.digest, event.status),
+        (false, true) => format!("Anchored {} ({})", event.digest, event.status),
+        (false, false) => format!("Anchored {} ({}); sign this PSBT:\n{}", event.digest, event.status, psbt),
+      };
+      let mut json = serde_json::to_value(&event).map_err(json_err)?;
+      json["existing"] = existing.into();
+      if !psbt.is_empty() {
+        json["psbt"] = psbt.into();
+      }
+      Ok(Output::found(json, text))
+    }
+    Command::Verify(file) => {
+      let digest = hash_file(&file)?;
+      let events = backend.events(&file).await?;
+      let text = match events.is_empty() {
+        true => format!("No anchor for {}", digest),
+        false => events.iter().map(Event::line).collect::<Vec<_>>().join("\n"),
+      };
+      let json = json!({ "digest": digest.to_string(), "verified": !events.is_empty(), "events": events });
+      Ok(Output { found: !events.is_empty(), json, text })
+    }
+    Command::Exists(digest) => {
+      let digest: Digest256 = digest.parse()?;
+      let exists = backend.exists(&digest).await?;
+      let json = json!({ "digest": digest.to_string(), "exists": exists });
+      Ok(Output { json, text: if exists { "yes" } else { "no" }.into(), found: exists })
+    }
+    Command::List => {
+      let events = backend.list(opts.limit, opts.offset).await?;
+      let text = events.iter().map(Event::line).collect::<Vec<_>>().join("\n");
+      Ok(Output::found(json!({ "events": events }), text))
+    }
+    Command::Export(digest) => {
+      let digest: Digest256 = digest.parse()?;
+      let psbt = backend.export(&digest).await?;
+      if let Some(out) = &opts.out {
+        psbt::write_file(&psbt::from_base64(&psbt)?, out)?;
+      }
+      let text = match &opts.out {
+        Some(out) => format!("Wrote {}", out),
+        None => psbt.clone(),
+      };
+      Ok(Output::found(json!({ "digest": digest.to_string(), "psbt": psbt }), text))
+    }
+    Command::Receipt(file) => {
+      let digest = hash_file(&file)?;
+      let events = backend.events(&file).await?;
+      let Some(txid) = events.iter().find(|ev| !ev.txid.is_empty()).map(|ev| ev.txid.clone()) else {
+        return Err(VBError::NotFound(format!("No on-chain anchor for {}", digest)).into());
+      };
+      let receipt = receipt(opts, digest, txid).await?;
+      let found = receipt["verified"].as_bool().unwrap_or(false);
+      let text = serde_json::to_string_pretty(&receipt).map_err(json_err)?;
+      if let Some(out) = &opts.out {
+        std::fs::write(out, &text)?;
+      }
+      Ok(Output { json: receipt, text, found })
+    }
+  }
+}
+
+/// Proof that `txid` anchors `digest`: the chain's verdict plus the block
+/// and Merkle branch to check it against independently
+async fn receipt(opts: &Options, digest: Digest256, txid: String) -> Result<serde_json::Value, Failure> {
+  let network = match &opts.network {
+    Some(name) => parse_network(name)?,
+    None => Network::Bitcoin,
+  };
+  let url = match &opts.chain_api {
+    Some(url) => url.clone(),
+    None => esplora::default_url(network)
+      .ok_or_else(|| VBError::InvalidArgument("No public chain API for this network; pass --chain-api".into()))?
+      .to_string(),
+  };
+  let txid = txid.parse().map_err(|_| VBError::Other(format!("Invalid txid: {}", txid)))?;
+  tokio::task::spawn_blocking(move || {
+    let chain = EsploraBackend::new(&url);
+    let verdict = verify_anchor(&chain, network, &txid, &digest)?;
+    let mut receipt = json!({
+      "digest": digest.to_string(),
+      "txid": txid.to_string(),
+      "network": network.to_string(),
+      "verified": verdict.is_verified(),
+      "verdict": verdict.to_string(),
+    });
+    if let Verdict::Confirmed { block_hash, height, time } = verdict {
+      let proof = chain
+        .merkle_proof(&txid)?
+        .ok_or_else(|| VBError::Other("Chain API dropped the Merkle proof".into()))?;
+      receipt["block_hash"] = block_hash.to_string().into();
+      receipt["height"] = height.into();
+      receipt["block_time"] = time.into();
+      receipt["pos"] = proof.pos.into();
+      receipt["merkle"] = proof.merkle.iter().map(|node| node.to_string()).collect::<Vec<_>>().into();
+    }
+    Ok(receipt)
+  })
+  .await
+  .map_err(|e| VBError::Other(e.to_string()))?
+}
+
+fn json_err(e: serde_json::Error) -> VBError {
+  VBError::Other(e.to_string())
+}
+
+fn exit_code(err: &Failure) -> u8 {
+  match err.code {
+    ErrorCode::NotFound => EXIT_NEGATIVE,
+    ErrorCode::InvalidArgument | ErrorCode::InvalidDigest | ErrorCode::InvalidPayload => EXIT_USAGE,
+    ErrorCode::DuplicateDigest => EXIT_DUPLICATE,
+    _ => EXIT_FAILURE,
+  }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+  let (command, opts) = match parse_args(std::env::args().skip(1)) {
+    Ok((Command::Help, _)) => {
+      println!("{}", USAGE);
+      return ExitCode::SUCCESS;
+    }
+    Ok(parsed) => parsed,
+    Err(e) => {
+      eprintln!("{}\n\n{}", e, USAGE);
+      return ExitCode::from(EXIT_USAGE);
+    }
+  };
+  match run(command, &opts).await {
+    Ok(output) => {
+      if opts.json {
+        println!("{}", output.json);
+      } else if !output.text.is_empty() {
+        println!("{}", output.text);
+      }
+      if output.found { ExitCode::SUCCESS } else { ExitCode::from(EXIT_NEGATIVE) }
+    }
+    Err(e) => {
+      if opts.json {
+        println!("{}", json!({ "error": { "code": e.code.as_str(), "message": e.message } }));
+      } else {
+        eprintln!("validblock: {}", e.message);
+      }
+      ExitCode::from(exit_code(&e))
+    }
+  }
+}
@@ -0,0 +1,541 @@
+//! Server configuration: defaults, then a TOML file, then environment
+//! variables, then command-line flags, each overriding the one before
+//!
+//! ```toml
+//! listen = "0.0.0.0:50051"
+//! db = "/var/lib/validblock/anchors.db"
+//! network = "signet"
+//! grpc_web = true
+//! rest = true
+//! cors_origins = ["https://app.example.com"]
+//! metrics_listen = "0.0.0.0:9100"
+//!
+//! [wallet]
+//! backend = "watch-only"
+//! xpub = "tpub..."
+//!
+//! [log]
+//! format = "json"
+//! dir = "/var/log/validblock"
+//!
+//! [limits]
+//! requests_per_minute = 120
+//! daily_on_chain = 50
+//!
+//! [tenants]
+//! required = true
+//!
+//! [auth]
+//! api_keys = "/etc/validblock/api-keys"
+//!
+//! [tls]
+//! cert = "/etc/validblock/server.pem"
+//! key = "/etc/validblock/server.key"
+//!
+//! [[webhooks]]
+//! url = "https://hooks.example.com/validblock"
+//! secret = "whsec-..."
+//! ```
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use validblock_types::VBError;
+use validblock_wallet::{parse_network, Network};
+
+use crate::auth::{API_KEYS_ENV, AUTH_TOKEN_ENV};
+use crate::limits::{LimitsConfig, MAX_UPLOAD_ENV, RATE_LIMIT_ENV};
+use crate::metrics::METRICS_LISTEN_ENV;
+use crate::telemetry::{LogConfig, LOG_DIR_ENV, LOG_FORMAT_ENV, OTLP_ENDPOINT_ENV};
+use crate::tenants::TenantsConfig;
+use crate::webhooks::WebhookConfig;
+use crate::tls::{TlsConfig, TLS_CERT_ENV, TLS_CLIENT_CA_ENV, TLS_KEY_ENV};
+use crate::{IDEMPOTENT_ENV, NETWORK_ENV};
+
+/// Environment variable naming the TOML configuration file
+pub const CONFIG_ENV: &str = "VALIDBLOCK_CONFIG";
+/// Environment variable overriding the listen address
+pub const LISTEN_ENV: &str = "VALIDBLOCK_LISTEN";
+/// Environment variable overriding the anchors database path
+pub const DB_ENV: &str = "VALIDBLOCK_DB";
+/// Environment variable selecting the wallet backend
+pub const WALLET_ENV: &str = "VALIDBLOCK_WALLET";
+/// Environment variable turning gRPC-Web on or off
+pub const GRPC_WEB_ENV: &str = "VALIDBLOCK_GRPC_WEB";
+/// Environment variable turning the REST gateway on or off
+pub const REST_ENV: &str = "VALIDBLOCK_REST";
+/// Environment variable listing CORS origins, comma separated
+pub const CORS_ORIGINS_ENV: &str = "VALIDBLOCK_CORS_ORIGINS";
+/// Environment variable holding the keystore passphrase for the hot wallet;
+/// never read from the file or the command line
+pub const KEYSTORE_PASSPHRASE_ENV: &str = "VALIDBLOCK_KEYSTORE_PASSPHRASE";
+
+pub const USAGE: &str = "\
+Usage:
+  validblock-server [options]
+
+Options:
+  --config <PATH>          TOML configuration file (env: VALIDBLOCK_CONFIG)
+  --listen <ADDR>          address to serve on (default: 127.0.0.1:50051)
+  --db <PATH>              anchors database (default: ./anchors.db)
+  --wallet <BACKEND>       mock, watch-only or hot (default: mock)
+  --network <NET>          bitcoin, testnet, signet or regtest (default: bitcoin)
+  --grpc-web               accept gRPC-Web from browsers
+  --no-grpc-web            serve plain gRPC only
+  --rest                   serve the REST/JSON gateway on the same address
+  --no-rest                serve gRPC only
+  --cors-origin <URL>      allow browser calls from this origin; repeatable
+  --idempotent             answer re-anchoring with the existing record
+  --metrics-listen <ADDR>  serve /metrics, /healthz and /readyz on this address
+  --log-format <FORMAT>    text or json (default: text)
+  --log-dir <PATH>         write daily-rotated log files here instead of stderr
+  --otlp-endpoint <URL>    export trace spans to this OTLP/gRPC collector
+  --rate-limit <N>         requests per minute per client (default: unlimited)
+  --max-upload-bytes <N>   largest file accepted (default: 33554432)
+
+Each option can also be set in the file or through its VALIDBLOCK_*
+environment variable; flags win over the environment, which wins over the file.
+RUST_LOG, when set, overrides the configured log level.";
+
+/// Which wallet commits anchors on-chain
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WalletBackend {
+  /// No chain at all; for development
+  #[default]
+  Mock,
+  /// Builds PSBTs from an xpub for an offline signer
+  WatchOnly,
+  /// Signs with the encrypted keystore
+  Hot,
+}
+
+impl FromStr for WalletBackend {
+  type Err = VBError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "mock" => Ok(WalletBackend::Mock),
+      "watch-only" => Ok(WalletBackend::WatchOnly),
+      "hot" => Ok(WalletBackend::Hot),
+      other => Err(VBError::InvalidArgument(format!("Unknown wallet backend: {}", other))),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WalletConfig {
+  pub backend: WalletBackend,
+  /// Account xpub for `watch-only`
+  pub xpub: Option<String>,
+  /// Keystore file for `hot`
+  pub keystore: Option<PathBuf>,
+  /// Esplora base URL; the network's public instance when unset
+  pub chain_api: Option<String>,
+  /// Fee rate for anchor transactions, sat/vB
+  pub fee_rate: u16,
+}
+
+impl Default for WalletConfig {
+  fn default() -> Self {
+    WalletConfig { backend: WalletBackend::Mock, xpub: None, keystore: None, chain_api: None, fee_rate: 1 }
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+  /// Bearer token acting for the default owner as an admin
+  pub token: Option<String>,
+  /// API keys file, see [`crate::auth`]
+  pub api_keys: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+  pub listen: SocketAddr,
+  /// Anchors database; `./anchors.db` when unset
+  pub db: Option<String>,
+  #[serde(deserialize_with = "de_network")]
+  pub network: Network,
+  pub grpc_web: bool,
+  /// REST/JSON gateway, see [`crate::rest`]
+  pub rest: bool,
+  pub cors_origins: Vec<String>,
+  pub idempotent: bool,
+  pub wallet: WalletConfig,
+  pub auth: AuthConfig,
+  /// Log output, see [`crate::telemetry`]
+  pub log: LogConfig,
+  /// Rate limits, upload cap and quotas, see [`crate::limits`]
+  pub limits: LimitsConfig,
+  /// Multi-tenant mode and tenant wallets, see [`crate::tenants`]
+  pub tenants: TenantsConfig,
+  pub tls: Option<TlsConfig>,
+  /// Receivers of anchor events, see [`crate::webhooks`]
+  pub webhooks: Vec<WebhookConfig>,
+  /// Seconds between checks of broadcast anchors for confirmation
+  pub confirm_interval: u64,
+  /// Seconds in-flight calls get to finish after a shutdown signal
+  pub shutdown_grace: u64,
+  /// Address of the metrics and probes endpoint, see [`crate::metrics`]; off
+  /// when unset
+  pub metrics_listen: Option<SocketAddr>,
+  /// Command-line arguments it was loaded with, replayed by a config reload
+  #[serde(skip)]
+  pub args: Vec<String>,
+}
+
+impl Default for ServerConfig {
+  fn default() -> Self {
+    ServerConfig {
+      listen: SocketAddr::from(([127, 0, 0, 1], 50051)),
+      db: None,
+      network: Network::Bitcoin,
+      grpc_web: false,
+      rest: false,
+      cors_origins: vec![],
+      idempotent: false,
+      wallet: WalletConfig::default(),
+      auth: AuthConfig::default(),
+      log: LogConfig::default(),
+      limits: LimitsConfig::default(),
+      tenants: TenantsConfig::default(),
+      tls: None,
+      webhooks: vec![],
+      confirm_interval: 60,
+      shutdown_grace: 30,
+      metrics_listen: None,
+      args: vec![],
+    }
+  }
+}
+
+fn de_network<'de, D: serde::Deserializer<'de>>(de: D) -> Result<Network, D::Error> {
+  let name = String::deserialize(de)?;
+  parse_network(&name).map_err(serde::de::Error::custom)
+}
+
+impl ServerConfig {
+  /// Parse a TOML configuration
+  pub fn from_toml(text: &str) -> Result<Self, VBError> {
+    toml::from_str(text).map_err(|e| VBError::InvalidArgument(format!("Invalid configuration: {}", e)))
+  }
+
+  /// The full configuration for a server started with `args`: the file named
+  /// by `--config` or `VALIDBLOCK_CONFIG` (keys it leaves out take the standard
+  /// defaults), or `self` when there is none, then the environment, then the
+  /// flags
+  pub fn load(self, args: impl IntoIterator<Item = String>) -> Result<Self, VBError> {
+    self.load_with(args, |name| std::env::var(name).ok())
+  }
+
+  /// `load` reading the environment through `var`
+  pub fn load_with(
+    self,
+    args: impl IntoIterator<Item = String>,
+    var: impl Fn(&str) -> Option<String>,
+  ) -> Result<Self, VBError> {
+    let args: Vec<String> = args.into_iter().collect();
+    let flags = Flags::parse(args.clone())?;
+    let mut config = match flags.config.clone().or_else(|| var(CONFIG_ENV).map(PathBuf::from)) {
+      Some(path) => Self::from_toml(&std::fs::read_to_string(path)?)?,
+      None => self,
+    };
+    config.apply_env(&var)?;
+    config.apply_flags(flags)?;
+    config.args = args;
+    Ok(config)
+  }
+
+  fn apply_env(&mut self, var: &impl Fn(&str) -> Option<String>) -> Result<(), VBError> {
+    if let Some(listen) = var(LISTEN_ENV) {
+      self.listen = parse_addr(&listen)?;
+    }
+    if let Some(db) = var(DB_ENV) {
+      self.db = Some(db);
+    }
+    if let Some(backend) = var(WALLET_ENV) {
+      self.wallet.backend = backend.parse()?;
+    }
+    if let Some(network) = var(NETWORK_ENV) {
+      self.network = parse_network(&network)?;
+    }
+    if let Some(on) = var(GRPC_WEB_ENV) {
+      self.grpc_web = parse_bool(GRPC_WEB_ENV, &on)?;
+    }
+    if let Some(on) = var(REST_ENV) {
+      self.rest = parse_bool(REST_ENV, &on)?;
+    }
+    if let Some(origins) = var(CORS_ORIGINS_ENV) {
+      self.cors_origins = origins.split(',').map(str::trim).filter(|o| !o.is_empty()).map(String::from).collect();
+    }
+    if let Some(on) = var(IDEMPOTENT_ENV) {
+      self.idempotent = parse_bool(IDEMPOTENT_ENV, &on)?;
+    }
+    if let Some(addr) = var(METRICS_LISTEN_ENV) {
+      self.metrics_listen = Some(parse_addr(&addr)?);
+    }
+    if let Some(format) = var(LOG_FORMAT_ENV) {
+      self.log.format = format.parse()?;
+    }
+    if let Some(dir) = var(LOG_DIR_ENV) {
+      self.log.dir = Some(dir.into());
+    }
+    if let Some(endpoint) = var(OTLP_ENDPOINT_ENV) {
+      self.log.otlp_endpoint = Some(endpoint);
+    }
+    if let Some(n) = var(RATE_LIMIT_ENV) {
+      self.limits.requests_per_minute = Some(parse_count(RATE_LIMIT_ENV, &n)?);
+    }
+    if let Some(n) = var(MAX_UPLOAD_ENV) {
+      self.limits.max_upload_bytes = parse_count(MAX_UPLOAD_ENV, &n)?;
+    }
+    if let Some(token) = var(AUTH_TOKEN_ENV) {
+      self.auth.token = Some(token);
+    }
+    if let Some(keys) = var(API_KEYS_ENV) {
+      self.auth.api_keys = Some(keys.into());
+    }
+    match (var(TLS_CERT_ENV), var(TLS_KEY_ENV)) {
+      (Some(cert), Some(key)) => {
+        self.tls = Some(TlsConfig { cert: cert.into(), key: key.into(), client_ca: var(TLS_CLIENT_CA_ENV).map(PathBuf::from) })
+      }
+      (None, None) => {}
+      _ => return Err(VBError::InvalidArgument(format!("Set both {} and {}", TLS_CERT_ENV, TLS_KEY_ENV))),
+    }
+    Ok(())
+  }
+
+  fn apply_flags(&mut self, flags: Flags) -> Result<(), VBError> {
+    if let Some(listen) = flags.listen {
+      self.listen = parse_addr(&listen)?;
+    }
+    if let Some(db) = flags.db {
+      self.db = Some(db);
+    }
+    if let Some(backend) = flags.wallet {
+      self.wallet.backend = backend.parse()?;
+    }
+    if let Some(network) = flags.network {
+      self.network = parse_network(&network)?;
+    }
+    if let Some(on) = flags.grpc_web {
+      self.grpc_web = on;
+    }
+    if let Some(on) = flags.rest {
+      self.rest = on;
+    }
+    if !flags.cors_origins.is_empty() {
+      self.cors_origins = flags.cors_origins;
+    }
+    if flags.idempotent {
+      self.idempotent = true;
+    }
+    if let Some(addr) = flags.metrics_listen {
+      self.metrics_listen = Some(parse_addr(&addr)?);
+    }
+    if let Some(format) = flags.log_format {
+      self.log.format = format.parse()?;
+    }
+    if let Some(dir) = flags.log_dir {
+      self.log.dir = Some(dir);
+    }
+    if let Some(endpoint) = flags.otlp_endpoint {
+      self.log.otlp_endpoint = Some(endpoint);
+    }
+    if let Some(n) = flags.rate_limit {
+      self.limits.requests_per_minute = Some(parse_count("--rate-limit", &n)?);
+    }
+    if let Some(n) = flags.max_upload_bytes {
+      self.limits.max_upload_bytes = parse_count("--max-upload-bytes", &n)?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Default)]
+struct Flags {
+  config: Option<PathBuf>,
+  listen: Option<String>,
+  db: Option<String>,
+  wallet: Option<String>,
+  network: Option<String>,
+  grpc_web: Option<bool>,
+  rest: Option<bool>,
+  cors_origins: Vec<String>,
+  idempotent: bool,
+  metrics_listen: Option<String>,
+  log_format: Option<String>,
+  log_dir: Option<PathBuf>,
+  otlp_endpoint: Option<String>,
+  rate_limit: Option<String>,
+  max_upload_bytes: Option<String>,
+}
+
+impl Flags {
+  fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, VBError> {
+    let mut flags = Flags::default();
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+      let mut value = || args.next().ok_or_else(|| VBError::InvalidArgument(format!("{} needs a value", flag)));
+      match flag.as_str() {
+        "--config" => flags.config = Some(value()?.into()),
+        "--listen" => flags.listen = Some(value()?),
+        "--db" => flags.db = Some(value()?),
+        "--wallet" => flags.wallet = Some(value()?),
+        "--network" => flags.network = Some(value()?),
+        "--grpc-web" => flags.grpc_web = Some(true),
+        "--no-grpc-web" => flags.grpc_web = Some(false),
+        "--rest" => flags.rest = Some(true),
+        "--no-rest" => flags.rest = Some(false),
+        "--cors-origin" => flags.cors_origins.push(value()?),
+        "--idempotent" => flags.idempotent = true,
+        "--metrics-listen" => flags.metrics_listen = Some(value()?),
+        "--log-format" => flags.log_format = Some(value()?),
+        "--log-dir" => flags.log_dir = Some(value()?.into()),
+        "--otlp-endpoint" => flags.otlp_endpoint = Some(value()?),
+        "--rate-limit" => flags.rate_limit = Some(value()?),
+        "--max-upload-bytes" => flags.max_upload_bytes = Some(value()?),
+        // answered before loading, see [`wants_help`]
+        "--help" | "-h" => {}
+        other => return Err(VBError::InvalidArgument(format!("Unknown argument: {}", other))),
+      }
+    }
+    Ok(flags)
+  }
+}
+
+/// Whether `args` ask for [`USAGE`] rather than a server
+pub fn wants_help(args: &[String]) -> bool {
+  args.iter().any(|arg| arg == "--help" || arg == "-h")
+}
+
+fn parse_addr(s: &str) -> Result<SocketAddr, VBError> {
+  s.parse().map_err(|_| VBError::InvalidArgument(format!("Invalid listen address: {}", s)))
+}
+
+fn parse_count<T: FromStr>(name: &str, s: &str) -> Result<T, VBError> {
+  s.parse().map_err(|_| VBError::InvalidArgument(format!("{} must be a whole number", name)))
+}
+
+fn parse_bool(name: &str, s: &str) -> Result<bool, VBError> {
+  match s {
+    "1" | "true" => Ok(true),
+    "0" | "false" => Ok(false),
+    _ => Err(VBError::InvalidArgument(format!("{} must be true or false", name))),
+  }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashMap;
+
+  use crate::telemetry::LogFormat;
+
+  fn args(s: &str) -> Vec<String> {
+    s.split_whitespace().map(String::from).collect()
+  }
+
+  #[test]
+  fn test_toml_config() {
+    let config = ServerConfig::from_toml(
+      r#"
+      listen = "0.0.0.0:9000"
+      network = "signet"
+      grpc_web = true
+      cors_origins = ["https://app.example.com"]
+
+      [log]
+      format = "json"
+
+      [limits]
+      daily_on_chain = 50
+
+      [limits.owners]
+      acme = 500
+
+      [tenants]
+      required = true
+
+      [tenants.wallets.hr]
+      xpub = "tpubHR"
+
+      [wallet]
+      backend = "watch-only"
+      xpub = "tpubX"
+
+      [tls]
+      cert = "server.pem"
+      key = "server.key"
+
+      [[webhooks]]
+      url = "https://hooks.example.com/validblock"
+      secret = "whsec"
+      "#,
+    )
+    .unwrap();
+    assert_eq!(config.listen, "0.0.0.0:9000".parse().unwrap());
+    assert_eq!(config.network, Network::Signet);
+    assert_eq!(config.wallet.backend, WalletBackend::WatchOnly);
+    assert_eq!(config.wallet.fee_rate, 1);
+    assert_eq!(config.tls.unwrap().client_ca, None);
+    assert_eq!(config.webhooks[0].url, "https://hooks.example.com/validblock");
+    assert_eq!(config.confirm_interval, 60);
+    assert_eq!(config.metrics_listen, None);
+    assert_eq!(config.log.format, LogFormat::Json);
+    assert_eq!(config.limits.daily_on_chain, Some(50));
+    assert_eq!(config.limits.owners["acme"], 500);
+    assert_eq!(config.limits.requests_per_minute, None);
+    assert!(config.tenants.required);
+    assert_eq!(config.tenants.wallets["hr"].xpub.as_deref(), Some("tpubHR"));
+    assert!(ServerConfig::from_toml("listen = \"nowhere\"").is_err());
+    assert!(ServerConfig::from_toml("port = 1").is_err());
+  }
+
+  #[test]
+  fn test_precedence() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("validblock.toml");
+    std::fs::write(&file, "network = \"testnet\"\ndb = \"file.db\"\nlisten = \"127.0.0.1:1\"\n").unwrap();
+    let env: HashMap<&str, String> = [
+      (CONFIG_ENV, file.display().to_string()),
+      (DB_ENV, "env.db".to_string()),
+      (LISTEN_ENV, "127.0.0.1:2".to_string()),
+    ]
+    .into();
+    let var = |name: &str| env.get(name).cloned();
+
+    let config = ServerConfig::default()
+      .load_with(args("--listen 127.0.0.1:3 --grpc-web --rest --metrics-listen 127.0.0.1:9100 --rate-limit 60"), var)
+      .unwrap();
+    assert_eq!(config.network, Network::Testnet);
+    assert_eq!(config.db.as_deref(), Some("env.db"));
+    assert_eq!(config.listen, "127.0.0.1:3".parse().unwrap());
+    assert!(config.grpc_web);
+    assert!(config.rest);
+    assert_eq!(config.metrics_listen, Some("127.0.0.1:9100".parse().unwrap()));
+    assert_eq!(config.limits.requests_per_minute, Some(60));
+
+    assert!(ServerConfig::default().load_with(args("--wallet cold"), |_| None).is_err());
+    assert!(ServerConfig::default().load_with(args("--db"), |_| None).is_err());
+    assert!(ServerConfig::default().load_with(args("--log-format yaml"), |_| None).is_err());
+    assert!(ServerConfig::default().load_with(args("--max-upload-bytes 1MB"), |_| None).is_err());
+  }
+
+  #[test]
+  fn test_help() {
+    assert!(wants_help(&args("--db file.db --help")));
+    assert!(wants_help(&args("-h")));
+    assert!(!wants_help(&args("--db file.db")));
+    assert!(ServerConfig::default().load_with(args("--help"), |_| None).is_ok());
+  }
+}
@@ -0,0 +1,183 @@
+//! Async access to an [`AnchorEngine`]
+//!
+//! [`AsyncEngine`] is a cheap, cloneable handle to one engine shared by every
+//! task, with no lock around it. Hashing runs on the blocking pool before the
+//! engine is involved at all; storage and wallet calls run there too, each
+//! holding the database connection or its wallet only for as long as it needs
+//! them. Lookups therefore never wait behind an anchor being broadcast, and
+//! anchors funded by different wallets proceed side by side.
+
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Instant;
+
+use tracing::Span;
+use validblock_hasher::hash_reader;
+use validblock_types::{AnchorRecord, Digest256, MemoPolicy, VBError};
+use validblock_wallet::psbt::Psbt;
+use validblock_wallet::{FeeBump, FeeCalc, WalletAdapter};
+
+use crate::metrics::{metrics, Operation};
+use crate::{AnchorEngine, Anchored};
+
+/// Shareable async handle to an [`AnchorEngine`]. Derefs to the engine for
+/// its fields and non-blocking calls
+pub struct AsyncEngine<W: WalletAdapter> {
+  engine: Arc<AnchorEngine<W>>,
+}
+
+impl<W: WalletAdapter> Clone for AsyncEngine<W> {
+  fn clone(&self) -> Self {
+    AsyncEngine { engine: self.engine.clone() }
+  }
+}
+
+impl<W: WalletAdapter> Deref for AsyncEngine<W> {
+  type Target = AnchorEngine<W>;
+
+  fn deref(&self) -> &AnchorEngine<W> {
+    &self.engine
+  }
+}
+
+impl<W: WalletAdapter> From<AnchorEngine<W>> for AsyncEngine<W> {
+  fn from(engine: AnchorEngine<W>) -> Self {
+    AsyncEngine { engine: Arc::new(engine) }
+  }
+}
+
+/// SHA-256 of `content`, computed on the blocking pool and counted in the
+/// hashing metrics
+pub async fn hash(content: Vec<u8>) -> Result<Digest256, VBError> {
+  blocking(move || {
+    let start = Instant::now();
+    let digest = hash_reader(content.as_slice())?;
+    metrics().hashed(content.len() as u64, start.elapsed().as_secs_f64());
+    Ok(digest)
+  })
+  .await
+}
+
+/// Run `f` on the blocking pool inside the caller's span
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, VBError> + Send + 'static) -> Result<T, VBError> {
+  let span = Span::current();
+  tokio::task::spawn_blocking(move || span.in_scope(f))
+    .await
+    .map_err(|e| VBError::Other(format!("Engine task failed: {}", e)))?
+}
+
+impl<W: WalletAdapter + Send + 'static> AsyncEngine<W> {
+  pub fn new(engine: AnchorEngine<W>) -> Self {
+    engine.into()
+  }
+
+  /// The shared engine, for blocking code
+  pub fn engine(&self) -> &Arc<AnchorEngine<W>> {
+    &self.engine
+  }
+
+  /// Run `f` against the engine on the blocking pool. Any number run at once
+  pub async fn run<T: Send + 'static>(
+    &self,
+    f: impl FnOnce(&AnchorEngine<W>) -> Result<T, VBError> + Send + 'static,
+  ) -> Result<T, VBError> {
+    let engine = self.engine.clone();
+    blocking(move || f(&engine)).await
+  }
+
+  /// [`AnchorEngine::anchor`] for file content, under idempotency `key` when
+  /// given (see [`AnchorEngine::anchor_with_key`])
+  pub async fn anchor(
+    &self,
+    content: Vec<u8>,
+    memo_policy: MemoPolicy,
+    owner: String,
+    memo: Option<Vec<u8>>,
+    key: Option<String>,
+  ) -> Result<Anchored, VBError> {
+    observe(Operation::Anchor, async {
+      let digest = hash(content).await?;
+      self
+        .run(move |engine| match key {
+          Some(key) => engine.anchor_digest_with_key(digest, memo_policy, &owner, memo.as_deref(), &key),
+          None => engine.anchor_digest(digest, memo_policy, &owner, memo.as_deref()),
+        })
+        .await
+    })
+    .await
+  }
+
+  /// [`AnchorEngine::verify_events`] for file content, alongside its digest
+  pub async fn verify(
+    &self,
+    content: Vec<u8>,
+    viewer: Option<String>,
+  ) -> Result<(Digest256, Vec<AnchorRecord>), VBError> {
+    observe(Operation::Verify, async {
+      let digest = hash(content).await?;
+      let looked_up = digest.clone();
+      let events = self.run(move |engine| engine.digest_events(&looked_up, viewer.as_deref())).await?;
+      Ok((digest, events))
+    })
+    .await
+  }
+
+  /// See [`AnchorEngine::exist_digest`]
+  pub async fn exist_digest(&self, digest: Digest256, viewer: Option<String>) -> Result<bool, VBError> {
+    self.run(move |engine| engine.exist_digest(&digest, viewer.as_deref())).await
+  }
+
+  /// A page of anchors, all of them or `viewer`'s own, earliest first
+  pub async fn list(&self, viewer: Option<String>, limit: u32, offset: u32) -> Result<Vec<AnchorRecord>, VBError> {
+    self.run(move |engine| engine.repo.list(viewer.as_deref(), limit, offset)).await
+  }
+
+  /// See [`AnchorEngine::pending_psbt`]
+  pub async fn pending_psbt(&self, digest: Digest256, owner: String) -> Result<Option<Psbt>, VBError> {
+    self.run(move |engine| engine.pending_psbt(&digest, &owner)).await
+  }
+
+  /// See [`AnchorEngine::submit_signed_psbt`]
+  pub async fn submit_signed_psbt(&self, signed: Psbt, owner: String) -> Result<AnchorRecord, VBError> {
+    self.run(move |engine| engine.submit_signed_psbt(signed, &owner)).await
+  }
+
+  /// See [`AnchorEngine::bump_fee`]
+  pub async fn bump_fee(
+    &self,
+    digest: Digest256,
+    owner: String,
+    method: FeeBump,
+    fee: FeeCalc,
+  ) -> Result<AnchorRecord, VBError> {
+    self.run(move |engine| engine.bump_fee(&digest, &owner, method, fee)).await
+  }
+
+  /// See [`AnchorEngine::check_confirmations`]
+  pub async fn check_confirmations(&self) -> Result<Vec<AnchorRecord>, VBError> {
+    self.run(|engine| engine.check_confirmations()).await
+  }
+
+  /// See [`AnchorEngine::restore_pending`]
+  pub async fn restore_pending(&self) -> Result<(), VBError> {
+    self.run(|engine| engine.restore_pending()).await
+  }
+
+    /// See [`AnchorEngine::recover_intents`]
+  pub async fn recover_intents(&self) -> Result<Vec<AnchorRecord>, VBError> {
+    self.run(|engine| engine.recover_intents()).await
+  }
+
+  /// Satoshis held by the wallet funding `owner`'s anchors
+  pub async fn balance(&self, owner: String) -> Result<Option<u64>, VBError> {
+    self.run(move |engine| engine.wallet_of(&owner).balance()).await
+  }
+}
+
+/// `f` as one `operation` in the metrics
+async fn observe<T>(operation: Operation, f: impl std::future::Future<Output = Result<T, VBError>>) -> Result<T, VBError> {
+  let start = Instant::now();
+  let result = f.await;
+  metrics().record(operation, start.elapsed().as_secs_f64(), result.is_ok());
+  result
+}
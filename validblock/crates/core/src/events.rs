@@ -0,0 +1,140 @@
+//! Anchor lifecycle events, fanned out to `SubscribeEvents` streams and
+//! webhooks
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use validblock_types::{Digest256, VBError};
+
+use crate::proto::EventNotice;
+
+/// Events a subscriber may fall behind by before it loses some
+const EVENT_BUFFER: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+  /// A record was written
+  Anchored,
+  /// Its transaction went out to the network
+  Broadcast,
+  /// Its transaction was mined
+  Confirmed,
+  /// Committing it on-chain failed
+  Failed,
+}
+
+impl EventKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      EventKind::Anchored => "anchored",
+      EventKind::Broadcast => "broadcast",
+      EventKind::Confirmed => "confirmed",
+      EventKind::Failed => "failed",
+    }
+  }
+}
+
+impl fmt::Display for EventKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for EventKind {
+  type Err = VBError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "anchored" => Ok(EventKind::Anchored),
+      "broadcast" => Ok(EventKind::Broadcast),
+      "confirmed" => Ok(EventKind::Confirmed),
+      "failed" => Ok(EventKind::Failed),
+      other => Err(VBError::InvalidArgument(format!("Unknown event kind: {}", other))),
+    }
+  }
+}
+
+/// What happened to one owner's anchor of a digest. This is also the JSON
+/// body of a webhook
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+  pub kind: EventKind,
+  pub digest: String,
+  pub owner: String,
+  pub timestamp: i64,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub txid: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub height: Option<u32>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+impl Event {
+  pub fn new(kind: EventKind, digest: &Digest256, owner: &str) -> Self {
+    Event {
+      kind,
+      digest: digest.to_string(),
+      owner: owner.to_string(),
+      timestamp: chrono::Utc::now().timestamp(),
+      txid: None,
+      height: None,
+      error: None,
+    }
+  }
+
+  pub fn with_txid(mut self, txid: Option<String>) -> Self {
+    self.txid = txid;
+    self
+  }
+
+  pub fn with_height(mut self, height: u32) -> Self {
+    self.height = Some(height);
+    self
+  }
+
+  pub fn with_error(mut self, error: &VBError) -> Self {
+    self.error = Some(error.to_string());
+    self
+  }
+}
+
+impl From<Event> for EventNotice {
+  fn from(event: Event) -> Self {
+    EventNotice {
+      kind: event.kind.to_string(),
+      digest: event.digest,
+      owner: event.owner,
+      timestamp: event.timestamp,
+      txid: event.txid.unwrap_or_default(),
+      height: event.height.unwrap_or_default(),
+      error: event.error.unwrap_or_default(),
+    }
+  }
+}
+
+/// In-process broadcast of engine events. Publishing never blocks; a
+/// subscriber that falls `EVENT_BUFFER` events behind is told it lagged
+#[derive(Clone, Debug)]
+pub struct EventBus {
+  sender: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+  fn default() -> Self {
+    EventBus { sender: broadcast::channel(EVENT_BUFFER).0 }
+  }
+}
+
+impl EventBus {
+  pub fn publish(&self, event: Event) {
+    // no subscribers is fine
+    let _ = self.sender.send(event);
+  }
+
+  pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+    self.sender.subscribe()
+  }
+}
@@ -0,0 +1,78 @@
+//! Readiness, reported through the standard `grpc.health.v1.Health` service
+//! and the `/readyz` probe. The server is ready while its database and wallet
+//! backend both answer
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+use tracing::warn;
+use validblock_types::VBError;
+use validblock_wallet::WalletAdapter;
+
+use crate::{AnchorEngine, AsyncEngine};
+
+/// Services whose status follows readiness; `""` is the server as a whole
+pub const SERVICES: [&str; 3] = ["", "validblock.AnchorService", "validblock.VerifyService"];
+
+/// Current readiness, mirrored into the gRPC health service
+pub struct Readiness {
+  ready: AtomicBool,
+  /// Shutting down; never ready again
+  stopping: AtomicBool,
+  reporter: Mutex<HealthReporter>,
+}
+
+impl Readiness {
+  /// Not ready until the first [`Readiness::set`]
+  pub fn new(reporter: HealthReporter) -> Self {
+    Readiness { ready: AtomicBool::new(false), stopping: AtomicBool::new(false), reporter: Mutex::new(reporter) }
+  }
+
+  pub fn is_ready(&self) -> bool {
+    self.ready.load(Ordering::SeqCst)
+  }
+
+  pub async fn set(&self, ready: bool) {
+    let ready = ready && !self.stopping.load(Ordering::SeqCst);
+    self.ready.store(ready, Ordering::SeqCst);
+    let status = if ready { ServingStatus::Serving } else { ServingStatus::NotServing };
+    let mut reporter = self.reporter.lock().await;
+    for service in SERVICES {
+      reporter.set_service_status(service, status).await;
+    }
+  }
+
+  /// Not ready from now on, whatever the probes find
+  pub async fn stop(&self) {
+    self.stopping.store(true, Ordering::SeqCst);
+    self.set(false).await;
+  }
+}
+
+/// Fail unless `engine`'s database and wallet backend answer
+pub fn probe<W: WalletAdapter>(engine: &AnchorEngine<W>) -> Result<(), VBError> {
+  engine.repo.ping()?;
+  engine.wallet().ping()
+}
+
+/// Probe `engine` every `interval`, updating `readiness`
+pub async fn watch_readiness<W: WalletAdapter + Send + 'static>(
+  engine: AsyncEngine<W>,
+  readiness: Arc<Readiness>,
+  interval: Duration,
+) {
+  loop {
+    let result = engine.run(|engine| probe(engine)).await;
+    if let Err(e) = &result {
+      if readiness.is_ready() {
+        warn!(error = %e, "Not ready");
+      }
+    }
+    readiness.set(result.is_ok()).await;
+    tokio::time::sleep(interval).await;
+  }
+}
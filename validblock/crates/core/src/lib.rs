@@ -1,59 +1,692 @@
 #![forbid(unsafe_code)]
+pub mod admin;
+pub mod audit;
+pub mod auth;
+pub mod config;
+pub mod engine;
+pub mod events;
+pub mod health;
+pub mod limits;
+pub mod metrics;
 pub mod proto;
+pub mod rescan;
+pub mod rest;
+pub mod server;
 pub mod services;
+pub mod status;
+pub mod telemetry;
+pub mod tenants;
+pub mod tls;
+pub mod webhooks;
 
 pub use validblock_types::*;
-use validblock_storage::AnchorRepo;
-use validblock_wallet::WalletAdapter;
+pub use validblock_wallet as wallet;
+pub use engine::AsyncEngine;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use validblock_storage::{AnchorIntent, AnchorRepo};
+use validblock_wallet::psbt::{self, Psbt};
+use validblock_wallet::{
+  verify_anchor, AnchorTx, ChainBackend, FeeBump, FeeCalc, Network, Txid, Verdict, WalletAdapter,
+};
 use validblock_hasher::hash_file;
+use tracing::{field, info, warn, Span};
+
+use crate::events::{Event, EventBus, EventKind};
+use crate::limits::Limits;
+use crate::metrics::{metrics, Operation};
+use crate::tenants::Tenant;
+
+/// Environment variable selecting the Bitcoin network for the server binaries
+pub const NETWORK_ENV: &str = "VALIDBLOCK_NETWORK";
+
+/// Environment variable turning on idempotent anchoring in the servers
+pub const IDEMPOTENT_ENV: &str = "VALIDBLOCK_IDEMPOTENT";
+
+/// Outcome of an anchoring request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Anchored {
+  pub record: AnchorRecord,
+  /// The record predates the request; nothing was written or spent
+  pub existing: bool,
+}
+
+/// `hash_file`, counted in the hashing metrics
+fn hash<P: AsRef<std::path::Path>>(path: P) -> Result<Digest256, VBError> {
+  let start = std::time::Instant::now();
+  let digest = hash_file(&path)?;
+  let bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+  metrics().hashed(bytes, start.elapsed().as_secs_f64());
+  Ok(digest)
+}
+
+/// Lock one of the engine's wallets. A call that panicked mid-way has no
+/// half-written state the wallet doesn't resync from the chain
+fn lock_wallet<W>(wallet: &Mutex<W>) -> MutexGuard<'_, W> {
+  wallet.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Verify a file against the chain alone: hash it and check that `txid`
+/// commits to the digest and is proven into a block of `network`. See
+/// [`verify_anchor`] for what the proof does and does not cover. `AnchorRepo`
+/// is not consulted, so a lost or forged database can't change the verdict
+pub fn verify_file_on_chain<P: AsRef<std::path::Path>, C: ChainBackend + ?Sized>(
+  path: P,
+  txid: &str,
+  chain: &C,
+  network: Network,
+) -> Result<Verdict, VBError> {
+  let digest = hash(&path)?;
+  let txid: Txid = txid
+    .trim()
+    .parse()
+    .map_err(|_| VBError::InvalidArgument(format!("Invalid txid: {}", txid)))?;
+  verify_anchor(chain, network, &txid, &digest)
+}
+
+/// The anchoring engine. Its methods take `&self` and block on hashing,
+/// SQLite and the wallet backend; share it through [`AsyncEngine`] from async
+/// code
 pub struct AnchorEngine<W: WalletAdapter> {
   pub repo: AnchorRepo,
-  pub wallet: W,
+  wallet: Mutex<W>,
+  pub network: Network,
+  /// Return the existing record for an already anchored digest instead of
+  /// `DbDuplicate`
+  pub idempotent: bool,
+  /// Lifecycle events of every anchor
+  pub events: EventBus,
+  /// URLs each event is queued for, see [`webhooks`]
+  pub webhooks: Vec<String>,
+  /// Only registered tenants may anchor, see [`tenants`]
+  pub require_tenants: bool,
+  /// Daily on-chain quotas, claimed together with each on-chain anchor
+  pub limits: Arc<Limits>,
+  /// Wallets of tenants funding their own anchors; everyone else's come
+  /// from `wallet`. Each is locked only for its own calls, so anchors funded
+  /// by different wallets, and everything not touching a wallet, run side by
+  /// side
+  tenant_wallets: HashMap<String, Mutex<W>>,
 }
 
 impl<W: WalletAdapter> AnchorEngine<W> {
   pub fn new(repo: AnchorRepo, wallet: W) -> Self {
-    Self { repo, wallet }
+    Self {
+      repo,
+      wallet: Mutex::new(wallet),
+      network: Network::Bitcoin,
+      idempotent: false,
+      events: EventBus::default(),
+      webhooks: vec![],
+      require_tenants: false,
+      limits: Arc::default(),
+      tenant_wallets: HashMap::new(),
+    }
+  }
+
+  /// Run against `network` instead of mainnet
+  pub fn with_network(mut self, network: Network) -> Self {
+    self.network = network;
+    self
+  }
+
+  /// Answer re-anchoring with the existing record rather than an error
+  pub fn with_idempotent(mut self, idempotent: bool) -> Self {
+    self.idempotent = idempotent;
+    self
+  }
+
+  /// Queue every event for delivery to `urls`
+  pub fn with_webhooks(mut self, urls: Vec<String>) -> Self {
+    self.webhooks = urls;
+    self
+  }
+
+  /// Refuse anchoring for owners that aren't registered tenants
+  pub fn with_required_tenants(mut self, required: bool) -> Self {
+    self.require_tenants = required;
+    self
+  }
+
+  /// Hold on-chain anchors to the quotas of `limits`
+  pub fn with_limits(mut self, limits: Arc<Limits>) -> Self {
+    self.limits = limits;
+    self
+  }
+
+  /// Fund `tenant`'s anchors from `wallet`
+  pub fn with_tenant_wallet(mut self, tenant: &str, wallet: W) -> Self {
+    self.tenant_wallets.insert(tenant.to_string(), Mutex::new(wallet));
+    self
+  }
+
+  /// Register a tenant, enabled
+  pub fn create_tenant(&self, id: &str, name: &str, fee_rate: Option<u16>) -> Result<Tenant, VBError> {
+    tenants::validate_id(id)?;
+    let tenant = Tenant {
+      id: id.to_string(),
+      name: name.to_string(),
+      disabled: false,
+      fee_rate,
+      created_at: chrono::Utc::now().timestamp(),
+    };
+    self.repo.insert_tenant(&tenant)?;
+    info!(tenant = id, "Tenant created");
+    Ok(tenant)
+  }
+
+  /// Disable or re-enable tenant `id`
+  pub fn set_tenant_disabled(&self, id: &str, disabled: bool) -> Result<Tenant, VBError> {
+    if !self.repo.set_tenant_disabled(id, disabled)? {
+      return Err(VBError::NotFound(format!("No tenant {}", id)));
+    }
+    info!(tenant = id, disabled, "Tenant updated");
+    self.repo.get_tenant(id)?.ok_or_else(|| VBError::NotFound(format!("No tenant {}", id)))
+  }
+
+  /// The tenant `owner` acts as. Disabled tenants are refused, and so are
+  /// unregistered owners when tenants are required; the default owner is the
+  /// operator and never a tenant
+  pub fn tenant(&self, owner: &str) -> Result<Option<Tenant>, VBError> {
+    if owner == DEFAULT_OWNER {
+      return Ok(None);
+    }
+    match self.repo.get_tenant(owner)? {
+      Some(tenant) if tenant.disabled => Err(VBError::PermissionDenied(format!("Tenant {} is disabled", owner))),
+      Some(tenant) => Ok(Some(tenant)),
+      None if self.require_tenants => Err(VBError::PermissionDenied(format!("{} is not a tenant", owner))),
+      None => Ok(None),
+    }
+  }
+
+  /// The default wallet, locked until the guard drops
+  pub fn wallet(&self) -> MutexGuard<'_, W> {
+    lock_wallet(&self.wallet)
+  }
+
+  /// The wallet funding `owner`'s anchors, locked until the guard drops
+  pub fn wallet_of(&self, owner: &str) -> MutexGuard<'_, W> {
+    lock_wallet(self.tenant_wallets.get(owner).unwrap_or(&self.wallet))
   }
 
-  /// Anchor a file, store record, call wallet (stub)
+  /// Whether tenant `id` funds its anchors from a wallet of its own
+  pub fn has_own_wallet(&self, id: &str) -> bool {
+    self.tenant_wallets.contains_key(id)
+  }
+
+  /// Publish `event` and queue it for each webhook. The anchor it reports
+  /// has already happened, so a queueing failure is only logged
+  fn emit(&self, event: Event) {
+    if !self.webhooks.is_empty() {
+      let body = serde_json::to_vec(&event).expect("events serialize");
+      for url in &self.webhooks {
+        if let Err(e) = self.repo.enqueue_delivery(url, &body, event.timestamp) {
+          warn!(kind = %event.kind, %url, error = %e, "Could not queue webhook delivery");
+        }
+      }
+    }
+    self.events.publish(event);
+  }
+
+  /// Anchor a file, store record, commit digest on-chain for `OnChain`
   pub fn anchor_file<P: AsRef<std::path::Path>>(
     &self,
     path: P,
     memo_policy: MemoPolicy,
   ) -> Result<AnchorRecord, VBError> {
-    let digest = hash_file(&path)?;
+    self.anchor(path, memo_policy, DEFAULT_OWNER, None).map(|anchored| anchored.record)
+  }
+
+  /// Anchor a file for `owner`, reporting whether the record already existed.
+  /// The memo is kept locally unless the policy disables it, and also
+  /// committed on-chain for `OnChain`
+  pub fn anchor<P: AsRef<std::path::Path>>(
+    &self,
+    path: P,
+    memo_policy: MemoPolicy,
+    owner: &str,
+    memo: Option<&[u8]>,
+  ) -> Result<Anchored, VBError> {
+    metrics().observe(Operation::Anchor, || {
+      let digest = hash(&path)?;
+      self.anchor_digest(digest, memo_policy, owner, memo)
+    })
+  }
+
+  /// Anchor a file under a client idempotency key. Retrying with the same key,
+  /// file, policy and memo returns the first result, whatever the engine's
+  /// mode; reusing the key for anything else is rejected. Keys are per owner
+  pub fn anchor_with_key<P: AsRef<std::path::Path>>(
+    &self,
+    path: P,
+    memo_policy: MemoPolicy,
+    owner: &str,
+    memo: Option<&[u8]>,
+    key: &str,
+  ) -> Result<Anchored, VBError> {
+    metrics().observe(Operation::Anchor, || {
+      self.tenant(owner)?;
+      let digest = hash(&path)?;
+      self.anchor_digest_with_key(digest, memo_policy, owner, memo, key)
+    })
+  }
+
+  /// [`AnchorEngine::anchor_with_key`] for an already hashed file
+  pub fn anchor_digest_with_key(
+    &self,
+    digest: Digest256,
+    memo_policy: MemoPolicy,
+    owner: &str,
+    memo: Option<&[u8]>,
+    key: &str,
+  ) -> Result<Anchored, VBError> {
+    self.tenant(owner)?;
+    let fingerprint = request_fingerprint(&digest, &memo_policy, memo);
+    if let Some((bound, digest)) = self.repo.get_idempotency_key(owner, key)? {
+      if bound != fingerprint {
+        return Err(VBError::InvalidArgument(format!(
+          "Idempotency key {} was already used with different parameters",
+          key
+        )));
+      }
+      let record = self
+        .repo
+        .get_owned(&digest, owner)?
+        .ok_or_else(|| VBError::NotFound(format!("No anchor for {}", digest)))?;
+      return Ok(Anchored { record, existing: true });
+    }
+    let anchored = self.anchor_digest(digest, memo_policy, owner, memo)?;
+    let ts = chrono::Utc::now().timestamp();
+    self.repo.put_idempotency_key(owner, key, &fingerprint, &anchored.record.digest, ts)?;
+    Ok(anchored)
+  }
+
+  /// [`AnchorEngine::anchor`] for an already hashed file
+  pub fn anchor_digest(
+    &self,
+    digest: Digest256,
+    memo_policy: MemoPolicy,
+    owner: &str,
+    memo: Option<&[u8]>,
+  ) -> Result<Anchored, VBError> {
+    // fields of the enclosing request span, if any
+    Span::current().record("digest", field::display(&digest)).record("policy", field::debug(&memo_policy));
+    let tenant = self.tenant(owner)?;
     let ts = chrono::Utc::now().timestamp();
     let memo = match memo_policy {
       MemoPolicy::Disabled => None,
-      _ => Some(vec![]), // stub: no memo content
+      _ => memo.filter(|m| !m.is_empty()),
+    };
+
+    // Settle duplicates before spending anything on-chain
+    if let Some(record) = self.repo.get_owned(&digest, owner)? {
+      return self.existing(record);
+    }
+    let tx = match memo_policy {
+      MemoPolicy::OnChain => {
+        let payload = AnchorPayload::new(digest.clone()).with_memo(memo.unwrap_or_default())?.encode();
+        // written before anything can reach the chain, see `recover_intents`
+        let intent = AnchorIntent {
+          digest: digest.clone(),
+          owner: owner.to_string(),
+          ts,
+          memo: memo.map(<[u8]>::to_vec),
+          payload: payload.clone(),
+        };
+        // claimed with the quota in one go, so concurrent requests can
+        // neither anchor the digest twice nor overspend the quota
+        match self.repo.reserve_intent(&intent, self.limits.on_chain_quota(owner, ts)) {
+          Err(VBError::DbDuplicate) => return self.concurrent_duplicate(&digest, owner),
+          result => result?,
+        }
+        let fee = tenant.and_then(|tenant| tenant.fee_rate).map(FeeCalc::fixed);
+        let mut wallet = self.wallet_of(owner);
+        let result = match fee {
+          Some(fee) => wallet.anchor_with_fee(&payload, fee),
+          None => wallet.anchor(&payload),
+        };
+        drop(wallet);
+        match result {
+          Ok(tx) => Some(tx),
+          // the transaction may be out: record it if the chain shows it,
+          // otherwise leave the intent for `recover_intents` to settle
+          Err(e @ VBError::BroadcastUnknown(_)) => match self.wallet_of(owner).find_anchor(&payload) {
+            Ok(Some(txid)) => {
+              warn!(%digest, owner, error = %e, %txid, "Broadcast reply lost, but the transaction is out");
+              Some(AnchorTx::Broadcast(txid))
+            }
+            _ => {
+              warn!(%digest, owner, error = %e, "Broadcast outcome unknown; kept for recovery");
+              self.emit(Event::new(EventKind::Failed, &digest, owner).with_error(&e));
+              return Err(e);
+            }
+          },
+          Err(e) => {
+            warn!(%digest, owner, error = %e, "On-chain anchoring failed");
+            self.repo.delete_intent(&digest, owner)?;
+            self.emit(Event::new(EventKind::Failed, &digest, owner).with_error(&e));
+            return Err(e);
+          }
+        }
+      }
+      _ => None,
+    };
+    let status = match &tx {
+      None => AnchorStatus::Local,
+      Some(AnchorTx::Broadcast(_)) => AnchorStatus::Broadcast,
+      Some(AnchorTx::AwaitingSignature(_)) => AnchorStatus::AwaitingSignature,
     };
-    
-    let txid = None;
     let rec = AnchorRecord {
       digest: digest.clone(),
+      owner: owner.to_string(),
       ts,
-      memo,
-      txid,
+      memo: memo.map(<[u8]>::to_vec),
+      txid: tx.as_ref().map(|tx| tx.txid().to_string()),
+      status,
+      txid_history: vec![],
     };
-    self.repo.insert(&rec)?;
+    match &tx {
+      None => match self.repo.insert(&rec) {
+        Err(VBError::DbDuplicate) => return self.concurrent_duplicate(&digest, owner),
+        result => result?,
+      },
+      Some(AnchorTx::Broadcast(_)) => self.repo.finalize_intent(&rec, None)?,
+      Some(AnchorTx::AwaitingSignature(psbt)) => self.repo.finalize_intent(&rec, Some(&psbt.serialize()))?,
+    }
+    info!(%digest, owner, status = %status, txid = rec.txid.as_deref(), "Anchored");
+    self.emit(Event::new(EventKind::Anchored, &digest, owner).with_txid(rec.txid.clone()));
+    if status == AnchorStatus::Broadcast {
+      self.emit(Event::new(EventKind::Broadcast, &digest, owner).with_txid(rec.txid.clone()));
+    }
+    Ok(Anchored { record: rec, existing: false })
+  }
+
+  /// Answer for an anchor `record` that already exists
+  fn existing(&self, record: AnchorRecord) -> Result<Anchored, VBError> {
+    if self.idempotent {
+      return Ok(Anchored { record, existing: true });
+    }
+    Err(VBError::DbDuplicate)
+  }
+
+  /// Answer for `owner`'s anchor of `digest` when a concurrent request got
+  /// there first. One still on its way on-chain has no record to return yet
+  fn concurrent_duplicate(&self, digest: &Digest256, owner: &str) -> Result<Anchored, VBError> {
+    match self.repo.get_owned(digest, owner)? {
+      Some(record) => self.existing(record),
+      None => Err(VBError::DbDuplicate),
+    }
+  }
+
+  /// Settle on-chain anchors interrupted between calling the wallet and
+  /// writing their record, as after a crash or a lost broadcast reply: an
+  /// intent whose payload the wallet finds on the chain is recorded as
+  /// broadcast, one it doesn't is dropped, since nothing was spent. Intents
+  /// the wallet can't check are kept for the next run. Returns the recovered
+  /// records
+  pub fn recover_intents(&self) -> Result<Vec<AnchorRecord>, VBError> {
+    let mut recovered = vec![];
+    for intent in self.repo.intents()? {
+      let (digest, owner) = (&intent.digest, intent.owner.as_str());
+      if self.repo.get_owned(digest, owner)?.is_some() {
+        self.repo.delete_intent(digest, owner)?;
+        continue;
+      }
+      let txid = match self.wallet_of(owner).find_anchor(&intent.payload) {
+        Ok(Some(txid)) => txid,
+        Ok(None) => {
+          warn!(%digest, owner, "Interrupted anchor never reached the chain; dropped");
+          self.repo.delete_intent(digest, owner)?;
+          let e = VBError::Other("Interrupted before broadcast".into());
+          self.emit(Event::new(EventKind::Failed, digest, owner).with_error(&e));
+          continue;
+        }
+        Err(e) => {
+          warn!(%digest, owner, error = %e, "Could not check an interrupted anchor; will retry");
+          continue;
+        }
+      };
+      let rec = AnchorRecord {
+        digest: intent.digest.clone(),
+        owner: intent.owner.clone(),
+        ts: intent.ts,
+        memo: intent.memo.clone(),
+        txid: Some(txid.to_string()),
+        status: AnchorStatus::Broadcast,
+        txid_history: vec![],
+      };
+      self.repo.finalize_intent(&rec, None)?;
+      info!(%digest, owner, %txid, "Recovered interrupted anchor");
+      self.emit(Event::new(EventKind::Anchored, digest, owner).with_txid(rec.txid.clone()));
+      self.emit(Event::new(EventKind::Broadcast, digest, owner).with_txid(rec.txid.clone()));
+      recovered.push(rec);
+    }
+    Ok(recovered)
+  }
+
+  /// Hand each wallet the stored PSBTs of the anchors it funds that still
+  /// await signature, so it doesn't spend their coins again, as after a
+  /// restart
+  pub fn restore_pending(&self) -> Result<(), VBError> {
+    let mut pending: HashMap<&str, Vec<Psbt>> = HashMap::new();
+    for rec in self.repo.with_status(AnchorStatus::AwaitingSignature)? {
+      if let Some(psbt) = self.pending_psbt(&rec.digest, &rec.owner)? {
+        let funder = self.tenant_wallets.get_key_value(rec.owner.as_str()).map_or(DEFAULT_OWNER, |(id, _)| id.as_str());
+        pending.entry(funder).or_default().push(psbt);
+      }
+    }
+    self.wallet().restore_pending(pending.get(DEFAULT_OWNER).map_or(&[], Vec::as_slice));
+    for (id, wallet) in &self.tenant_wallets {
+      lock_wallet(wallet).restore_pending(pending.get(id.as_str()).map_or(&[], Vec::as_slice));
+    }
+    Ok(())
+  }
+
+  /// Unsigned PSBT of `owner`'s anchor awaiting signature
+  pub fn pending_psbt(&self, digest: &Digest256, owner: &str) -> Result<Option<Psbt>, VBError> {
+    match self.repo.get_psbt(digest, owner)? {
+      Some(bytes) => Psbt::deserialize(&bytes)
+        .map(Some)
+        .map_err(|e| VBError::Db(format!("Stored PSBT is corrupt: {}", e))),
+      None => Ok(None),
+    }
+  }
+
+  /// Import a signed PSBT, broadcast it and mark its anchor as broadcast.
+  /// Only the anchor's owner, or the default owner, may submit it
+  pub fn submit_signed_psbt(&self, signed: Psbt, owner: &str) -> Result<AnchorRecord, VBError> {
+    let txid = signed.unsigned_tx.txid().to_string();
+    let mut rec = self
+      .repo
+      .get_by_txid(&txid)?
+      .filter(|rec| rec.status == AnchorStatus::AwaitingSignature)
+      .filter(|rec| rec.owner == owner || owner == DEFAULT_OWNER)
+      .ok_or_else(|| VBError::NotFound(format!("No anchor awaiting signature for {}", txid)))?;
+    self.tenant(&rec.owner)?;
+    if let Err(e) = self.wallet_of(&rec.owner).broadcast_signed(signed) {
+      self.emit(Event::new(EventKind::Failed, &rec.digest, &rec.owner).with_txid(Some(txid)).with_error(&e));
+      return Err(e);
+    }
+    self.repo.set_status(&rec.digest, &rec.owner, AnchorStatus::Broadcast)?;
+    self.repo.delete_psbt(&rec.digest, &rec.owner)?;
+    rec.status = AnchorStatus::Broadcast;
+    self.emit(Event::new(EventKind::Broadcast, &rec.digest, &rec.owner).with_txid(rec.txid.clone()));
     Ok(rec)
   }
 
-  /// Verify a file, return anchor record if present
+  /// Speed up the unconfirmed transaction of `owner`'s anchor of `digest`. An
+  /// RBF replacement becomes the record's txid, the old one moving to its
+  /// history; CPFP leaves the record as is
+  pub fn bump_fee(
+    &self,
+    digest: &Digest256,
+    owner: &str,
+    method: FeeBump,
+    fee: FeeCalc,
+  ) -> Result<AnchorRecord, VBError> {
+    self.tenant(owner)?;
+    let rec = self
+      .repo
+      .get_owned(digest, owner)?
+      .ok_or_else(|| VBError::NotFound(format!("No anchor for {}", digest)))?;
+    let txid: Txid = rec
+      .txid
+      .as_deref()
+      .ok_or_else(|| VBError::Other(format!("Anchor {} has no transaction", digest)))?
+      .parse()
+      .map_err(|_| VBError::Wallet("Invalid txid on record".into()))?;
+    let bumped = self.wallet_of(owner).bump_fee(&txid, method, fee)?;
+    match (method, bumped) {
+      (FeeBump::Cpfp, _) => {}
+      (FeeBump::Rbf, AnchorTx::Broadcast(new)) => {
+        self.repo.replace_txid(digest, owner, &new.to_string(), AnchorStatus::Broadcast)?;
+        self.repo.delete_psbt(digest, owner)?;
+        self.emit(Event::new(EventKind::Broadcast, digest, owner).with_txid(Some(new.to_string())));
+      }
+      (FeeBump::Rbf, AnchorTx::AwaitingSignature(psbt)) => {
+        if let Some(replaced) = self.pending_psbt(digest, owner)? {
+          self.wallet_of(owner).release(&replaced);
+        }
+        let new = psbt.unsigned_tx.txid().to_string();
+        self.repo.replace_txid(digest, owner, &new, AnchorStatus::AwaitingSignature)?;
+        self.repo.put_psbt(digest, owner, &psbt.serialize())?;
+      }
+    }
+    self
+      .repo
+      .get_owned(digest, owner)?
+      .ok_or_else(|| VBError::NotFound(format!("No anchor for {}", digest)))
+  }
+
+  /// Which of the transactions, current or replaced, of `owner`'s anchor made
+  /// it into a block, and at what height. The record is pointed at the
+  /// confirmed one
+  pub fn confirmed_txid<C: ChainBackend>(
+    &self,
+    digest: &Digest256,
+    owner: &str,
+    chain: &C,
+  ) -> Result<Option<(String, u32)>, VBError> {
+    let Some(rec) = self.repo.get_owned(digest, owner)? else {
+      return Ok(None);
+    };
+    for txid in rec.txid.iter().chain(rec.txid_history.iter().rev()) {
+      let parsed: Txid = txid.parse().map_err(|_| VBError::Wallet("Invalid txid on record".into()))?;
+      if let Some(height) = chain.confirmed_height(&parsed)? {
+        if rec.txid.as_ref() != Some(txid) {
+          self.repo.replace_txid(digest, owner, txid, AnchorStatus::Broadcast)?;
+        }
+        return Ok(Some((txid.clone(), height)));
+      }
+    }
+    Ok(None)
+  }
+
+  /// Mark broadcast anchors whose transaction, current or replaced, the
+  /// wallet sees in a block as confirmed, returning them. That includes
+  /// anchors whose RBF replacement still awaits signature: the transaction
+  /// it would replace may confirm first
+  pub fn check_confirmations(&self) -> Result<Vec<AnchorRecord>, VBError> {
+    let mut confirmed = vec![];
+    let replacing = self.repo.with_status(AnchorStatus::AwaitingSignature)?.into_iter().filter(|rec| !rec.txid_history.is_empty());
+    for rec in self.repo.with_status(AnchorStatus::Broadcast)?.into_iter().chain(replacing) {
+      for txid in rec.txid.iter().chain(rec.txid_history.iter().rev()) {
+        let parsed: Txid = txid.parse().map_err(|_| VBError::Wallet("Invalid txid on record".into()))?;
+        let Some(height) = self.wallet_of(&rec.owner).confirmed_height(&parsed)? else {
+          continue;
+        };
+        if rec.txid.as_ref() == Some(txid) {
+          self.repo.set_status(&rec.digest, &rec.owner, AnchorStatus::Confirmed)?;
+        } else {
+          self.repo.replace_txid(&rec.digest, &rec.owner, txid, AnchorStatus::Confirmed)?;
+          // an unsigned replacement can never be broadcast now
+          if let Some(replacement) = self.pending_psbt(&rec.digest, &rec.owner)? {
+            self.wallet_of(&rec.owner).release(&replacement);
+          }
+          self.repo.delete_psbt(&rec.digest, &rec.owner)?;
+        }
+        info!(digest = %rec.digest, owner = %rec.owner, txid, height, "Confirmed");
+        self.emit(Event::new(EventKind::Confirmed, &rec.digest, &rec.owner).with_txid(Some(txid.clone())).with_height(height));
+        confirmed.extend(self.repo.get_owned(&rec.digest, &rec.owner)?);
+        break;
+      }
+    }
+    Ok(confirmed)
+  }
+
+  /// Write the pending PSBT of `owner`'s anchor of `digest` to a `.psbt` file
+  pub fn export_psbt_file<P: AsRef<std::path::Path>>(
+    &self,
+    digest: &Digest256,
+    owner: &str,
+    path: P,
+  ) -> Result<(), VBError> {
+    let psbt = self
+      .pending_psbt(digest, owner)?
+      .ok_or_else(|| VBError::NotFound(format!("No PSBT pending for {}", digest)))?;
+    psbt::write_file(&psbt, path)
+  }
+
+  /// Read a signed `.psbt` file and submit it as the default owner
+  pub fn import_psbt_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<AnchorRecord, VBError> {
+    self.submit_signed_psbt(psbt::read_file(path)?, DEFAULT_OWNER)
+  }
+
+  /// Verify a file, return its earliest anchor record if present
   pub fn verify_file<P: AsRef<std::path::Path>>(
     &self,
     path: P,
   ) -> Result<Option<AnchorRecord>, VBError> {
-    let digest = hash_file(&path)?;
-    self.repo.get(&digest)
+    metrics().observe(Operation::Verify, || {
+      let digest = hash(&path)?;
+      Span::current().record("digest", field::display(&digest));
+      self.repo.get(&digest)
+    })
   }
 
-  /// check whether a digest exists
-  pub fn exist_digest(&self, digest: &Digest256) -> Result<bool, VBError> {
-    Ok(self.repo.get(digest)?.is_some())
+  /// Anchor events of a file, earliest first, restricted to `viewer`'s own
+  /// when given
+  pub fn verify_events<P: AsRef<std::path::Path>>(
+    &self,
+    path: P,
+    viewer: Option<&str>,
+  ) -> Result<Vec<AnchorRecord>, VBError> {
+    metrics().observe(Operation::Verify, || self.digest_events(&hash(&path)?, viewer))
   }
 
+  /// [`AnchorEngine::verify_events`] for an already hashed file
+  pub fn digest_events(&self, digest: &Digest256, viewer: Option<&str>) -> Result<Vec<AnchorRecord>, VBError> {
+    Span::current().record("digest", field::display(digest));
+    let mut events = self.repo.events(digest)?;
+    if let Some(viewer) = viewer {
+      self.tenant(viewer)?;
+      events.retain(|rec| rec.owner == viewer);
+    }
+    Ok(events)
+  }
+
+  /// Whether `digest` was anchored, by `viewer` when given
+  pub fn exist_digest(&self, digest: &Digest256, viewer: Option<&str>) -> Result<bool, VBError> {
+    match viewer {
+      Some(viewer) => {
+        self.tenant(viewer)?;
+        Ok(self.repo.get_owned(digest, viewer)?.is_some())
+      }
+      None => Ok(self.repo.get(digest)?.is_some()),
+    }
+  }
+
+}
+
+/// What makes two anchoring requests the same request
+fn request_fingerprint(digest: &Digest256, memo_policy: &MemoPolicy, memo: Option<&[u8]>) -> Vec<u8> {
+  let policy = match memo_policy {
+    MemoPolicy::LocalOnly => 0,
+    MemoPolicy::OnChain => 1,
+    MemoPolicy::Disabled => 2,
+  };
+  [&digest.0[..], &[policy], memo.unwrap_or_default()].concat()
 }
 
 // ============================================================================
@@ -67,9 +700,7 @@ mod tests {
   use std::fs::File;
   use std::io::Write;
   use tempfile::tempdir;
-
-  struct MockWallet;
-  impl WalletAdapter for MockWallet {}
+  use validblock_wallet::mock::MockWallet;
 
   #[test]
   fn test_anchor_and_verify_happy_path() {
@@ -87,4 +718,333 @@ mod tests {
     println!("found: {:?}", found);
     assert_eq!(rec, found);
   }
-} 
\ No newline at end of file
+
+  #[test]
+  fn test_anchor_on_chain_records_txid() {
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet);
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.txt");
+    std::fs::write(&file_path, b"on chain").unwrap();
+    let rec = engine.anchor_file(&file_path, MemoPolicy::OnChain).unwrap();
+    assert!(rec.txid.is_some());
+    assert_eq!(rec.status, AnchorStatus::Broadcast);
+    assert!(matches!(
+      engine.anchor_file(&file_path, MemoPolicy::OnChain),
+      Err(VBError::DbDuplicate)
+    ));
+  }
+
+  #[test]
+  fn test_idempotent_anchoring() {
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet).with_idempotent(true);
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.txt");
+    std::fs::write(&file_path, b"retry me").unwrap();
+    let first = engine.anchor(&file_path, MemoPolicy::OnChain, DEFAULT_OWNER, None).unwrap();
+    assert!(!first.existing);
+    let again = engine.anchor(&file_path, MemoPolicy::OnChain, DEFAULT_OWNER, None).unwrap();
+    assert!(again.existing);
+    assert_eq!(again.record, first.record);
+  }
+
+  #[test]
+  fn test_idempotency_key() {
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet);
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.txt");
+    std::fs::write(&file_path, b"pipeline output").unwrap();
+    let first = engine.anchor_with_key(&file_path, MemoPolicy::OnChain, DEFAULT_OWNER, None, "job-1").unwrap();
+    let retry = engine.anchor_with_key(&file_path, MemoPolicy::OnChain, DEFAULT_OWNER, None, "job-1").unwrap();
+    assert_eq!((retry.existing, retry.record), (true, first.record));
+
+    // same key, different request
+    let err = engine.anchor_with_key(&file_path, MemoPolicy::LocalOnly, DEFAULT_OWNER, None, "job-1").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidArgument);
+    // a fresh key for the same digest is a duplicate outside idempotent mode
+    assert!(matches!(
+      engine.anchor_with_key(&file_path, MemoPolicy::OnChain, DEFAULT_OWNER, None, "job-2"),
+      Err(VBError::DbDuplicate)
+    ));
+  }
+
+  #[test]
+  fn test_anchor_events_per_owner() {
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet);
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.txt");
+    std::fs::write(&file_path, b"shared contract").unwrap();
+    let first = engine.anchor(&file_path, MemoPolicy::LocalOnly, "acme", Some(b"signed")).unwrap().record;
+    let second = engine.anchor(&file_path, MemoPolicy::OnChain, "globex", Some(b"countersigned")).unwrap().record;
+    assert_eq!(first.memo.as_deref(), Some(&b"signed"[..]));
+    assert!(matches!(
+      engine.anchor(&file_path, MemoPolicy::OnChain, "acme", None),
+      Err(VBError::DbDuplicate)
+    ));
+
+    let all = engine.verify_events(&file_path, None).unwrap();
+    assert_eq!(all.iter().map(|r| r.owner.as_str()).collect::<Vec<_>>(), ["acme", "globex"]);
+    assert_eq!(engine.verify_file(&file_path).unwrap().unwrap().owner, "acme");
+    assert_eq!(engine.verify_events(&file_path, Some("globex")).unwrap(), vec![second]);
+    assert!(engine.verify_events(&file_path, Some("initech")).unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_tenants_isolated_with_own_wallet_and_fee() {
+    use validblock_wallet::mock::MockChain;
+    use validblock_wallet::HotWallet;
+
+    let (hd, chain) = MockChain::funded_hd(100_000);
+    let hot = |chain| HotWallet::new(hd.clone(), chain, FeeCalc::fixed(1));
+    // only hr's wallet has coins
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), hot(MockChain::default()))
+      .with_tenant_wallet("hr", hot(chain))
+      .with_required_tenants(true);
+    engine.create_tenant("hr", "Human Resources", Some(10)).unwrap();
+    engine.create_tenant("legal", "Legal", None).unwrap();
+    assert!(engine.create_tenant("hr", "again", None).is_err());
+    assert!(engine.create_tenant("Not An Id", "", None).is_err());
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.txt");
+    std::fs::write(&file_path, b"payroll").unwrap();
+    let rec = engine.anchor(&file_path, MemoPolicy::OnChain, "hr", None).unwrap().record;
+    let tx = &engine.wallet_of("hr").chain().broadcasts()[0];
+    assert_eq!(tx.txid().to_string(), rec.txid.unwrap());
+    assert!(100_000 - tx.output[1].value >= 10 * tx.weight().to_vbytes_ceil());
+    assert!(engine.anchor(&file_path, MemoPolicy::OnChain, "legal", None).is_err());
+
+    // tenants see only their own namespace
+    engine.anchor(&file_path, MemoPolicy::LocalOnly, "legal", None).unwrap();
+    assert!(engine.exist_digest(&rec.digest, Some("legal")).unwrap());
+    assert_eq!(engine.verify_events(&file_path, Some("hr")).unwrap().len(), 1);
+    assert_eq!(engine.verify_events(&file_path, None).unwrap().len(), 2);
+
+    engine.set_tenant_disabled("legal", true).unwrap();
+    let err = engine.anchor(&file_path, MemoPolicy::LocalOnly, "legal", None).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::PermissionDenied);
+    assert!(engine.verify_events(&file_path, Some("legal")).is_err());
+    assert!(engine.set_tenant_disabled("finance", true).is_err());
+    let err = engine.anchor(&file_path, MemoPolicy::LocalOnly, "finance", None).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::PermissionDenied);
+    // the operator is no tenant
+    engine.anchor(&file_path, MemoPolicy::LocalOnly, DEFAULT_OWNER, None).unwrap();
+  }
+
+  #[test]
+  fn test_watch_only_anchor_awaits_signature() {
+    use validblock_wallet::mock::MockChain;
+    use validblock_wallet::{FeeCalc, WatchOnlyWallet};
+
+    let (signer, hd, chain) = MockChain::funded_watch_only(50_000);
+    let wallet = WatchOnlyWallet::new(hd, chain, FeeCalc::fixed(1));
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), wallet);
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.txt");
+    std::fs::write(&file_path, b"offline signer").unwrap();
+    let rec = engine.anchor_file(&file_path, MemoPolicy::OnChain).unwrap();
+    assert_eq!(rec.status, AnchorStatus::AwaitingSignature);
+
+    let psbt_path = dir.path().join("anchor.psbt");
+    engine.export_psbt_file(&rec.digest, DEFAULT_OWNER, &psbt_path).unwrap();
+    let mut psbt = psbt::read_file(&psbt_path).unwrap();
+    signer.sign_psbt(&mut psbt).unwrap();
+    psbt::write_file(&psbt, &psbt_path).unwrap();
+
+    // another owner cannot submit it
+    let err = engine.submit_signed_psbt(psbt, "globex").unwrap_err();
+    assert!(matches!(err, VBError::NotFound(_)), "{}", err);
+    let done = engine.import_psbt_file(&psbt_path).unwrap();
+    assert_eq!(done.txid, rec.txid);
+    assert_eq!(engine.verify_file(&file_path).unwrap().unwrap().status, AnchorStatus::Broadcast);
+    assert!(engine.pending_psbt(&rec.digest, DEFAULT_OWNER).unwrap().is_none());
+    assert_eq!(engine.wallet().chain().broadcasts().len(), 1);
+
+    // an RBF bump waits for the signer, but the original may confirm first
+    let bumped = engine.bump_fee(&rec.digest, DEFAULT_OWNER, FeeBump::Rbf, FeeCalc::fixed(10)).unwrap();
+    assert_eq!(bumped.status, AnchorStatus::AwaitingSignature);
+    engine.wallet().chain().mine();
+    let confirmed = engine.check_confirmations().unwrap();
+    assert_eq!(confirmed.len(), 1);
+    assert_eq!((confirmed[0].status, &confirmed[0].txid), (AnchorStatus::Confirmed, &rec.txid));
+    assert!(engine.pending_psbt(&rec.digest, DEFAULT_OWNER).unwrap().is_none());
+  }
+
+  #[test]
+  fn test_rbf_bump_tracks_txid_history() {
+    use validblock_wallet::mock::MockChain;
+    use validblock_wallet::HotWallet;
+
+    let (hd, chain) = MockChain::funded_hd(100_000);
+    let wallet = HotWallet::new(hd, chain, FeeCalc::fixed(1));
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), wallet);
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.txt");
+    std::fs::write(&file_path, b"stuck in the mempool").unwrap();
+    let rec = engine.anchor_file(&file_path, MemoPolicy::OnChain).unwrap();
+    let bumped = engine.bump_fee(&rec.digest, DEFAULT_OWNER, FeeBump::Rbf, FeeCalc::fixed(25)).unwrap();
+    assert_ne!(bumped.txid, rec.txid);
+    assert_eq!(bumped.txid_history, vec![rec.txid.clone().unwrap()]);
+
+    let wallet = engine.wallet();
+    let chain = wallet.chain();
+    assert!(engine.confirmed_txid(&rec.digest, DEFAULT_OWNER, chain).unwrap().is_none());
+    let height = chain.mine();
+    let (txid, at) = engine.confirmed_txid(&rec.digest, DEFAULT_OWNER, chain).unwrap().unwrap();
+    assert_eq!((Some(txid.clone()), at), (bumped.txid, height));
+    drop(wallet);
+
+    // the chain alone vouches for the file, even with the database gone
+    let wallet = engine.wallet.into_inner().unwrap();
+    let chain = wallet.chain();
+    assert!(verify_file_on_chain(&file_path, &txid, chain, Network::Regtest).unwrap().is_verified());
+    std::fs::write(&file_path, b"tampered").unwrap();
+    assert_eq!(verify_file_on_chain(&file_path, &txid, chain, Network::Regtest).unwrap(), Verdict::Mismatch);
+  }
+
+  #[test]
+  fn test_lifecycle_events_and_confirmation() {
+    use crate::events::EventKind;
+    use validblock_wallet::hd::HdWallet;
+    use validblock_wallet::mock::{MockChain, TEST_MNEMONIC};
+    use validblock_wallet::HotWallet;
+
+    let (hd, chain) = MockChain::funded_hd(100_000);
+    let wallet = HotWallet::new(hd, chain, FeeCalc::fixed(1));
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), wallet)
+      .with_webhooks(vec!["https://hooks.example.com/a".into()]);
+    let mut events = engine.events.subscribe();
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.txt");
+    std::fs::write(&file_path, b"on its way to a block").unwrap();
+    let rec = engine.anchor_file(&file_path, MemoPolicy::OnChain).unwrap();
+    assert!(engine.check_confirmations().unwrap().is_empty());
+    let height = engine.wallet().chain().mine();
+    let confirmed = engine.check_confirmations().unwrap();
+    assert_eq!(confirmed[0].status, AnchorStatus::Confirmed);
+    assert!(engine.check_confirmations().unwrap().is_empty());
+
+    let kinds: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+    assert_eq!(
+      kinds.iter().map(|e| e.kind).collect::<Vec<_>>(),
+      vec![EventKind::Anchored, EventKind::Broadcast, EventKind::Confirmed]
+    );
+    assert_eq!(kinds[2].txid, rec.txid);
+    assert_eq!(kinds[2].height, Some(height));
+    // each event waits in the webhook queue too
+    assert_eq!(engine.repo.due_deliveries(i64::MAX, 10).unwrap().len(), 3);
+
+    // an anchor the wallet can't fund fails, and says so
+    std::fs::write(&file_path, b"no coins left").unwrap();
+    let broke = AnchorEngine::new(
+      AnchorRepo::memory().unwrap(),
+      HotWallet::new(HdWallet::from_mnemonic(TEST_MNEMONIC, "", Network::Regtest).unwrap(), MockChain::default(), FeeCalc::fixed(1)),
+    );
+    let mut events = broke.events.subscribe();
+    assert!(broke.anchor_file(&file_path, MemoPolicy::OnChain).is_err());
+    let failed = events.try_recv().unwrap();
+    assert_eq!(failed.kind, EventKind::Failed);
+    assert!(failed.error.is_some());
+  }
+
+  #[test]
+  fn test_recover_interrupted_anchors() {
+    use crate::events::EventKind;
+    use validblock_wallet::mock::MockChain;
+    use validblock_wallet::HotWallet;
+
+    let (hd, chain) = MockChain::funded_hd(100_000);
+    let wallet = HotWallet::new(hd, chain, FeeCalc::fixed(1));
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), wallet);
+    let mut events = engine.events.subscribe();
+
+    // one crash after the broadcast, one before it
+    let intent = |content: &[u8], ts: i64| {
+      let digest = validblock_hasher::hash_reader(content).unwrap();
+      let payload = AnchorPayload::new(digest.clone()).encode();
+      AnchorIntent { digest, owner: DEFAULT_OWNER.to_string(), ts, memo: None, payload }
+    };
+    let (sent, unsent) = (intent(b"broadcast", 100), intent(b"never sent", 200));
+    engine.repo.insert_intent(&sent).unwrap();
+    engine.repo.insert_intent(&unsent).unwrap();
+    let txid = engine.wallet().anchor(&sent.payload).unwrap().txid();
+
+    let recovered = engine.recover_intents().unwrap();
+    assert_eq!(recovered.len(), 1);
+    assert_eq!(recovered[0].txid, Some(txid.to_string()));
+    let rec = engine.repo.get_owned(&sent.digest, DEFAULT_OWNER).unwrap().unwrap();
+    assert_eq!((rec.status, rec.ts), (AnchorStatus::Broadcast, 100));
+    assert!(engine.repo.get_owned(&unsent.digest, DEFAULT_OWNER).unwrap().is_none());
+    assert!(engine.repo.intents().unwrap().is_empty());
+    let kinds: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).map(|e| e.kind).collect();
+    assert_eq!(kinds, vec![EventKind::Anchored, EventKind::Broadcast, EventKind::Failed]);
+
+    // the dropped anchor can simply be made again
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.txt");
+    std::fs::write(&file_path, b"never sent").unwrap();
+    let rec = engine.anchor_file(&file_path, MemoPolicy::OnChain).unwrap();
+    assert_eq!(rec.digest, unsent.digest);
+    assert!(engine.repo.intents().unwrap().is_empty());
+    assert_eq!(engine.wallet().chain().broadcasts().len(), 2);
+  }
+
+  #[test]
+  fn test_lost_broadcast_reply_keeps_the_anchor() {
+    use validblock_wallet::mock::MockChain;
+    use validblock_wallet::HotWallet;
+
+    let (hd, chain) = MockChain::funded_hd(100_000);
+    let wallet = HotWallet::new(hd, chain, FeeCalc::fixed(1));
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), wallet);
+
+    // the chain took the transaction, so the anchor stands
+    engine.wallet().chain().lose_next_reply();
+    let digest = validblock_hasher::hash_reader(&b"reply lost"[..]).unwrap();
+    let anchored = engine.anchor_digest(digest.clone(), MemoPolicy::OnChain, DEFAULT_OWNER, None).unwrap();
+    let sent = engine.wallet().chain().broadcasts()[0].txid().to_string();
+    assert_eq!((anchored.record.status, anchored.record.txid), (AnchorStatus::Broadcast, Some(sent)));
+    assert!(engine.repo.intents().unwrap().is_empty());
+
+    // a wallet that can't look the transaction up leaves it to recovery
+    struct Unreachable;
+    impl WalletAdapter for Unreachable {
+      fn anchor(&mut self, _payload: &[u8]) -> Result<AnchorTx, VBError> {
+        Err(VBError::BroadcastUnknown("timed out".into()))
+      }
+    }
+    let engine = AnchorEngine::new(AnchorRepo::memory().unwrap(), Unreachable);
+    let err = engine.anchor_digest(digest.clone(), MemoPolicy::OnChain, DEFAULT_OWNER, None).unwrap_err();
+    assert!(matches!(err, VBError::BroadcastUnknown(_)), "{}", err);
+    assert_eq!(engine.repo.intents().unwrap().len(), 1);
+    assert!(engine.repo.get_owned(&digest, DEFAULT_OWNER).unwrap().is_none());
+  }
+
+  #[test]
+  fn test_pending_psbt_coins_held_back_after_restart() {
+    use validblock_wallet::mock::MockChain;
+    use validblock_wallet::WatchOnlyWallet;
+
+    // the same single coin on every start
+    let start = |db: &str| {
+      let (_, hd, chain) = MockChain::funded_watch_only(50_000);
+      AnchorEngine::new(AnchorRepo::new(Some(db)).unwrap(), WatchOnlyWallet::new(hd, chain, FeeCalc::fixed(1)))
+    };
+    let dir = tempdir().unwrap();
+    let db = dir.path().join("anchors.db");
+    let db = db.to_str().unwrap();
+    let digest = |byte| Digest256([byte; 32]);
+
+    let engine = start(db);
+    let pending = engine.anchor_digest(digest(1), MemoPolicy::OnChain, DEFAULT_OWNER, None).unwrap();
+    assert_eq!(pending.record.status, AnchorStatus::AwaitingSignature);
+    drop(engine);
+
+    let engine = start(db);
+    engine.restore_pending().unwrap();
+    let err = engine.anchor_digest(digest(2), MemoPolicy::OnChain, DEFAULT_OWNER, None).unwrap_err();
+    assert!(matches!(err, VBError::Wallet(_)), "{}", err);
+  }
+}
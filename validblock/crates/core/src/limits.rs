@@ -0,0 +1,264 @@
+//! Request limits enforced by the server: per-client rate limits, a cap on
+//! uploaded file size, and per-owner daily quotas on on-chain anchors. Every
+//! refusal is `RESOURCE_EXHAUSTED`; `GetQuota` reports the caller's usage
+//!
+//! ```toml
+//! [limits]
+//! requests_per_minute = 120
+//! max_upload_bytes = 33554432
+//! daily_on_chain = 50
+//!
+//! [limits.owners]
+//! acme = 500
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+use serde::Deserialize;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use validblock_storage::AnchorRepo;
+use validblock_types::{VBError, DEFAULT_OWNER};
+
+use crate::services::Owner;
+use crate::status::to_status;
+
+/// Environment variable setting requests per minute per client
+pub const RATE_LIMIT_ENV: &str = "VALIDBLOCK_RATE_LIMIT";
+/// Environment variable setting the largest file accepted, in bytes
+pub const MAX_UPLOAD_ENV: &str = "VALIDBLOCK_MAX_UPLOAD_BYTES";
+
+/// Room for the fields of a request besides the file
+pub const MESSAGE_OVERHEAD: usize = 64 * 1024;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+  /// Sustained calls per minute per client, with bursts of as many; no limit
+  /// when unset
+  pub requests_per_minute: Option<u32>,
+  /// Largest file accepted for anchoring or verification
+  pub max_upload_bytes: usize,
+  /// On-chain anchors per owner per UTC day; no limit when unset
+  pub daily_on_chain: Option<u32>,
+  /// Per-owner overrides of `daily_on_chain`
+  pub owners: HashMap<String, u32>,
+}
+
+impl Default for LimitsConfig {
+  fn default() -> Self {
+    LimitsConfig { requests_per_minute: None, max_upload_bytes: 32 * 1024 * 1024, daily_on_chain: None, owners: HashMap::new() }
+  }
+}
+
+/// An owner's on-chain allowance for the current UTC day
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quota {
+  pub used: u32,
+  /// `None` when unlimited
+  pub limit: Option<u32>,
+  /// Unix time the day ends
+  pub resets_at: i64,
+}
+
+impl Quota {
+  pub fn remaining(&self) -> Option<u32> {
+    self.limit.map(|limit| limit.saturating_sub(self.used))
+  }
+}
+
+struct Bucket {
+  tokens: f64,
+  updated: Instant,
+}
+
+/// The configured limits and each client's rate-limit bucket
+pub struct Limits {
+  config: RwLock<LimitsConfig>,
+  buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl Default for Limits {
+  fn default() -> Self {
+    Limits::new(LimitsConfig::default())
+  }
+}
+
+impl Limits {
+  pub fn new(config: LimitsConfig) -> Self {
+    Limits { config: RwLock::new(config), buckets: Mutex::new(HashMap::new()) }
+  }
+
+  /// Take on the rate limits and quotas of `config`. The upload cap stays:
+  /// the services' message size limits were set from it at startup
+  pub fn reload(&self, config: LimitsConfig) {
+    let mut current = self.config.write().unwrap();
+    *current = LimitsConfig { max_upload_bytes: current.max_upload_bytes, ..config };
+  }
+
+  pub fn max_upload_bytes(&self) -> usize {
+    self.config.read().unwrap().max_upload_bytes
+  }
+
+  /// Take one call from `client`'s bucket
+  pub fn check_rate(&self, client: &str) -> Result<(), VBError> {
+    let Some(per_minute) = self.config.read().unwrap().requests_per_minute else {
+      return Ok(());
+    };
+    let capacity = per_minute as f64;
+    let now = Instant::now();
+    let mut buckets = self.buckets.lock().unwrap();
+    let bucket = buckets.entry(client.to_string()).or_insert(Bucket { tokens: capacity, updated: now });
+    let refill = now.duration_since(bucket.updated).as_secs_f64() * capacity / 60.0;
+    bucket.tokens = (bucket.tokens + refill).min(capacity);
+    bucket.updated = now;
+    if bucket.tokens < 1.0 {
+      return Err(VBError::ResourceExhausted(format!("Rate limit of {} requests per minute reached", per_minute)));
+    }
+    bucket.tokens -= 1.0;
+    Ok(())
+  }
+
+  /// Refuse files over the upload cap
+  pub fn check_upload(&self, len: usize) -> Result<(), VBError> {
+    let max = self.max_upload_bytes();
+    if len > max {
+      return Err(VBError::ResourceExhausted(format!("File of {} bytes exceeds the {} byte limit", len, max)));
+    }
+    Ok(())
+  }
+
+  /// `owner`'s on-chain allowance on the day of `now`
+  pub fn quota(&self, repo: &AnchorRepo, owner: &str, now: i64) -> Result<Quota, VBError> {
+    let day_start = day_start(now);
+    Ok(Quota {
+      used: repo.on_chain_count(owner, day_start)?,
+      limit: self.daily_on_chain(owner),
+      resets_at: day_start + SECONDS_PER_DAY,
+    })
+  }
+
+  /// `owner`'s daily on-chain limit and the start of the day of `now`, to
+  /// claim an anchor against with [`AnchorRepo::reserve_intent`]; `None`
+  /// when unlimited
+  pub fn on_chain_quota(&self, owner: &str, now: i64) -> Option<(u32, i64)> {
+    self.daily_on_chain(owner).map(|limit| (limit, day_start(now)))
+  }
+
+  fn daily_on_chain(&self, owner: &str) -> Option<u32> {
+    let config = self.config.read().unwrap();
+    config.owners.get(owner).copied().or(config.daily_on_chain)
+  }
+
+  /// `inner`, then a rate-limit check
+  pub fn interceptor<I: Interceptor>(self: &Arc<Self>, inner: I) -> LimitInterceptor<I> {
+    LimitInterceptor { limits: self.clone(), inner }
+  }
+}
+
+/// Start of the UTC day of `now`
+fn day_start(now: i64) -> i64 {
+  now - now.rem_euclid(SECONDS_PER_DAY)
+}
+
+/// Whose bucket a call is taken from: its owner, or for the shared default
+/// owner, its peer address
+pub fn client_key(owner: &str, peer: Option<std::net::SocketAddr>) -> String {
+  match peer {
+    Some(addr) if owner == DEFAULT_OWNER => addr.ip().to_string(),
+    _ => owner.to_string(),
+  }
+}
+
+/// Rate-limits calls that `inner` let through
+#[derive(Clone)]
+pub struct LimitInterceptor<I> {
+  limits: Arc<Limits>,
+  inner: I,
+}
+
+impl<I: Interceptor> Interceptor for LimitInterceptor<I> {
+  fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+    let request = self.inner.call(request)?;
+    let client = client_key(&Owner::of(&request), request.remote_addr());
+    self.limits.check_rate(&client).map_err(to_status)?;
+    Ok(request)
+  }
+}
+
+/// tonic refuses messages over the decoding limit with `OUT_OF_RANGE`, which
+/// the services never send otherwise; report them as `RESOURCE_EXHAUSTED`
+/// like every other limit
+pub fn oversize_exhausted<B>(mut res: http::Response<B>) -> http::Response<B> {
+  let out_of_range = (tonic::Code::OutOfRange as i32).to_string();
+  if res.headers().get("grpc-status").is_some_and(|status| status.as_bytes() == out_of_range.as_bytes()) {
+    let exhausted = (tonic::Code::ResourceExhausted as i32).to_string();
+    res.headers_mut().insert("grpc-status", exhausted.parse().expect("digits are a valid header value"));
+  }
+  res
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use validblock_types::{AnchorRecord, AnchorStatus, Digest256};
+
+  #[test]
+  fn test_rate_limit_per_client() {
+    let limits = Limits::new(LimitsConfig { requests_per_minute: Some(2), ..Default::default() });
+    assert!(limits.check_rate("acme").is_ok());
+    assert!(limits.check_rate("acme").is_ok());
+    let err = limits.check_rate("acme").unwrap_err();
+    assert_eq!(err.code(), validblock_types::ErrorCode::ResourceExhausted);
+    assert!(limits.check_rate("globex").is_ok());
+    assert!(Limits::default().check_rate("acme").is_ok());
+
+    // a reload lifts the limit but keeps the upload cap
+    limits.reload(LimitsConfig { requests_per_minute: None, max_upload_bytes: 1, ..Default::default() });
+    assert!(limits.check_rate("acme").is_ok());
+    assert_eq!(limits.max_upload_bytes(), LimitsConfig::default().max_upload_bytes);
+  }
+
+  #[test]
+  fn test_daily_quota() {
+    let config = LimitsConfig {
+      max_upload_bytes: 10,
+      daily_on_chain: Some(1),
+      owners: [("acme".to_string(), 2)].into(),
+      ..Default::default()
+    };
+    let limits = Limits::new(config);
+    assert!(limits.check_upload(10).is_ok());
+    assert!(limits.check_upload(11).is_err());
+
+    let repo = AnchorRepo::memory().unwrap();
+    let now = chrono::Utc::now().timestamp();
+    for (i, owner) in ["acme", "globex"].into_iter().enumerate() {
+      repo.insert(&AnchorRecord {
+        digest: Digest256([i as u8; 32]),
+        owner: owner.into(),
+        ts: now,
+        memo: None,
+        txid: Some(format!("tx{}", i)),
+        status: AnchorStatus::Broadcast,
+        txid_history: vec![],
+      }).unwrap();
+    }
+    let quota = limits.quota(&repo, "acme", now).unwrap();
+    assert_eq!((quota.used, quota.limit, quota.remaining()), (1, Some(2), Some(1)));
+    assert_eq!(quota.resets_at % SECONDS_PER_DAY, 0);
+    assert_eq!(limits.on_chain_quota("acme", now), Some((2, quota.resets_at - SECONDS_PER_DAY)));
+    assert_eq!(limits.on_chain_quota("globex", now).map(|(limit, _)| limit), Some(1));
+    assert_eq!(Limits::default().on_chain_quota("acme", now), None);
+    // tomorrow starts afresh
+    assert_eq!(limits.quota(&repo, "globex", quota.resets_at).unwrap().used, 0);
+  }
+}
@@ -0,0 +1,210 @@
+//! Prometheus metrics, and the plain HTTP endpoint operators scrape them and
+//! probe the server from:
+//!
+//! - `GET /metrics`: text exposition format
+//! - `GET /healthz`: 200 while the process serves
+//! - `GET /readyz`: 200 while the database and wallet backend answer, else 503
+//!
+//! Metrics are process-wide, so every engine and gateway feeds the same
+//! registry.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use prometheus::{
+  Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use tracing::warn;
+use validblock_types::{AnchorStatus, ErrorCode, VBError};
+use validblock_wallet::WalletAdapter;
+
+use crate::health::Readiness;
+use crate::{AnchorEngine, AsyncEngine};
+
+/// Environment variable setting the metrics and probes address
+pub const METRICS_LISTEN_ENV: &str = "VALIDBLOCK_METRICS_LISTEN";
+
+/// Engine operations timed and counted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+  Anchor,
+  Verify,
+}
+
+impl Operation {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Operation::Anchor => "anchor",
+      Operation::Verify => "verify",
+    }
+  }
+}
+
+pub struct Metrics {
+  registry: Registry,
+  /// By operation and outcome, `ok` or `error`
+  operations: IntCounterVec,
+  operation_seconds: HistogramVec,
+  hashed_bytes: IntCounter,
+  hash_seconds: Histogram,
+  /// Errors returned to clients, by `ErrorCode`
+  errors: IntCounterVec,
+  db_size: IntGauge,
+  wallet_balance: IntGauge,
+  pending_confirmations: IntGauge,
+}
+
+/// The process-wide metrics
+pub fn metrics() -> &'static Metrics {
+  static METRICS: OnceLock<Metrics> = OnceLock::new();
+  METRICS.get_or_init(|| Metrics::new().expect("metric definitions are valid"))
+}
+
+impl Metrics {
+  fn new() -> Result<Self, prometheus::Error> {
+    let registry = Registry::new_custom(Some("validblock".into()), None)?;
+    let operations = IntCounterVec::new(
+      Opts::new("operations_total", "Anchor and verify requests handled"),
+      &["operation", "outcome"],
+    )?;
+    let operation_seconds = HistogramVec::new(
+      HistogramOpts::new("operation_duration_seconds", "Time to anchor or verify a file"),
+      &["operation"],
+    )?;
+    let hashed_bytes = IntCounter::new("hashed_bytes_total", "Bytes of file content hashed")?;
+    let hash_seconds = Histogram::with_opts(HistogramOpts::new("hash_duration_seconds", "Time to hash one file"))?;
+    let errors = IntCounterVec::new(Opts::new("errors_total", "Errors returned to clients"), &["code"])?;
+    let db_size = IntGauge::new("db_size_bytes", "Size of the anchors database")?;
+    let wallet_balance = IntGauge::new("wallet_balance_sats", "Funds held by the anchoring wallet")?;
+    let pending_confirmations =
+      IntGauge::new("pending_confirmations", "Broadcast anchors not yet seen in a block")?;
+    registry.register(Box::new(operations.clone()))?;
+    registry.register(Box::new(operation_seconds.clone()))?;
+    registry.register(Box::new(hashed_bytes.clone()))?;
+    registry.register(Box::new(hash_seconds.clone()))?;
+    registry.register(Box::new(errors.clone()))?;
+    registry.register(Box::new(db_size.clone()))?;
+    registry.register(Box::new(wallet_balance.clone()))?;
+    registry.register(Box::new(pending_confirmations.clone()))?;
+    Ok(Metrics {
+      registry,
+      operations,
+      operation_seconds,
+      hashed_bytes,
+      hash_seconds,
+      errors,
+      db_size,
+      wallet_balance,
+      pending_confirmations,
+    })
+  }
+
+  /// Run `f` as one `operation`, recording its duration and outcome
+  pub fn observe<T>(&self, operation: Operation, f: impl FnOnce() -> Result<T, VBError>) -> Result<T, VBError> {
+    let start = Instant::now();
+    let result = f();
+    self.record(operation, start.elapsed().as_secs_f64(), result.is_ok());
+    result
+  }
+
+  /// Count one `operation` that took `seconds`
+  pub fn record(&self, operation: Operation, seconds: f64, ok: bool) {
+    self.operation_seconds.with_label_values(&[operation.as_str()]).observe(seconds);
+    let outcome = if ok { "ok" } else { "error" };
+    self.operations.with_label_values(&[operation.as_str(), outcome]).inc();
+  }
+
+  /// Count `bytes` hashed in `seconds`
+  pub fn hashed(&self, bytes: u64, seconds: f64) {
+    self.hashed_bytes.inc_by(bytes);
+    self.hash_seconds.observe(seconds);
+  }
+
+  /// Count an error sent back to a client
+  pub fn error(&self, code: ErrorCode) {
+    self.errors.with_label_values(&[code.as_str()]).inc();
+  }
+
+  /// Read the database gauges from `engine`
+  pub fn sample_storage<W: WalletAdapter>(&self, engine: &AnchorEngine<W>) -> Result<(), VBError> {
+    self.db_size.set(engine.repo.size_bytes()? as i64);
+    self.pending_confirmations.set(engine.repo.with_status(AnchorStatus::Broadcast)?.len() as i64);
+    Ok(())
+  }
+
+  /// Read the wallet balance from `engine`; wallets without funds leave it at 0
+  pub fn sample_wallet<W: WalletAdapter>(&self, engine: &AnchorEngine<W>) -> Result<(), VBError> {
+    if let Some(sats) = engine.wallet().balance()? {
+      self.wallet_balance.set(sats as i64);
+    }
+    Ok(())
+  }
+
+  /// Every metric in the text exposition format
+  pub fn render(&self) -> String {
+    let mut out = vec![];
+    TextEncoder::new().encode(&self.registry.gather(), &mut out).expect("text encoding does not fail");
+    String::from_utf8(out).expect("text encoding is UTF-8")
+  }
+}
+
+// ============================================================================
+// HTTP endpoint
+// ============================================================================
+
+struct Probe<W: WalletAdapter> {
+  engine: AsyncEngine<W>,
+  readiness: Arc<Readiness>,
+}
+
+impl<W: WalletAdapter> Clone for Probe<W> {
+  fn clone(&self) -> Self {
+    Probe { engine: self.engine.clone(), readiness: self.readiness.clone() }
+  }
+}
+
+/// `/metrics`, `/healthz` and `/readyz` over `engine`
+pub fn router<W: WalletAdapter + Send + 'static>(
+  engine: AsyncEngine<W>,
+  readiness: Arc<Readiness>,
+) -> Router {
+  Router::new()
+    .route("/metrics", get(scrape::<W>))
+    .route("/healthz", get(|| async { "ok" }))
+    .route("/readyz", get(ready::<W>))
+    .with_state(Probe { engine, readiness })
+}
+
+/// Serve [`router`] on `addr` until the listener fails
+pub async fn serve<W: WalletAdapter + Send + 'static>(
+  addr: SocketAddr,
+  engine: AsyncEngine<W>,
+  readiness: Arc<Readiness>,
+) -> Result<(), VBError> {
+  axum::Server::try_bind(&addr)
+    .map_err(|e| VBError::Other(format!("Cannot serve metrics on {}: {}", addr, e)))?
+    .serve(router(engine, readiness).into_make_service())
+    .await
+    .map_err(|e| VBError::Other(format!("Metrics endpoint failed: {}", e)))
+}
+
+async fn scrape<W: WalletAdapter + Send + 'static>(State(probe): State<Probe<W>>) -> impl IntoResponse {
+  if let Err(e) = probe.engine.run(|engine| metrics().sample_storage(engine)).await {
+    warn!(error = %e, "Could not sample storage metrics");
+  }
+  ([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], metrics().render())
+}
+
+async fn ready<W: WalletAdapter>(State(probe): State<Probe<W>>) -> (StatusCode, &'static str) {
+  if probe.readiness.is_ready() {
+    (StatusCode::OK, "ready")
+  } else {
+    (StatusCode::SERVICE_UNAVAILABLE, "not ready")
+  }
+}
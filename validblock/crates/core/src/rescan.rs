@@ -0,0 +1,182 @@
+//! Rebuild anchor records from the chain when `anchors.db` is lost
+
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use validblock_storage::AnchorRepo;
+use validblock_types::{AnchorPayload, AnchorRecord, AnchorStatus, VBError, DEFAULT_OWNER};
+use validblock_wallet::tx::op_return_payload;
+use validblock_wallet::{ChainBackend, HdWallet, Transaction};
+
+/// What a rescan looked at and what it restored
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RescanReport {
+  /// Transactions inspected
+  pub scanned: usize,
+  /// Transactions carrying a ValidBlock payload
+  pub found: usize,
+  /// Records written; digests already in the repo are left alone
+  pub restored: usize,
+}
+
+/// Restore anchors from every transaction touching the wallet's watched
+/// addresses. Confirmed anchors are restored as confirmed and timestamped
+/// with their block time, unconfirmed ones as broadcast with the current time.
+pub fn rescan_wallet<C: ChainBackend + ?Sized>(
+  repo: &AnchorRepo,
+  hd: &HdWallet,
+  chain: &C,
+) -> Result<RescanReport, VBError> {
+  let mut seen = HashSet::new();
+  let mut txids = Vec::new();
+  for (_, _, script) in hd.watched_scripts()? {
+    for txid in chain.script_history(&script)? {
+      if seen.insert(txid) {
+        txids.push(txid);
+      }
+    }
+  }
+
+  // Oldest first, so the first confirmation of a digest wins
+  let mut found = Vec::new();
+  for txid in &txids {
+    let Some(tx) = chain.transaction(txid)? else {
+      continue;
+    };
+    if anchored_payload(&tx).is_none() {
+      continue;
+    }
+    let block_time = match chain.merkle_proof(txid)? {
+      Some(proof) => {
+        let header = chain
+          .block_header(&proof.block_hash)?
+          .ok_or_else(|| VBError::Other(format!("Unknown block {}", proof.block_hash)))?;
+        Some((proof.height, header.time))
+      }
+      None => None,
+    };
+    found.push((block_time, tx));
+  }
+  found.sort_by_key(|(block_time, _)| block_time.map_or(u32::MAX, |(height, _)| height));
+
+  let mut report = RescanReport { scanned: txids.len(), ..Default::default() };
+  for (block_time, tx) in found {
+    report.found += 1;
+    let (ts, status) = match block_time {
+      Some((_, time)) => (time as i64, AnchorStatus::Confirmed),
+      None => (chrono::Utc::now().timestamp(), AnchorStatus::Broadcast),
+    };
+    if restore(repo, &tx, ts, status)? {
+      report.restored += 1;
+    }
+  }
+  Ok(report)
+}
+
+/// Restore anchors, as confirmed, from every transaction in the blocks at
+/// `heights`, whoever made them
+pub fn rescan_blocks<C: ChainBackend + ?Sized>(
+  repo: &AnchorRepo,
+  chain: &C,
+  heights: RangeInclusive<u32>,
+) -> Result<RescanReport, VBError> {
+  let mut report = RescanReport::default();
+  for height in heights {
+    let Some(hash) = chain.block_hash(height)? else {
+      break;
+    };
+    let block = chain
+      .block(&hash)?
+      .ok_or_else(|| VBError::Other(format!("Block {} missing from backend", hash)))?;
+    for tx in &block.txdata {
+      report.scanned += 1;
+      if anchored_payload(tx).is_some() {
+        report.found += 1;
+        if restore(repo, tx, block.header.time as i64, AnchorStatus::Confirmed)? {
+          report.restored += 1;
+        }
+      }
+    }
+  }
+  Ok(report)
+}
+
+/// Valid ValidBlock payload carried by a transaction, if any
+pub fn anchored_payload(tx: &Transaction) -> Option<AnchorPayload> {
+  AnchorPayload::decode(&op_return_payload(tx)?).ok()
+}
+
+fn restore(repo: &AnchorRepo, tx: &Transaction, ts: i64, status: AnchorStatus) -> Result<bool, VBError> {
+  let Some(AnchorPayload { digest, memo, .. }) = anchored_payload(tx) else {
+    return Ok(false);
+  };
+  if repo.exists_digest(&digest)? {
+    return Ok(false);
+  }
+  repo.insert(&AnchorRecord {
+    digest,
+    owner: DEFAULT_OWNER.to_string(),
+    ts,
+    memo,
+    txid: Some(tx.txid().to_string()),
+    status,
+    txid_history: vec![],
+  })?;
+  Ok(true)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use validblock_types::Digest256;
+  use validblock_wallet::mock::MockChain;
+  use validblock_wallet::{FeeCalc, HotWallet, WalletAdapter};
+
+  fn anchored_wallet() -> HotWallet<MockChain> {
+    let (hd, chain) = MockChain::funded_hd(100_000);
+    let mut wallet = HotWallet::new(hd, chain, FeeCalc::fixed(1));
+    wallet.anchor(&AnchorPayload::new(Digest256([1; 32])).encode()).unwrap();
+    wallet.chain().mine();
+    // not ours: no payload header
+    wallet.anchor(&[2; 32]).unwrap();
+    let memo = AnchorPayload::new(Digest256([3; 32])).with_memo(b"invoice 7").unwrap();
+    wallet.anchor(&memo.encode()).unwrap();
+    wallet
+  }
+
+  #[test]
+  fn test_rescan_wallet_restores_anchors() {
+    let wallet = anchored_wallet();
+    let repo = AnchorRepo::memory().unwrap();
+    let report = rescan_wallet(&repo, wallet.hd(), wallet.chain()).unwrap();
+    assert_eq!(report.found, 2);
+    assert_eq!(report.restored, 2);
+
+    let header = wallet.chain().block_header(&wallet.chain().block_hash(1).unwrap().unwrap()).unwrap().unwrap();
+    let rec = repo.get(&Digest256([1; 32])).unwrap().unwrap();
+    assert_eq!(rec.ts, header.time as i64);
+    assert_eq!(rec.status, AnchorStatus::Confirmed);
+    // still in the mempool
+    let rec = repo.get(&Digest256([3; 32])).unwrap().unwrap();
+    assert_eq!(rec.memo.as_deref(), Some(&b"invoice 7"[..]));
+    assert_eq!(rec.status, AnchorStatus::Broadcast);
+
+    // a second pass finds the same anchors but writes nothing
+    assert_eq!(rescan_wallet(&repo, wallet.hd(), wallet.chain()).unwrap().restored, 0);
+  }
+
+  #[test]
+  fn test_rescan_blocks_reads_block_range() {
+    let wallet = anchored_wallet();
+    wallet.chain().mine();
+    let repo = AnchorRepo::memory().unwrap();
+    let report = rescan_blocks(&repo, wallet.chain(), 2..=10).unwrap();
+    assert_eq!((report.scanned, report.found, report.restored), (2, 1, 1));
+    assert!(repo.get(&Digest256([1; 32])).unwrap().is_none());
+    assert_eq!(repo.get(&Digest256([3; 32])).unwrap().unwrap().status, AnchorStatus::Confirmed);
+  }
+}
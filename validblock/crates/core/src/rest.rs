@@ -0,0 +1,452 @@
+//! REST/JSON gateway to the anchor engine, served beside the gRPC services
+//!
+//! Requests whose content type isn't `application/grpc*` are routed here by
+//! [`RestLayer`], so the gateway shares the gRPC listener, TLS and
+//! credentials. File contents travel base64-encoded; errors come back as
+//! `{"error": {"code": "NOT_FOUND", "message": "..."}}` with a matching HTTP
+//! status. The OpenAPI document is served at `/openapi.json`.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::body::HttpBody;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use tonic::body::BoxBody;
+use tonic::codegen::{BoxFuture, StdError};
+use tonic::metadata::MetadataMap;
+use tonic::transport::Body;
+use tonic::Status;
+use tower::{Layer, Service, ServiceExt};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
+use validblock_types::{AnchorRecord, AnchorStatus, Digest256, ErrorCode, VBError};
+use validblock_wallet::{psbt, WalletAdapter};
+
+use crate::audit::AuditTarget;
+use crate::auth::{Authenticator, Role};
+use crate::limits::{client_key, Limits, MESSAGE_OVERHEAD};
+use crate::metrics::metrics;
+use crate::services::{idempotency_key, Owner, DEFAULT_LIST_LIMIT, MAX_LIST_LIMIT};
+use crate::{AsyncEngine, MemoPolicy};
+
+// ============================================================================
+// Bodies
+// ============================================================================
+
+/// A file to anchor
+#[derive(Deserialize, ToSchema)]
+pub struct AnchorBody {
+  /// File contents, base64
+  pub content: String,
+  /// Note stored with the anchor
+  #[serde(default)]
+  pub memo: Option<String>,
+  /// Commit the digest in a Bitcoin transaction, not just the database
+  #[serde(default)]
+  pub on_chain: bool,
+}
+
+/// A file to look up
+#[derive(Deserialize, ToSchema)]
+pub struct VerifyBody {
+  /// File contents, base64
+  pub content: String,
+}
+
+/// Page of `GET /anchors`
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+  /// Most events to return; 100 when unset, at most 1000
+  pub limit: Option<u32>,
+  /// Events to skip, earliest first
+  pub offset: Option<u32>,
+}
+
+/// Outcome of `POST /anchors`
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AnchorReply {
+  pub digest: String,
+  pub timestamp: i64,
+  pub status: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub txid: Option<String>,
+  /// Base64 PSBT when the anchor awaits an offline signature
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub psbt: Option<String>,
+  /// The digest was already anchored; nothing was written or spent
+  pub existing: bool,
+}
+
+/// One anchor of a digest
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct EventReply {
+  pub digest: String,
+  pub owner: String,
+  pub timestamp: i64,
+  pub status: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub memo: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub txid: Option<String>,
+}
+
+/// Anchors of one digest, earliest first
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct VerifyReply {
+  pub verified: bool,
+  pub digest: String,
+  pub events: Vec<EventReply>,
+}
+
+/// A page of anchors, earliest first
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ListReply {
+  pub events: Vec<EventReply>,
+}
+
+/// Body of every error reply
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ErrorReply {
+  pub error: ErrorBody,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ErrorBody {
+  /// One of the `ErrorCode` names, e.g. `DUPLICATE_DIGEST`
+  pub code: String,
+  pub message: String,
+}
+
+impl From<AnchorRecord> for EventReply {
+  fn from(record: AnchorRecord) -> Self {
+    EventReply {
+      digest: record.digest.to_string(),
+      owner: record.owner,
+      timestamp: record.ts,
+      status: record.status.to_string(),
+      memo: record.memo.map(|m| String::from_utf8_lossy(&m).into_owned()),
+      txid: record.txid,
+    }
+  }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// `VBError` as an HTTP reply
+pub struct RestError(VBError);
+
+impl From<VBError> for RestError {
+  fn from(e: VBError) -> Self {
+    RestError(e)
+  }
+}
+
+impl From<JsonRejection> for RestError {
+  fn from(e: JsonRejection) -> Self {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+      return RestError(VBError::ResourceExhausted(e.body_text()));
+    }
+    RestError(VBError::InvalidArgument(e.body_text()))
+  }
+}
+
+/// HTTP status each error code is reported under
+pub fn http_status(code: ErrorCode) -> StatusCode {
+  match code {
+    ErrorCode::InvalidDigest | ErrorCode::InvalidPayload | ErrorCode::InvalidArgument => StatusCode::BAD_REQUEST,
+    ErrorCode::DuplicateDigest => StatusCode::CONFLICT,
+    ErrorCode::NotFound => StatusCode::NOT_FOUND,
+    ErrorCode::Unauthenticated => StatusCode::UNAUTHORIZED,
+    ErrorCode::PermissionDenied => StatusCode::FORBIDDEN,
+    ErrorCode::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+    ErrorCode::Wallet => StatusCode::UNPROCESSABLE_ENTITY,
+    ErrorCode::Io | ErrorCode::Database | ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+  }
+}
+
+impl IntoResponse for RestError {
+  fn into_response(self) -> Response {
+    let code = self.0.code();
+    metrics().error(code);
+    let body = ErrorReply { error: ErrorBody { code: code.as_str().to_string(), message: self.0.to_string() } };
+    (http_status(code), Json(body)).into_response()
+  }
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+struct Gateway<W: WalletAdapter> {
+  engine: AsyncEngine<W>,
+  auth: Arc<Authenticator>,
+  limits: Arc<Limits>,
+}
+
+impl<W: WalletAdapter> Clone for Gateway<W> {
+  fn clone(&self) -> Self {
+    Gateway { engine: self.engine.clone(), auth: self.auth.clone(), limits: self.limits.clone() }
+  }
+}
+
+impl<W: WalletAdapter> Gateway<W> {
+  /// The caller's owner, once authenticated and within its rate limit
+  fn authenticate(&self, headers: &HeaderMap, role: Role) -> Result<String, VBError> {
+    let owner = self.auth.authenticate(&MetadataMap::from_headers(headers.clone()), role)?.0;
+    self.limits.check_rate(&client_key(&owner, None))?;
+    Ok(owner)
+  }
+}
+
+fn decode(content: &str) -> Result<Vec<u8>, VBError> {
+  base64::engine::general_purpose::STANDARD
+    .decode(content)
+    .map_err(|_| VBError::InvalidArgument("content must be base64".into()))
+}
+
+/// Anchor a file
+#[utoipa::path(
+  post,
+  path = "/anchors",
+  request_body = AnchorBody,
+  params(("Idempotency-Key" = Option<String>, Header, description = "Replays of the same request return its first outcome")),
+  responses(
+    (status = 200, description = "Anchored", body = AnchorReply),
+    (status = 409, description = "Already anchored", body = ErrorReply),
+    (status = 429, description = "Rate limit, upload cap or daily quota reached", body = ErrorReply),
+  ),
+  security(("bearer" = []))
+)]
+async fn anchor<W: WalletAdapter + Send + 'static>(
+  State(gateway): State<Gateway<W>>,
+  headers: HeaderMap,
+  body: Result<Json<AnchorBody>, JsonRejection>,
+) -> Result<(Extension<AuditTarget>, Json<AnchorReply>), RestError> {
+  let owner = gateway.authenticate(&headers, Role::Anchorer)?;
+  let key = idempotency_key(&MetadataMap::from_headers(headers))?;
+  let Json(body) = body?;
+  let content = decode(&body.content)?;
+  gateway.limits.check_upload(content.len())?;
+  let policy = if body.on_chain { MemoPolicy::OnChain } else { MemoPolicy::LocalOnly };
+  let memo = body.memo.map(String::into_bytes).filter(|m| !m.is_empty());
+
+  let engine = &gateway.engine;
+  let anchored = engine.anchor(content, policy, owner.clone(), memo, key).await?;
+  let record = anchored.record;
+  let psbt = match record.status {
+    AnchorStatus::AwaitingSignature => {
+      engine.pending_psbt(record.digest.clone(), owner).await?.map(|p| psbt::to_base64(&p))
+    }
+    _ => None,
+  };
+  let target = Extension(AuditTarget(record.digest.to_string()));
+  Ok((target, Json(AnchorReply {
+    digest: record.digest.to_string(),
+    timestamp: record.ts,
+    status: record.status.to_string(),
+    txid: record.txid,
+    psbt,
+    existing: anchored.existing,
+  })))
+}
+
+/// Anchors of a digest, earliest first
+#[utoipa::path(
+  get,
+  path = "/anchors/{digest}",
+  params(("digest" = String, Path, description = "Hex SHA-256 digest")),
+  responses(
+    (status = 200, description = "Anchored", body = VerifyReply),
+    (status = 404, description = "Never anchored", body = ErrorReply),
+  ),
+  security(("bearer" = []))
+)]
+async fn get_anchor<W: WalletAdapter + Send + 'static>(
+  State(gateway): State<Gateway<W>>,
+  headers: HeaderMap,
+  Path(digest): Path<String>,
+) -> Result<Json<VerifyReply>, RestError> {
+  let owner = gateway.authenticate(&headers, Role::Viewer)?;
+  let digest: Digest256 = digest.parse()?;
+  let viewer = Owner::viewer(&owner).map(str::to_string);
+  let looked_up = digest.clone();
+  let events = gateway.engine.run(move |engine| engine.digest_events(&looked_up, viewer.as_deref())).await?;
+  if events.is_empty() {
+    return Err(VBError::NotFound(format!("No anchor for {}", digest)).into());
+  }
+  Ok(Json(VerifyReply {
+    verified: true,
+    digest: digest.to_string(),
+    events: events.into_iter().map(EventReply::from).collect(),
+  }))
+}
+
+/// Look a file up; `verified` is false when it was never anchored
+#[utoipa::path(
+  post,
+  path = "/verify",
+  request_body = VerifyBody,
+  responses((status = 200, description = "Looked up", body = VerifyReply)),
+  security(("bearer" = []))
+)]
+async fn verify<W: WalletAdapter + Send + 'static>(
+  State(gateway): State<Gateway<W>>,
+  headers: HeaderMap,
+  body: Result<Json<VerifyBody>, JsonRejection>,
+) -> Result<(Extension<AuditTarget>, Json<VerifyReply>), RestError> {
+  let owner = gateway.authenticate(&headers, Role::Viewer)?;
+  let Json(body) = body?;
+  let content = decode(&body.content)?;
+  gateway.limits.check_upload(content.len())?;
+
+  let viewer = Owner::viewer(&owner).map(str::to_string);
+  let (digest, events) = gateway.engine.verify(content, viewer).await?;
+  let digest = digest.to_string();
+  Ok((Extension(AuditTarget(digest.clone())), Json(VerifyReply {
+    verified: !events.is_empty(),
+    digest,
+    events: events.into_iter().map(EventReply::from).collect(),
+  })))
+}
+
+/// Anchors, earliest first
+#[utoipa::path(
+  get,
+  path = "/anchors",
+  params(ListQuery),
+  responses((status = 200, description = "A page of anchors", body = ListReply)),
+  security(("bearer" = []))
+)]
+async fn list_anchors<W: WalletAdapter + Send + 'static>(
+  State(gateway): State<Gateway<W>>,
+  headers: HeaderMap,
+  Query(query): Query<ListQuery>,
+) -> Result<Json<ListReply>, RestError> {
+  let owner = gateway.authenticate(&headers, Role::Viewer)?;
+  let limit = query.limit.filter(|n| *n > 0).unwrap_or(DEFAULT_LIST_LIMIT).min(MAX_LIST_LIMIT);
+  let viewer = Owner::viewer(&owner).map(str::to_string);
+  let records = gateway.engine.list(viewer, limit, query.offset.unwrap_or(0)).await?;
+  Ok(Json(ListReply { events: records.into_iter().map(EventReply::from).collect() }))
+}
+
+async fn openapi_json() -> impl IntoResponse {
+  ([(header::CONTENT_TYPE, "application/json")], ApiDoc::openapi().to_pretty_json().unwrap_or_default())
+}
+
+// ============================================================================
+// OpenAPI
+// ============================================================================
+
+/// The gateway's OpenAPI document, generated from the handlers above
+#[derive(OpenApi)]
+#[openapi(
+  info(title = "ValidBlock REST API"),
+  paths(anchor, get_anchor, verify, list_anchors),
+  components(schemas(AnchorBody, VerifyBody, AnchorReply, EventReply, VerifyReply, ListReply, ErrorReply, ErrorBody)),
+  modifiers(&BearerAuth)
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+  fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+    let components = openapi.components.get_or_insert_with(Default::default);
+    let scheme = HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build();
+    components.add_security_scheme("bearer", SecurityScheme::Http(scheme));
+  }
+}
+
+/// The gateway's routes over `engine`, guarded by `auth` and `limits`
+pub fn router<W: WalletAdapter + Send + Sync + 'static>(
+  engine: AsyncEngine<W>,
+  auth: Arc<Authenticator>,
+  limits: Arc<Limits>,
+) -> Router {
+  // file contents arrive base64-encoded inside JSON
+  let body_limit = limits.max_upload_bytes().div_ceil(3) * 4 + MESSAGE_OVERHEAD;
+  Router::new()
+    .route("/anchors", post(anchor::<W>).get(list_anchors::<W>))
+    .route("/anchors/:digest", get(get_anchor::<W>))
+    .route("/verify", post(verify::<W>))
+    .route("/openapi.json", get(openapi_json))
+    .layer(DefaultBodyLimit::max(body_limit))
+    .with_state(Gateway { engine, auth, limits })
+}
+
+// ============================================================================
+// Layer
+// ============================================================================
+
+/// Sends everything that isn't gRPC or gRPC-Web to the gateway's router
+#[derive(Clone)]
+pub struct RestLayer {
+  router: Router,
+}
+
+impl RestLayer {
+  pub fn new(router: Router) -> Self {
+    RestLayer { router }
+  }
+}
+
+impl<S> Layer<S> for RestLayer {
+  type Service = RestService<S>;
+
+  fn layer(&self, inner: S) -> Self::Service {
+    RestService { inner, router: self.router.clone() }
+  }
+}
+
+#[derive(Clone)]
+pub struct RestService<S> {
+  inner: S,
+  router: Router,
+}
+
+fn is_grpc(request: &http::Request<Body>) -> bool {
+  request
+    .headers()
+    .get(header::CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| value.starts_with("application/grpc"))
+}
+
+impl<S> Service<http::Request<Body>> for RestService<S>
+where
+  S: Service<http::Request<Body>, Response = http::Response<BoxBody>> + Send + 'static,
+  S::Future: Send + 'static,
+  S::Error: Into<StdError>,
+{
+  type Response = http::Response<BoxBody>;
+  type Error = StdError;
+  type Future = BoxFuture<Self::Response, StdError>;
+
+  fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready(cx).map_err(Into::into)
+  }
+
+  fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+    if is_grpc(&request) {
+      let response = self.inner.call(request);
+      Box::pin(async move { response.await.map_err(Into::into) })
+    } else {
+      let response = self.router.clone().oneshot(request);
+      Box::pin(async move {
+        let response: Response = response.await.unwrap_or_else(|e: Infallible| match e {});
+        Ok(response.map(|body| body.map_err(|e| Status::internal(e.to_string())).boxed_unsync()))
+      })
+    }
+  }
+}
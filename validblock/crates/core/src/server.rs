@@ -0,0 +1,304 @@
+//! The ValidBlock gRPC server, assembled from a [`ServerConfig`]
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tonic::transport::Server;
+use tonic::service::interceptor::InterceptedService;
+use tower::util::{option_layer, MapResponseLayer};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::{DefaultOnFailure, DefaultOnResponse, TraceLayer};
+use tracing::{error, info, warn, Level};
+use validblock_storage::AnchorRepo;
+use validblock_types::VBError;
+use validblock_wallet::esplora::{self, EsploraBackend};
+use validblock_wallet::mock::MockWallet;
+use validblock_wallet::{FeeCalc, HdWallet, HotWallet, Keystore, WalletAdapter, WatchOnlyWallet};
+
+use crate::audit::AuditLayer;
+use crate::auth::{Authenticator, Role};
+use crate::config::{ServerConfig, WalletBackend, KEYSTORE_PASSPHRASE_ENV};
+use crate::health::{watch_readiness, Readiness};
+use crate::limits::{oversize_exhausted, Limits, MESSAGE_OVERHEAD};
+use crate::metrics::{self, metrics};
+use crate::proto::admin_service_server::AdminServiceServer;
+use crate::proto::anchor_service_server::AnchorServiceServer;
+use crate::proto::tenant_service_server::TenantServiceServer;
+use crate::proto::verify_service_server::VerifyServiceServer;
+use crate::rest::{self, RestLayer};
+use crate::services::{AdminServiceImpl, AnchorServiceImpl, TenantServiceImpl, VerifyServiceImpl};
+use crate::telemetry::{request_span, REQUEST_ID_HEADER};
+use crate::tenants::TenantWalletConfig;
+use crate::webhooks::Dispatcher;
+use crate::{AnchorEngine, AsyncEngine};
+
+/// How often failed webhook deliveries are looked at again
+const WEBHOOK_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the database and wallet backend are probed for readiness
+const READINESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Response headers browsers may read from a gRPC-Web reply
+const GRPC_WEB_EXPOSED_HEADERS: [&str; 3] = ["grpc-status", "grpc-message", "grpc-status-details-bin"];
+
+/// Open the configured wallet and serve until the listener fails or a
+/// shutdown signal arrives
+pub async fn run(config: ServerConfig) -> Result<(), VBError> {
+  let fee = FeeCalc::fixed(config.wallet.fee_rate);
+  match config.wallet.backend {
+    WalletBackend::Mock => {
+      let tenants = tenant_wallets(&config, |_, _| Ok(MockWallet))?;
+      serve(config, MockWallet, tenants).await
+    }
+    WalletBackend::WatchOnly => {
+      let open = |xpub: &str| -> Result<_, VBError> {
+        Ok(WatchOnlyWallet::new(HdWallet::watch_only(xpub, config.network)?, chain_backend(&config)?, fee))
+      };
+      let xpub = config
+        .wallet
+        .xpub
+        .as_deref()
+        .ok_or_else(|| VBError::InvalidArgument("The watch-only wallet needs wallet.xpub".into()))?;
+      let wallet = open(xpub)?;
+      let tenants = tenant_wallets(&config, |id, tenant| {
+        open(tenant.xpub.as_deref().ok_or_else(|| {
+          VBError::InvalidArgument(format!("The watch-only wallet of tenant {} needs an xpub", id))
+        })?)
+      })?;
+      serve(config, wallet, tenants).await
+    }
+    WalletBackend::Hot => {
+      let passphrase = std::env::var(KEYSTORE_PASSPHRASE_ENV)
+        .map_err(|_| VBError::InvalidArgument(format!("The hot wallet needs {}", KEYSTORE_PASSPHRASE_ENV)))?;
+      let open = |path: &Path| -> Result<_, VBError> {
+        let hd = Keystore::load(path)?.decrypt(&passphrase)?.hd_wallet(config.network)?;
+        Ok(HotWallet::new(hd, chain_backend(&config)?, fee))
+      };
+      let path = config
+        .wallet
+        .keystore
+        .as_deref()
+        .ok_or_else(|| VBError::InvalidArgument("The hot wallet needs wallet.keystore".into()))?;
+      let wallet = open(path)?;
+      let tenants = tenant_wallets(&config, |id, tenant| {
+        open(tenant.keystore.as_deref().ok_or_else(|| {
+          VBError::InvalidArgument(format!("The hot wallet of tenant {} needs a keystore", id))
+        })?)
+      })?;
+      serve(config, wallet, tenants).await
+    }
+  }
+}
+
+/// Each configured tenant wallet, opened with `open`
+fn tenant_wallets<W>(
+  config: &ServerConfig,
+  open: impl Fn(&str, &TenantWalletConfig) -> Result<W, VBError>,
+) -> Result<HashMap<String, W>, VBError> {
+  config.tenants.wallets.iter().map(|(id, tenant)| Ok((id.clone(), open(id, tenant)?))).collect()
+}
+
+fn chain_backend(config: &ServerConfig) -> Result<EsploraBackend, VBError> {
+  let url = match &config.wallet.chain_api {
+    Some(url) => url.as_str(),
+    None => esplora::default_url(config.network)
+      .ok_or_else(|| VBError::InvalidArgument("No public chain API for this network; set wallet.chain_api".into()))?,
+  };
+  Ok(EsploraBackend::new(url))
+}
+
+/// Serve `AnchorService`, `VerifyService`, `TenantService`, `AdminService`,
+/// the gRPC health service and, when enabled, the REST gateway and metrics endpoint over
+/// `wallet`, with `tenant_wallets` funding their tenants' anchors, until
+/// [`shutdown_signal`]
+pub async fn serve<W: WalletAdapter + Send + Sync + 'static>(
+  config: ServerConfig,
+  wallet: W,
+  tenant_wallets: HashMap<String, W>,
+) -> Result<(), VBError> {
+  serve_until(config, wallet, tenant_wallets, shutdown_signal()).await
+}
+
+/// [`serve`], shutting down gracefully once `signal` completes: the server
+/// reports itself not serving, stops accepting connections and gives calls in
+/// flight `shutdown_grace` seconds to finish. Anchors a previous run left
+/// between broadcast and record are settled before the first call, see
+/// [`AnchorEngine::recover_intents`], and wallets hold back the coins of
+/// PSBTs still awaiting signature, see [`AnchorEngine::restore_pending`]
+pub async fn serve_until<W: WalletAdapter + Send + Sync + 'static>(
+  config: ServerConfig,
+  wallet: W,
+  tenant_wallets: HashMap<String, W>,
+  signal: impl Future<Output = ()>,
+) -> Result<(), VBError> {
+  let repo = AnchorRepo::new(config.db.as_deref())?;
+  let auth = Arc::new(Authenticator::from_config(&config.auth)?.with_repo(&repo)?);
+  let limits = Arc::new(Limits::new(config.limits.clone()));
+  let engine = AnchorEngine::new(repo, wallet)
+    .with_network(config.network)
+    .with_idempotent(config.idempotent)
+    .with_webhooks(config.webhooks.iter().map(|hook| hook.url.clone()).collect())
+    .with_required_tenants(config.tenants.required)
+    .with_limits(limits.clone());
+  let engine = AsyncEngine::new(
+    tenant_wallets.into_iter().fold(engine, |engine, (id, wallet)| engine.with_tenant_wallet(&id, wallet)),
+  );
+  engine.restore_pending().await?;
+  let recovered = engine.recover_intents().await?;
+  if !recovered.is_empty() {
+    info!(count = recovered.len(), "Recovered interrupted anchors");
+  }
+  if !config.webhooks.is_empty() {
+    tokio::spawn(Dispatcher::new(engine.clone(), &config.webhooks).run(WEBHOOK_RETRY_INTERVAL));
+  }
+  tokio::spawn(watch_confirmations(engine.clone(), Duration::from_secs(config.confirm_interval.max(1))));
+  let (reporter, health) = tonic_health::server::health_reporter();
+  let readiness = Arc::new(Readiness::new(reporter));
+  tokio::spawn(watch_readiness(engine.clone(), readiness.clone(), READINESS_INTERVAL));
+  if let Some(addr) = config.metrics_listen {
+    let (engine, readiness) = (engine.clone(), readiness.clone());
+    tokio::spawn(async move {
+      if let Err(e) = metrics::serve(addr, engine, readiness).await {
+        error!("{}", e);
+      }
+    });
+    info!(%addr, "Serving metrics");
+  }
+
+  let mut builder = Server::builder();
+  if let Some(tls) = &config.tls {
+    builder = builder.tls_config(tls.server_config()?).map_err(transport_err)?;
+  }
+  let cors = (!config.cors_origins.is_empty()).then(|| cors_layer(&config.cors_origins)).transpose()?;
+  let grpc_web = config.grpc_web.then(tonic_web::GrpcWebLayer::new);
+  let gateway = config.rest.then(|| RestLayer::new(rest::router(engine.clone(), auth.clone(), limits.clone())));
+  let max_message = limits.max_upload_bytes() + MESSAGE_OVERHEAD;
+  let request_id = http::HeaderName::from_static(REQUEST_ID_HEADER);
+  let trace = TraceLayer::new_for_grpc()
+    .make_span_with(request_span)
+    .on_response(DefaultOnResponse::new().level(Level::INFO))
+    .on_failure(DefaultOnFailure::new().level(Level::WARN));
+  let router = builder
+    // gRPC-Web and REST arrive over HTTP/1.1
+    .accept_http1(config.grpc_web || config.rest)
+    .layer(SetRequestIdLayer::new(request_id.clone(), MakeRequestUuid))
+    .layer(trace)
+    .layer(PropagateRequestIdLayer::new(request_id))
+    .layer(option_layer(cors))
+    .layer(AuditLayer::new(engine.clone(), auth.clone()))
+    .layer(option_layer(gateway))
+    .layer(option_layer(grpc_web))
+    .layer(MapResponseLayer::new(oversize_exhausted))
+    // probes carry no credentials
+    .add_service(health)
+    .add_service(InterceptedService::new(
+      AnchorServiceServer::new(AnchorServiceImpl::new(engine.clone()).with_limits(limits.clone()))
+        .max_decoding_message_size(max_message),
+      limits.interceptor(auth.interceptor(Role::Anchorer)),
+    ))
+    .add_service(InterceptedService::new(
+      VerifyServiceServer::new(VerifyServiceImpl::new(engine.clone()).with_limits(limits.clone()))
+        .max_decoding_message_size(max_message),
+      limits.interceptor(auth.interceptor(Role::Viewer)),
+    ))
+    .add_service(TenantServiceServer::with_interceptor(
+      TenantServiceImpl::new(engine.clone(), auth.clone()),
+      auth.interceptor(Role::Admin),
+    ))
+    .add_service(AdminServiceServer::with_interceptor(
+      AdminServiceImpl::new(engine, auth.clone()).with_limits(limits).with_args(config.args.clone()),
+      auth.interceptor(Role::Admin),
+    ));
+
+  info!(
+    listen = %config.listen,
+    network = %config.network,
+    grpc_web = config.grpc_web,
+    rest = config.rest,
+    tls = config.tls.is_some(),
+    "Serving gRPC",
+  );
+  let (draining, drained) = tokio::sync::oneshot::channel();
+  let signal = async move {
+    signal.await;
+    info!("Shutting down; draining calls in flight");
+    readiness.stop().await;
+    let _ = draining.send(());
+  };
+  let server = router.serve_with_shutdown(config.listen, signal);
+  tokio::pin!(server);
+  tokio::select! {
+    result = &mut server => return result.map_err(transport_err),
+    Ok(()) = drained => {}
+  }
+  match tokio::time::timeout(Duration::from_secs(config.shutdown_grace), server).await {
+    Ok(result) => result.map_err(transport_err)?,
+    Err(_) => warn!(grace = config.shutdown_grace, "Calls still in flight after the grace period; stopping anyway"),
+  }
+  info!("Stopped");
+  Ok(())
+}
+
+/// Completes on Ctrl-C or, on Unix, SIGTERM
+pub async fn shutdown_signal() {
+  let interrupt = async {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+      error!(error = %e, "Could not listen for Ctrl-C");
+      std::future::pending::<()>().await;
+    }
+  };
+  #[cfg(unix)]
+  let terminate = async {
+    match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+      Ok(mut terminate) => {
+        terminate.recv().await;
+      }
+      Err(e) => {
+        error!(error = %e, "Could not listen for SIGTERM");
+        std::future::pending::<()>().await;
+      }
+    }
+  };
+  #[cfg(not(unix))]
+  let terminate = std::future::pending::<()>();
+  tokio::select! {
+    _ = interrupt => {}
+    _ = terminate => {}
+  }
+}
+
+/// Every `interval`, mark anchors the chain has confirmed and sample the
+/// wallet balance
+async fn watch_confirmations<W: WalletAdapter + Send + 'static>(engine: AsyncEngine<W>, interval: Duration) {
+  loop {
+    tokio::time::sleep(interval).await;
+    if let Err(e) = engine.check_confirmations().await {
+      warn!(error = %e, "Confirmation check failed");
+    }
+    if let Err(e) = engine.run(|engine| metrics().sample_wallet(engine)).await {
+      warn!(error = %e, "Could not sample the wallet balance");
+    }
+  }
+}
+
+fn cors_layer(origins: &[String]) -> Result<CorsLayer, VBError> {
+  let origins = origins
+    .iter()
+    .map(|origin| origin.parse().map_err(|_| VBError::InvalidArgument(format!("Invalid CORS origin: {}", origin))))
+    .collect::<Result<Vec<_>, _>>()?;
+  let exposed = GRPC_WEB_EXPOSED_HEADERS.map(http::HeaderName::from_static);
+  Ok(CorsLayer::new()
+    .allow_origin(AllowOrigin::list(origins))
+    .allow_methods([http::Method::GET, http::Method::POST, http::Method::OPTIONS])
+    .allow_headers(tower_http::cors::Any)
+    .expose_headers(exposed)
+    .max_age(Duration::from_secs(24 * 60 * 60)))
+}
+
+fn transport_err(e: tonic::transport::Error) -> VBError {
+  VBError::Other(format!("Transport error: {}", e))
+}
@@ -1,27 +1,99 @@
+use tonic::metadata::MetadataMap;
 use tonic::{Request, Response, Status};
-use crate::{AnchorEngine};
+use crate::{AnchorRecord, AnchorStatus, AsyncEngine, DEFAULT_OWNER};
 use crate::proto::{
     anchor_service_server::AnchorService,
-    AnchorRequest, AnchorResponse,
+    AnchorRequest, AnchorResponse, Policy,
+    ExportPsbtRequest, ExportPsbtResponse, SubmitPsbtRequest,
+    GetQuotaRequest, GetQuotaResponse,
 };
 use crate::proto::{
     verify_service_server::VerifyService,
-    VerifyRequest, VerifyResponse,
-    ExistDigestRequest, ExistDigestResponse, 
+    AnchorEvent, VerifyRequest, VerifyResponse,
+    ExistDigestRequest, ExistDigestResponse,
+    ListAnchorsRequest, ListAnchorsResponse,
+    SubscribeEventsRequest, EventNotice,
 };
-use validblock_storage::AnchorRepo;
-use validblock_wallet::WalletAdapter;
+use crate::proto::{
+    tenant_service_server::TenantService,
+    CreateTenantRequest, DisableTenantRequest, EnableTenantRequest,
+    ListTenantsRequest, ListTenantsResponse,
+    CreateTenantKeyRequest, CreateTenantKeyResponse,
+};
+use crate::proto::{
+    admin_service_server::AdminService,
+    ApiKeyInfo, CreateKeyRequest, CreateKeyResponse,
+    ListKeysRequest, ListKeysResponse, RevokeKeyRequest, RevokeKeyResponse,
+    WalletStatusRequest, WalletStatusResponse,
+    MaintenanceRequest, MaintenanceResponse, IntegrityCheckResponse,
+    ReloadConfigRequest, ReloadConfigResponse,
+    AuditEntry, ListAuditRequest, ListAuditResponse, ExportAuditRequest, ExportAuditChunk,
+    VerifyAuditRequest, VerifyAuditResponse,
+};
+use crate::admin;
+use crate::audit::{self, AuditFilter};
+use crate::auth::{key_id, Authenticator, KeyRecord, Role};
+use crate::events::EventKind;
+use crate::tenants::{self, Tenant};
+use std::collections::HashSet;
+use std::pin::Pin;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use validblock_wallet::{psbt, WalletAdapter};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use validblock_types::Digest256; 
+use validblock_types::{Digest256, VBError};
+use crate::status::to_status;
+use crate::limits::Limits;
+
+/// Request header carrying a client idempotency key for `Anchor`
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Audit entries per chunk of `ExportAudit`
+const EXPORT_CHUNK_ENTRIES: usize = 1000;
+
+/// Longest idempotency key accepted
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Page size of `ListAnchors` when the request doesn't pick one, and the cap
+pub(crate) const DEFAULT_LIST_LIMIT: u32 = 100;
+pub(crate) const MAX_LIST_LIMIT: u32 = 1000;
+
+/// Owner (tenant) a request acts for, set as a request extension by the
+/// server's interceptors. Requests without one act for `DEFAULT_OWNER`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Owner(pub String);
+
+impl Owner {
+    /// The owner attached to `request`
+    pub fn of<T>(request: &Request<T>) -> String {
+        request
+            .extensions()
+            .get::<Owner>()
+            .map_or_else(|| DEFAULT_OWNER.to_string(), |owner| owner.0.clone())
+    }
+
+    /// Whose anchors `owner` may read: the default owner sees every event,
+    /// anyone else only their own
+    pub fn viewer(owner: &str) -> Option<&str> {
+        Some(owner).filter(|o| *o != DEFAULT_OWNER)
+    }
+}
 
 pub struct AnchorServiceImpl<W: WalletAdapter + Send + Sync + 'static> {
-    engine: Arc<Mutex<AnchorEngine<W>>>,
+    engine: AsyncEngine<W>,
+    limits: Arc<Limits>,
 }
 
 impl<W: WalletAdapter + Send + Sync + 'static> AnchorServiceImpl<W> {
-    pub fn new(engine: Arc<Mutex<AnchorEngine<W>>>) -> Self {
-        Self { engine }
+    pub fn new(engine: AsyncEngine<W>) -> Self {
+        Self { limits: engine.limits.clone(), engine }
+    }
+
+    /// Enforce upload caps other than the engine's
+    pub fn with_limits(mut self, limits: Arc<Limits>) -> Self {
+        self.limits = limits;
+        self
     }
 }
 
@@ -31,37 +103,137 @@ impl<W: WalletAdapter + Send + Sync + 'static> AnchorService for AnchorServiceIm
         &self,
         request: Request<AnchorRequest>,
     ) -> Result<Response<AnchorResponse>, Status> {
+        let key = idempotency_key(request.metadata()).map_err(to_status)?;
+        let owner = Owner::of(&request);
         let req = request.into_inner();
-        let engine = self.engine.lock().await;
+        self.limits.check_upload(req.file_content.len()).map_err(to_status)?;
+
+        // Parse memo policy
+        let policy = match req.policy() {
+            Policy::OnChain => crate::MemoPolicy::OnChain,
+            _ => crate::MemoPolicy::LocalOnly,
+        };
 
-        // Parse file content and memo policy
-        let file_bytes = req.file_content;
-        let path = std::env::temp_dir().join("tmpfile.dat");
-        std::fs::write(&path, &file_bytes).map_err(|e| {
-            Status::internal(format!("Failed to write file: {}", e))
-        })?;
+        let memo = Some(req.memo.into_bytes()).filter(|m| !m.is_empty());
+        let anchored = self
+            .engine
+            .anchor(req.file_content, policy, owner.clone(), memo, key)
+            .await
+            .map_err(to_status)?;
+        let record = anchored.record;
 
-        let policy = crate::MemoPolicy::LocalOnly; // Use logic to map if needed
+        let psbt = match record.status {
+            AnchorStatus::AwaitingSignature => self
+                .engine
+                .pending_psbt(record.digest.clone(), owner)
+                .await
+                .map_err(to_status)?
+                .map(|p| psbt::to_base64(&p))
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+        let digest = record.digest.clone();
+        Ok(audit::targeted(AnchorResponse {
+            existing: anchored.existing,
+            ..anchor_response(record, psbt)
+        }, digest))
+    }
 
-        let record = engine
-            .anchor_file(&path, policy)
-            .map_err(|e| Status::internal(format!("Anchor failed: {}", e)))?;
+    async fn export_psbt(
+        &self,
+        request: Request<ExportPsbtRequest>,
+    ) -> Result<Response<ExportPsbtResponse>, Status> {
+        let owner = Owner::of(&request);
+        let req = request.into_inner();
+        let digest = req.digest.parse::<Digest256>().map_err(to_status)?;
 
-        Ok(Response::new(AnchorResponse {
-            digest: record.digest.to_string(),
-            timestamp: record.ts,
-            txid: record.txid.unwrap_or_default(),
+        let psbt = self
+            .engine
+            .pending_psbt(digest.clone(), owner)
+            .await
+            .map_err(to_status)?
+            .ok_or_else(|| to_status(VBError::NotFound(format!("No PSBT awaiting signature for {}", digest))))?;
+
+        Ok(audit::targeted(ExportPsbtResponse { psbt: psbt::to_base64(&psbt) }, digest))
+    }
+
+    async fn submit_psbt(
+        &self,
+        request: Request<SubmitPsbtRequest>,
+    ) -> Result<Response<AnchorResponse>, Status> {
+        let owner = Owner::of(&request);
+        let req = request.into_inner();
+        let signed = psbt::from_base64(&req.psbt).map_err(to_status)?;
+
+        let record = self
+            .engine
+            .submit_signed_psbt(signed, owner)
+            .await
+            .map_err(to_status)?;
+
+        let digest = record.digest.clone();
+        Ok(audit::targeted(anchor_response(record, String::new()), digest))
+    }
+
+    async fn get_quota(
+        &self,
+        request: Request<GetQuotaRequest>,
+    ) -> Result<Response<GetQuotaResponse>, Status> {
+        let owner = Owner::of(&request);
+        let quota = self
+            .engine
+            .run(move |engine| engine.limits.quota(&engine.repo, &owner, chrono::Utc::now().timestamp()))
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(GetQuotaResponse {
+            used: quota.used,
+            limit: quota.limit.unwrap_or_default(),
+            remaining: quota.remaining().unwrap_or_default(),
+            resets_at: quota.resets_at,
         }))
     }
 }
 
+fn anchor_response(record: AnchorRecord, psbt: String) -> AnchorResponse {
+    AnchorResponse {
+        digest: record.digest.to_string(),
+        timestamp: record.ts,
+        txid: record.txid.unwrap_or_default(),
+        status: record.status.to_string(),
+        psbt,
+        existing: false,
+    }
+}
+
+/// The request's idempotency key, if it sent a usable one
+pub(crate) fn idempotency_key(metadata: &MetadataMap) -> Result<Option<String>, VBError> {
+    let Some(value) = metadata.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    match value.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => Ok(Some(key.to_string())),
+        _ => Err(VBError::InvalidArgument(format!(
+            "{} must be 1 to {} visible ASCII characters",
+            IDEMPOTENCY_KEY_HEADER, MAX_IDEMPOTENCY_KEY_LEN
+        ))),
+    }
+}
+
 pub struct VerifyServiceImpl<W: WalletAdapter + Send + Sync + 'static> {
-    engine: Arc<Mutex<AnchorEngine<W>>>,
+    engine: AsyncEngine<W>,
+    limits: Arc<Limits>,
 }
 
 impl<W: WalletAdapter + Send + Sync + 'static> VerifyServiceImpl<W> {
-    pub fn new(engine: Arc<Mutex<AnchorEngine<W>>>) -> Self {
-        Self { engine }
+    pub fn new(engine: AsyncEngine<W>) -> Self {
+        Self { limits: engine.limits.clone(), engine }
+    }
+
+    /// Enforce upload caps other than the engine's
+    pub fn with_limits(mut self, limits: Arc<Limits>) -> Self {
+        self.limits = limits;
+        self
     }
 }
 
@@ -71,47 +243,484 @@ impl<W: WalletAdapter + Send + Sync + 'static> VerifyService for VerifyServiceIm
         &self,
         request: Request<VerifyRequest>,
     ) -> Result<Response<VerifyResponse>, Status> {
+        let owner = Owner::of(&request);
         let req = request.into_inner();
-        let file_bytes = req.file_content;
-        let path = std::env::temp_dir().join("verify_tmp.dat");
-        std::fs::write(&path, &file_bytes).map_err(|e| {
-            Status::internal(format!("Failed to write file: {}", e))
-        })?;
-
-        let engine = self.engine.lock().await;
-        let maybe_rec = engine
-            .verify_file(&path)
-            .map_err(|e| Status::internal(format!("Verify failed: {}", e)))?;
-
-        if let Some(record) = maybe_rec {
-            Ok(Response::new(VerifyResponse {
-                verified: true,
-                digest: record.digest.to_string(),
-                timestamp: record.ts,
-                txid: record.txid.unwrap_or_default(),
-            }))
-        } else {
-            Err(Status::not_found("Record not found"))
-        }
+        self.limits.check_upload(req.file_content.len()).map_err(to_status)?;
+
+        let viewer = Owner::viewer(&owner).map(str::to_string);
+        let (_, events) = self
+            .engine
+            .verify(req.file_content, viewer)
+            .await
+            .map_err(to_status)?;
+
+        let Some(earliest) = events.first().cloned() else {
+            return Err(to_status(VBError::NotFound("No anchor for this file".into())));
+        };
+        Ok(audit::targeted(VerifyResponse {
+            verified: true,
+            digest: earliest.digest.to_string(),
+            timestamp: earliest.ts,
+            txid: earliest.txid.unwrap_or_default(),
+            status: earliest.status.to_string(),
+            events: events.into_iter().map(anchor_event).collect(),
+        }, earliest.digest))
     }
 
     async fn exist_digest(
         &self,
         request: Request<ExistDigestRequest>,
     ) -> Result<Response<ExistDigestResponse>, Status> {
+        let owner = Owner::of(&request);
         let req = request.into_inner();
         let digest_str = req.digest;
     
         // Try to parse digest (base64 or hex as string)
         let digest = digest_str
             .parse::<Digest256>()
-            .map_err(|e| Status::invalid_argument(format!("Invalid digest format: {}", e)))?;
+            .map_err(to_status)?;
     
-        let engine = self.engine.lock().await;
-        let exists = engine.repo.get(&digest).map(|opt| opt.is_some())
-            .map_err(|e| Status::internal(format!("Repo lookup failed: {}", e)))?;
+        let viewer = Owner::viewer(&owner).map(str::to_string);
+        let exists = self
+            .engine
+            .exist_digest(digest.clone(), viewer)
+            .await
+            .map_err(to_status)?;
     
-        Ok(Response::new(ExistDigestResponse { exists }))
+        Ok(audit::targeted(ExistDigestResponse { exists }, digest))
     }
-    
-}
\ No newline at end of file
+
+    async fn list_anchors(
+        &self,
+        request: Request<ListAnchorsRequest>,
+    ) -> Result<Response<ListAnchorsResponse>, Status> {
+        let owner = Owner::of(&request);
+        let req = request.into_inner();
+        let limit = match req.limit {
+            0 => DEFAULT_LIST_LIMIT,
+            n => n.min(MAX_LIST_LIMIT),
+        };
+
+        let viewer = Owner::viewer(&owner).map(str::to_string);
+        let records = self
+            .engine
+            .list(viewer, limit, req.offset)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(ListAnchorsResponse {
+            events: records.into_iter().map(anchor_event).collect(),
+        }))
+    }
+
+    type SubscribeEventsStream = Pin<Box<dyn Stream<Item = Result<EventNotice, Status>> + Send>>;
+
+    async fn subscribe_events(
+        &self,
+        request: Request<SubscribeEventsRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        let owner = Owner::of(&request);
+        let kinds = request
+            .into_inner()
+            .kinds
+            .iter()
+            .map(|kind| kind.parse())
+            .collect::<Result<HashSet<EventKind>, _>>()
+            .map_err(to_status)?;
+
+        let events = self.engine.events.subscribe();
+        let stream = BroadcastStream::new(events).filter_map(move |event| match event {
+            Ok(event) => {
+                let visible = Owner::viewer(&owner).is_none_or(|viewer| event.owner == viewer);
+                let wanted = kinds.is_empty() || kinds.contains(&event.kind);
+                if visible && wanted {
+                    Some(Ok(EventNotice::from(event)))
+                } else {
+                    None
+                }
+            }
+            // the subscriber fell behind and missed events; end the stream so
+            // it resubscribes and catches up from ListAnchors
+            Err(BroadcastStreamRecvError::Lagged(missed)) => Some(Err(Status::data_loss(format!(
+                "Missed {} events; resubscribe",
+                missed
+            )))),
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+fn anchor_event(record: AnchorRecord) -> AnchorEvent {
+    AnchorEvent {
+        digest: record.digest.to_string(),
+        owner: record.owner,
+        timestamp: record.ts,
+        memo: record.memo.unwrap_or_default(),
+        txid: record.txid.unwrap_or_default(),
+        status: record.status.to_string(),
+    }
+}
+
+pub struct TenantServiceImpl<W: WalletAdapter + Send + Sync + 'static> {
+    engine: AsyncEngine<W>,
+    auth: Arc<Authenticator>,
+}
+
+impl<W: WalletAdapter + Send + Sync + 'static> TenantServiceImpl<W> {
+    /// Tenants of `engine`, whose keys and status are kept in step in `auth`
+    pub fn new(engine: AsyncEngine<W>, auth: Arc<Authenticator>) -> Self {
+        Self { engine, auth }
+    }
+
+    async fn set_disabled(&self, id: &str, disabled: bool) -> Result<Response<crate::proto::Tenant>, Status> {
+        let id_owned = id.to_string();
+        let tenant = self
+            .engine
+            .run(move |engine| engine.set_tenant_disabled(&id_owned, disabled))
+            .await
+            .map_err(to_status)?;
+        self.auth.set_disabled(id, disabled);
+        Ok(audit::targeted(tenant_message(tenant), id))
+    }
+}
+
+#[tonic::async_trait]
+impl<W: WalletAdapter + Send + Sync + 'static> TenantService for TenantServiceImpl<W> {
+    async fn create_tenant(
+        &self,
+        request: Request<CreateTenantRequest>,
+    ) -> Result<Response<crate::proto::Tenant>, Status> {
+        let req = request.into_inner();
+        let fee_rate = match req.fee_rate {
+            0 => None,
+            n => Some(u16::try_from(n).map_err(|_| {
+                to_status(VBError::InvalidArgument(format!("Fee rate of {} sat/vB is out of range", n)))
+            })?),
+        };
+        let (id, name) = (req.id.clone(), req.name);
+        let tenant = self
+            .engine
+            .run(move |engine| engine.create_tenant(&id, &name, fee_rate))
+            .await
+            .map_err(to_status)?;
+        Ok(audit::targeted(tenant_message(tenant), req.id))
+    }
+
+    async fn disable_tenant(
+        &self,
+        request: Request<DisableTenantRequest>,
+    ) -> Result<Response<crate::proto::Tenant>, Status> {
+        self.set_disabled(&request.into_inner().id, true).await
+    }
+
+    async fn enable_tenant(
+        &self,
+        request: Request<EnableTenantRequest>,
+    ) -> Result<Response<crate::proto::Tenant>, Status> {
+        self.set_disabled(&request.into_inner().id, false).await
+    }
+
+    async fn list_tenants(
+        &self,
+        _request: Request<ListTenantsRequest>,
+    ) -> Result<Response<ListTenantsResponse>, Status> {
+        let tenants = self.engine.run(|engine| engine.repo.tenants()).await.map_err(to_status)?;
+        Ok(Response::new(ListTenantsResponse {
+            tenants: tenants.into_iter().map(tenant_message).collect(),
+        }))
+    }
+
+    async fn create_tenant_key(
+        &self,
+        request: Request<CreateTenantKeyRequest>,
+    ) -> Result<Response<CreateTenantKeyResponse>, Status> {
+        let req = request.into_inner();
+        let role = match req.role.as_str() {
+            "" => Role::Anchorer,
+            role => role.parse().map_err(to_status)?,
+        };
+        if role == Role::Admin {
+            return Err(to_status(VBError::InvalidArgument("Tenant keys may not administer the server".into())));
+        }
+
+        let auth = self.auth.clone();
+        let (secret, record) = self
+            .engine
+            .run(move |engine| {
+                if engine.repo.get_tenant(&req.tenant)?.is_none() {
+                    return Err(VBError::NotFound(format!("No tenant {}", req.tenant)));
+                }
+                auth.issue_key(&engine.repo, &req.tenant, role)
+            })
+            .await
+            .map_err(to_status)?;
+
+        Ok(audit::targeted(CreateTenantKeyResponse { secret }, key_id(&record.digest)))
+    }
+}
+
+fn tenant_message(tenant: Tenant) -> crate::proto::Tenant {
+    crate::proto::Tenant {
+        id: tenant.id,
+        name: tenant.name,
+        disabled: tenant.disabled,
+        fee_rate: tenant.fee_rate.map(u32::from).unwrap_or_default(),
+        created_at: tenant.created_at,
+    }
+}
+
+pub struct AdminServiceImpl<W: WalletAdapter + Send + Sync + 'static> {
+    engine: AsyncEngine<W>,
+    auth: Arc<Authenticator>,
+    limits: Arc<Limits>,
+    /// Command-line arguments `ReloadConfig` re-reads the configuration with
+    args: Vec<String>,
+}
+
+impl<W: WalletAdapter + Send + Sync + 'static> AdminServiceImpl<W> {
+    /// Administration of `engine`, with credentials kept in step in `auth`
+    pub fn new(engine: AsyncEngine<W>, auth: Arc<Authenticator>) -> Self {
+        Self { engine, auth, limits: Arc::new(Limits::default()), args: vec![] }
+    }
+
+    /// Limits a config reload updates
+    pub fn with_limits(mut self, limits: Arc<Limits>) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Command-line arguments the server was started with
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    async fn maintain(
+        &self,
+        op: fn(&validblock_storage::AnchorRepo) -> Result<(), VBError>,
+    ) -> Result<Response<MaintenanceResponse>, Status> {
+        // VACUUM rewrites the whole file
+        let size_bytes = self
+            .engine
+            .run(move |engine| {
+                op(&engine.repo)?;
+                engine.repo.size_bytes()
+            })
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(MaintenanceResponse { size_bytes }))
+    }
+}
+
+#[tonic::async_trait]
+impl<W: WalletAdapter + Send + Sync + 'static> AdminService for AdminServiceImpl<W> {
+    async fn create_key(
+        &self,
+        request: Request<CreateKeyRequest>,
+    ) -> Result<Response<CreateKeyResponse>, Status> {
+        let req = request.into_inner();
+        let role: Role = req.role.parse().map_err(to_status)?;
+        let owner = match req.owner.as_str() {
+            "" => DEFAULT_OWNER,
+            owner => owner,
+        };
+        if owner != DEFAULT_OWNER {
+            tenants::validate_id(owner).map_err(to_status)?;
+            // an admin key administers every tenant, so only the operator's
+            // own owner may hold one
+            if role == Role::Admin {
+                return Err(to_status(VBError::InvalidArgument(format!("Admin keys cannot belong to tenant {}", owner))));
+            }
+        }
+
+        let (auth, owner) = (self.auth.clone(), owner.to_string());
+        let (secret, record) = self
+            .engine
+            .run(move |engine| {
+                if owner != DEFAULT_OWNER && engine.repo.get_tenant(&owner)?.is_none() {
+                    return Err(VBError::NotFound(format!("No tenant {}", owner)));
+                }
+                auth.issue_key(&engine.repo, &owner, role)
+            })
+            .await
+            .map_err(to_status)?;
+        let id = key_id(&record.digest);
+        Ok(audit::targeted(CreateKeyResponse { secret, key: Some(key_message(record)) }, id))
+    }
+
+    async fn list_keys(
+        &self,
+        _request: Request<ListKeysRequest>,
+    ) -> Result<Response<ListKeysResponse>, Status> {
+        let keys = self.engine.run(|engine| engine.repo.keys()).await.map_err(to_status)?;
+        Ok(Response::new(ListKeysResponse { keys: keys.into_iter().map(key_message).collect() }))
+    }
+
+    async fn revoke_key(
+        &self,
+        request: Request<RevokeKeyRequest>,
+    ) -> Result<Response<RevokeKeyResponse>, Status> {
+        let id = request.into_inner().id;
+        let digest: Digest256 = id.parse().map_err(to_status)?;
+        let (auth, revoked) = (self.auth.clone(), digest.clone());
+        if !self.engine.run(move |engine| auth.revoke_key(&engine.repo, &revoked)).await.map_err(to_status)? {
+            return Err(to_status(VBError::NotFound(format!("No issued key {}", id))));
+        }
+        Ok(audit::targeted(RevokeKeyResponse {}, key_id(&digest)))
+    }
+
+    async fn wallet_status(
+        &self,
+        request: Request<WalletStatusRequest>,
+    ) -> Result<Response<WalletStatusResponse>, Status> {
+        let tenant = request.into_inner().tenant;
+        let owner = if tenant.is_empty() {
+            DEFAULT_OWNER.to_string()
+        } else if self.engine.has_own_wallet(&tenant) {
+            tenant
+        } else {
+            return Err(to_status(VBError::NotFound(format!("Tenant {} has no wallet of its own", tenant))));
+        };
+        let status_owner = owner.clone();
+        let status = self
+            .engine
+            .run(move |engine| admin::wallet_status(engine, &status_owner))
+            .await
+            .map_err(to_status)?;
+        Ok(audit::targeted(WalletStatusResponse {
+            network: self.engine.network.to_string(),
+            reachable: status.error.is_none(),
+            error: status.error.unwrap_or_default(),
+            has_balance: status.balance.is_some(),
+            balance: status.balance.unwrap_or_default(),
+            awaiting_signature: status.awaiting_signature,
+            unconfirmed: status.unconfirmed,
+        }, owner))
+    }
+
+    async fn checkpoint(
+        &self,
+        _request: Request<MaintenanceRequest>,
+    ) -> Result<Response<MaintenanceResponse>, Status> {
+        self.maintain(|repo| repo.checkpoint()).await
+    }
+
+    async fn vacuum(
+        &self,
+        _request: Request<MaintenanceRequest>,
+    ) -> Result<Response<MaintenanceResponse>, Status> {
+        self.maintain(|repo| repo.vacuum()).await
+    }
+
+    async fn integrity_check(
+        &self,
+        _request: Request<MaintenanceRequest>,
+    ) -> Result<Response<IntegrityCheckResponse>, Status> {
+        let problems = self.engine.run(|engine| engine.repo.integrity_check()).await.map_err(to_status)?;
+        Ok(Response::new(IntegrityCheckResponse { ok: problems.is_empty(), problems }))
+    }
+
+    async fn reload_config(
+        &self,
+        _request: Request<ReloadConfigRequest>,
+    ) -> Result<Response<ReloadConfigResponse>, Status> {
+        let (args, auth, limits) = (self.args.clone(), self.auth.clone(), self.limits.clone());
+        self.engine
+            .run(move |engine| admin::reload(&args, &auth, &limits, &engine.repo))
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(ReloadConfigResponse { api_keys: self.auth.key_count() as u32 }))
+    }
+
+    async fn list_audit(
+        &self,
+        request: Request<ListAuditRequest>,
+    ) -> Result<Response<ListAuditResponse>, Status> {
+        let req = request.into_inner();
+        let limit = match req.limit {
+            0 => DEFAULT_LIST_LIMIT,
+            n => n.min(MAX_LIST_LIMIT),
+        };
+        let filter = AuditFilter {
+            actor: Some(req.actor).filter(|actor| !actor.is_empty()),
+            action: Some(req.action).filter(|action| !action.is_empty()),
+            since: Some(req.since).filter(|&ts| ts != 0),
+            until: Some(req.until).filter(|&ts| ts != 0),
+            limit: Some(limit),
+            offset: req.offset,
+        };
+        let entries = self.engine.run(move |engine| engine.repo.audit_entries(&filter)).await.map_err(to_status)?;
+        Ok(Response::new(ListAuditResponse { entries: entries.into_iter().map(audit_message).collect() }))
+    }
+
+    type ExportAuditStream = Pin<Box<dyn Stream<Item = Result<ExportAuditChunk, Status>> + Send>>;
+
+    async fn export_audit(
+        &self,
+        request: Request<ExportAuditRequest>,
+    ) -> Result<Response<Self::ExportAuditStream>, Status> {
+        let req = request.into_inner();
+        let filter = AuditFilter {
+            since: Some(req.since).filter(|&ts| ts != 0),
+            until: Some(req.until).filter(|&ts| ts != 0),
+            ..Default::default()
+        };
+        let entries = self.engine.run(move |engine| engine.repo.audit_entries(&filter)).await.map_err(to_status)?;
+        let chunks: Vec<_> = entries
+            .chunks(EXPORT_CHUNK_ENTRIES)
+            .map(|chunk| {
+                let data = chunk.iter().map(|entry| audit::export_line(entry) + "\n").collect::<String>();
+                ExportAuditChunk { data: data.into_bytes() }
+            })
+            .collect();
+        Ok(Response::new(Box::pin(tokio_stream::iter(chunks.into_iter().map(Ok)))))
+    }
+
+    async fn verify_audit(
+        &self,
+        _request: Request<VerifyAuditRequest>,
+    ) -> Result<Response<VerifyAuditResponse>, Status> {
+        let (entries, first_bad, head) = self
+            .engine
+            .run(|engine| {
+                let (entries, first_bad) = engine.repo.verify_audit()?;
+                let head = match entries {
+                    0 => None,
+                    n => engine.repo.audit_entries(&AuditFilter { limit: Some(1), offset: n as u32 - 1, ..Default::default() })?
+                        .pop(),
+                };
+                Ok((entries, first_bad, head))
+            })
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(VerifyAuditResponse {
+            ok: first_bad.is_none(),
+            entries,
+            first_bad_seq: first_bad.unwrap_or_default(),
+            head: head.map(|entry| entry.hash.to_string()).unwrap_or_default(),
+        }))
+    }
+}
+
+fn key_message(key: KeyRecord) -> ApiKeyInfo {
+    ApiKeyInfo {
+        id: key.digest.to_string(),
+        owner: key.owner,
+        role: key.role,
+        created_at: key.created_at,
+    }
+}
+
+fn audit_message(entry: audit::AuditEntry) -> AuditEntry {
+    AuditEntry {
+        seq: entry.seq,
+        timestamp: entry.ts,
+        actor: entry.actor,
+        credential: entry.credential,
+        source: entry.source,
+        action: entry.action,
+        target: entry.target,
+        outcome: entry.outcome,
+        hash: entry.hash.to_string(),
+    }
+}
@@ -0,0 +1,89 @@
+//! `VBError` <-> gRPC status mapping
+
+use prost::Message;
+use tonic::{Code, Status};
+use validblock_types::{ErrorCode, VBError};
+
+use crate::metrics::metrics;
+use crate::proto::ErrorDetail;
+
+/// Type URL of `ErrorDetail` packed into an `Any`
+pub const ERROR_DETAIL_TYPE: &str = "type.googleapis.com/validblock.ErrorDetail";
+
+/// `google.rpc.Status`, the standard payload of `grpc-status-details-bin`
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+  #[prost(int32, tag = "1")]
+  code: i32,
+  #[prost(string, tag = "2")]
+  message: String,
+  #[prost(message, repeated, tag = "3")]
+  details: Vec<Any>,
+}
+
+/// `google.protobuf.Any`
+#[derive(Clone, PartialEq, Message)]
+struct Any {
+  #[prost(string, tag = "1")]
+  type_url: String,
+  #[prost(bytes = "vec", tag = "2")]
+  value: Vec<u8>,
+}
+
+/// gRPC code each error code is reported under
+pub fn grpc_code(code: ErrorCode) -> Code {
+  match code {
+    ErrorCode::InvalidDigest | ErrorCode::InvalidPayload | ErrorCode::InvalidArgument => Code::InvalidArgument,
+    ErrorCode::DuplicateDigest => Code::AlreadyExists,
+    ErrorCode::NotFound => Code::NotFound,
+    ErrorCode::Unauthenticated => Code::Unauthenticated,
+    ErrorCode::PermissionDenied => Code::PermissionDenied,
+    ErrorCode::ResourceExhausted => Code::ResourceExhausted,
+    ErrorCode::Wallet => Code::FailedPrecondition,
+    ErrorCode::Io | ErrorCode::Database | ErrorCode::Internal => Code::Internal,
+  }
+}
+
+/// Status carrying the error's code both as the gRPC code and, exactly, as
+/// an `ErrorDetail` in the standard rich error details
+pub fn to_status(err: VBError) -> Status {
+  metrics().error(err.code());
+  let code = grpc_code(err.code());
+  let message = err.to_string();
+  let detail = ErrorDetail { code: err.code().as_str().to_string(), message: message.clone() };
+  let details = RpcStatus {
+    code: code as i32,
+    message: message.clone(),
+    details: vec![Any { type_url: ERROR_DETAIL_TYPE.to_string(), value: detail.encode_to_vec() }],
+  };
+  Status::with_details(code, message, details.encode_to_vec().into())
+}
+
+/// The `ErrorCode` a ValidBlock server attached to `status`, if any
+pub fn error_code(status: &Status) -> Option<ErrorCode> {
+  let details = RpcStatus::decode(status.details()).ok()?;
+  let any = details.details.iter().find(|any| any.type_url == ERROR_DETAIL_TYPE)?;
+  ErrorDetail::decode(&*any.value).ok()?.code.parse().ok()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_status_roundtrip() {
+    let status = to_status(VBError::DbDuplicate);
+    assert_eq!(status.code(), Code::AlreadyExists);
+    assert_eq!(error_code(&status), Some(ErrorCode::DuplicateDigest));
+
+    let status = to_status("xyz".parse::<crate::Digest256>().unwrap_err());
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(error_code(&status), Some(ErrorCode::InvalidDigest));
+
+    assert_eq!(error_code(&Status::internal("no details")), None);
+  }
+}
@@ -0,0 +1,172 @@
+//! Logging and tracing: `tracing` events as text or JSON lines, on stderr or
+//! in daily-rotated files, and, with the `otlp` feature, spans exported to an
+//! OpenTelemetry collector
+//!
+//! `RUST_LOG` takes the usual `tracing_subscriber` directives and wins over
+//! the configured level.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use tracing::Span;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+use validblock_types::VBError;
+
+/// Environment variable selecting `text` or `json` log lines
+pub const LOG_FORMAT_ENV: &str = "VALIDBLOCK_LOG_FORMAT";
+/// Environment variable naming the directory of rotated log files
+pub const LOG_DIR_ENV: &str = "VALIDBLOCK_LOG_DIR";
+/// Environment variable setting the OTLP/gRPC collector endpoint
+pub const OTLP_ENDPOINT_ENV: &str = "VALIDBLOCK_OTLP_ENDPOINT";
+
+/// Request header carrying the request id, generated when the client sends
+/// none and echoed in the response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+  /// Human-readable lines
+  #[default]
+  Text,
+  /// One JSON object per line, with the enclosing spans' fields
+  Json,
+}
+
+impl FromStr for LogFormat {
+  type Err = VBError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "text" => Ok(LogFormat::Text),
+      "json" => Ok(LogFormat::Json),
+      other => Err(VBError::InvalidArgument(format!("Unknown log format: {}", other))),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+  pub format: LogFormat,
+  /// Filter directives when `RUST_LOG` is unset
+  pub level: String,
+  /// Write daily-rotated files here instead of stderr
+  pub dir: Option<PathBuf>,
+  /// OTLP/gRPC collector receiving spans; needs the `otlp` feature
+  pub otlp_endpoint: Option<String>,
+}
+
+impl Default for LogConfig {
+  fn default() -> Self {
+    LogConfig { format: LogFormat::Text, level: "info".into(), dir: None, otlp_endpoint: None }
+  }
+}
+
+/// Keeps buffered log lines and spans flowing until dropped
+pub struct LogGuard {
+  _file: Option<WorkerGuard>,
+  #[cfg(feature = "otlp")]
+  otlp: bool,
+}
+
+impl Drop for LogGuard {
+  fn drop(&mut self) {
+    #[cfg(feature = "otlp")]
+    if self.otlp {
+      opentelemetry::global::shutdown_tracer_provider();
+    }
+  }
+}
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Install the global subscriber for `config`. Files in `config.dir` are
+/// named `<name>.log.<date>`; `name` is also the OTLP service name
+pub fn init(config: &LogConfig, name: &str) -> Result<LogGuard, VBError> {
+  let filter = EnvFilter::try_from_default_env()
+    .or_else(|_| EnvFilter::try_new(&config.level))
+    .map_err(|e| VBError::InvalidArgument(format!("Invalid log level: {}", e)))?;
+  let (writer, file) = match &config.dir {
+    Some(dir) => {
+      std::fs::create_dir_all(dir)?;
+      let (writer, guard) = tracing_appender::non_blocking(tracing_appender::rolling::daily(dir, format!("{}.log", name)));
+      (tracing_subscriber::fmt::writer::BoxMakeWriter::new(writer), Some(guard))
+    }
+    None => (tracing_subscriber::fmt::writer::BoxMakeWriter::new(std::io::stderr), None),
+  };
+  let fmt = tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(config.dir.is_none());
+  let mut layers: Vec<BoxedLayer> = vec![match config.format {
+    LogFormat::Text => fmt.boxed(),
+    LogFormat::Json => fmt.json().with_current_span(true).with_span_list(true).boxed(),
+  }];
+  if let Some(endpoint) = &config.otlp_endpoint {
+    layers.push(otlp_layer(endpoint, name)?);
+  }
+  tracing_subscriber::registry()
+    .with(layers.with_filter(filter))
+    .try_init()
+    .map_err(|e| VBError::Other(format!("Logging is already set up: {}", e)))?;
+  Ok(LogGuard {
+    _file: file,
+    #[cfg(feature = "otlp")]
+    otlp: config.otlp_endpoint.is_some(),
+  })
+}
+
+#[cfg(feature = "otlp")]
+fn otlp_layer(endpoint: &str, name: &str) -> Result<BoxedLayer, VBError> {
+  use opentelemetry::KeyValue;
+  use opentelemetry_otlp::WithExportConfig;
+  use opentelemetry_sdk::{trace, Resource};
+
+  let tracer = opentelemetry_otlp::new_pipeline()
+    .tracing()
+    .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+    .with_trace_config(trace::config().with_resource(Resource::new([KeyValue::new("service.name", name.to_string())])))
+    .install_batch(opentelemetry_sdk::runtime::Tokio)
+    .map_err(|e| VBError::Other(format!("Cannot export to {}: {}", endpoint, e)))?;
+  Ok(tracing_opentelemetry::layer().with_tracer(tracer).boxed())
+}
+
+#[cfg(not(feature = "otlp"))]
+fn otlp_layer(_endpoint: &str, _name: &str) -> Result<BoxedLayer, VBError> {
+  Err(VBError::InvalidArgument("This build has no OTLP export; rebuild with the otlp feature".into()))
+}
+
+/// Span of one HTTP or gRPC request, named after its path. Handlers fill in
+/// `digest` and `policy` as they learn them
+pub fn request_span<B>(request: &http::Request<B>) -> Span {
+  let id = request.headers().get(REQUEST_ID_HEADER).and_then(|v| v.to_str().ok()).unwrap_or_default();
+  tracing::info_span!(
+    "request",
+    request_id = %id,
+    method = %request.method(),
+    path = %request.uri().path(),
+    digest = tracing::field::Empty,
+    policy = tracing::field::Empty,
+  )
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_log_config() {
+    let config: LogConfig = toml::from_str("format = \"json\"\ndir = \"/var/log/validblock\"").unwrap();
+    assert_eq!(config.format, LogFormat::Json);
+    assert_eq!(config.level, "info");
+    assert_eq!(config.dir, Some(PathBuf::from("/var/log/validblock")));
+    assert!("yaml".parse::<LogFormat>().is_err());
+    #[cfg(not(feature = "otlp"))]
+    assert!(otlp_layer("http://127.0.0.1:4317", "validblock").is_err());
+  }
+}
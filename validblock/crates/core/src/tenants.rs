@@ -0,0 +1,81 @@
+//! Tenants: departments or customers sharing one server
+//!
+//! A tenant's id is the owner of its anchors, so each tenant anchors, lists
+//! and verifies in its own namespace. The operator, calling with the server's
+//! bearer token or another admin credential, manages tenants through
+//! `TenantService` and issues their API keys, which are stored as digests
+//! beside the anchors. A tenant may carry its own fee rate and fund its
+//! anchors from its own wallet:
+//!
+//! ```toml
+//! [tenants]
+//! required = true
+//!
+//! [tenants.wallets.hr]
+//! xpub = "tpub..."
+//! ```
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use validblock_types::{VBError, DEFAULT_OWNER};
+
+pub use validblock_storage::Tenant;
+
+/// Longest tenant id accepted
+const MAX_ID_LEN: usize = 64;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TenantsConfig {
+  /// Multi-tenant mode: API keys must belong to a registered tenant to anchor
+  pub required: bool,
+  /// Tenants funding their anchors from their own wallet, by id
+  pub wallets: HashMap<String, TenantWalletConfig>,
+}
+
+/// A tenant's wallet, of the server's wallet backend
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TenantWalletConfig {
+  /// Account xpub for `watch-only`
+  pub xpub: Option<String>,
+  /// Keystore file for `hot`, unlocked with the server's passphrase
+  pub keystore: Option<PathBuf>,
+}
+
+/// Refuse ids that aren't short lowercase slugs, and the default owner's
+pub fn validate_id(id: &str) -> Result<(), VBError> {
+  let slug = id.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_');
+  if id.is_empty() || id.len() > MAX_ID_LEN || !slug {
+    return Err(VBError::InvalidArgument(format!(
+      "Tenant ids are 1 to {} lowercase letters, digits, '-' or '_'",
+      MAX_ID_LEN
+    )));
+  }
+  if id == DEFAULT_OWNER {
+    return Err(VBError::InvalidArgument(format!("{} is reserved", DEFAULT_OWNER)));
+  }
+  Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_validate_id() {
+    assert!(validate_id("hr").is_ok());
+    assert!(validate_id("r-and-d_2").is_ok());
+    assert!(validate_id("").is_err());
+    assert!(validate_id("Legal").is_err());
+    assert!(validate_id("a/b").is_err());
+    assert!(validate_id(&"x".repeat(MAX_ID_LEN + 1)).is_err());
+    assert!(validate_id(DEFAULT_OWNER).is_err());
+  }
+}
@@ -0,0 +1,92 @@
+//! TLS and mutual TLS for the gRPC servers and their clients
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
+use validblock_types::VBError;
+
+/// Environment variable naming the server's PEM certificate chain
+pub const TLS_CERT_ENV: &str = "VALIDBLOCK_TLS_CERT";
+
+/// Environment variable naming the server's PEM private key
+pub const TLS_KEY_ENV: &str = "VALIDBLOCK_TLS_KEY";
+
+/// Environment variable naming the PEM CA that client certificates must
+/// chain to; setting it turns on mutual TLS
+pub const TLS_CLIENT_CA_ENV: &str = "VALIDBLOCK_TLS_CLIENT_CA";
+
+/// Where a server finds its certificate, key and, for mTLS, the client CA
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+  pub cert: PathBuf,
+  pub key: PathBuf,
+  #[serde(default)]
+  pub client_ca: Option<PathBuf>,
+}
+
+impl TlsConfig {
+  /// Load the PEM files for `Server::tls_config`
+  pub fn server_config(&self) -> Result<ServerTlsConfig, VBError> {
+    let identity = Identity::from_pem(std::fs::read(&self.cert)?, std::fs::read(&self.key)?);
+    let mut config = ServerTlsConfig::new().identity(identity);
+    if let Some(ca) = &self.client_ca {
+      config = config.client_ca_root(Certificate::from_pem(std::fs::read(ca)?));
+    }
+    Ok(config)
+  }
+}
+
+/// Client settings trusting the PEM CA at `ca`, presenting `identity`
+/// (certificate, key) when the server requires mutual TLS
+pub fn client_config(ca: &Path, identity: Option<(&Path, &Path)>) -> Result<ClientTlsConfig, VBError> {
+  let mut config = ClientTlsConfig::new().ca_certificate(Certificate::from_pem(std::fs::read(ca)?));
+  if let Some((cert, key)) = identity {
+    config = config.identity(Identity::from_pem(std::fs::read(cert)?, std::fs::read(key)?));
+  }
+  Ok(config)
+}
+
+/// PEM certificate and key
+#[derive(Clone)]
+pub struct SelfSigned {
+  pub cert_pem: String,
+  pub key_pem: String,
+}
+
+impl SelfSigned {
+  /// Fresh self-signed certificate valid for `names` (DNS names or IP
+  /// addresses). Clients trust it by using the certificate itself as CA
+  pub fn generate(names: &[&str]) -> Result<Self, VBError> {
+    let names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+    let tls_err = |e: rcgen::Error| VBError::Other(format!("Certificate generation failed: {}", e));
+    let key = rcgen::KeyPair::generate().map_err(tls_err)?;
+    let cert = rcgen::CertificateParams::new(names).map_err(tls_err)?.self_signed(&key).map_err(tls_err)?;
+    Ok(SelfSigned { cert_pem: cert.pem(), key_pem: key.serialize_pem() })
+  }
+
+  /// Write `<stem>.pem` and `<stem>.key` into `dir`, returning their paths
+  pub fn write<P: AsRef<Path>>(&self, dir: P, stem: &str) -> Result<(PathBuf, PathBuf), VBError> {
+    let cert = dir.as_ref().join(format!("{}.pem", stem));
+    let key = dir.as_ref().join(format!("{}.key", stem));
+    std::fs::write(&cert, &self.cert_pem)?;
+    write_private(&key, &self.key_pem)?;
+    Ok((cert, key))
+  }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &str) -> Result<(), VBError> {
+  use std::io::Write;
+  use std::os::unix::fs::OpenOptionsExt;
+  let mut file = std::fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+  file.write_all(contents.as_bytes())?;
+  Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &str) -> Result<(), VBError> {
+  std::fs::write(path, contents)?;
+  Ok(())
+}
@@ -0,0 +1,170 @@
+//! Outbound webhooks
+//!
+//! The engine queues every event in storage once per configured URL; a
+//! [`Dispatcher`] POSTs the queue as JSON [`Event`](crate::events::Event)s,
+//! retrying failures with exponential backoff, so deliveries survive
+//! restarts. Each POST is signed:
+//!
+//! ```text
+//! x-validblock-timestamp: 1767225600
+//! x-validblock-signature: sha256=<hex HMAC-SHA256 of "1767225600.<body>" keyed by the secret>
+//! ```
+//!
+//! Receivers should recompute the signature and reject stale timestamps.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use tracing::warn;
+use validblock_storage::Delivery;
+use validblock_types::VBError;
+use validblock_wallet::WalletAdapter;
+
+use crate::AsyncEngine;
+
+/// Request header carrying the delivery's Unix timestamp
+pub const TIMESTAMP_HEADER: &str = "x-validblock-timestamp";
+
+/// Request header carrying the delivery's signature
+pub const SIGNATURE_HEADER: &str = "x-validblock-signature";
+
+/// Attempts after which a delivery is dropped
+pub const MAX_ATTEMPTS: u32 = 10;
+
+/// Deliveries attempted per pass
+const BATCH_SIZE: u32 = 32;
+
+/// How long a receiver gets to answer
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A receiver of events
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+  pub url: String,
+  /// Key of the HMAC signature
+  pub secret: String,
+}
+
+/// Signature header value for `body` sent at `timestamp`
+pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+  let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+  mac.update(timestamp.to_string().as_bytes());
+  mac.update(b".");
+  mac.update(body);
+  let hex: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+  format!("sha256={}", hex)
+}
+
+/// Seconds to wait after a delivery's `attempts`th failure: 10s, doubling,
+/// at most an hour
+pub fn backoff(attempts: u32) -> i64 {
+  (10i64 << attempts.min(9)).min(3600)
+}
+
+/// Works through the delivery queue
+pub struct Dispatcher<W: WalletAdapter> {
+  engine: AsyncEngine<W>,
+  secrets: HashMap<String, String>,
+  agent: ureq::Agent,
+}
+
+impl<W: WalletAdapter + Send + 'static> Dispatcher<W> {
+  pub fn new(engine: AsyncEngine<W>, hooks: &[WebhookConfig]) -> Self {
+    Dispatcher {
+      engine,
+      secrets: hooks.iter().map(|hook| (hook.url.clone(), hook.secret.clone())).collect(),
+      agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+    }
+  }
+
+  /// Attempt every due delivery once, returning how many succeeded
+  pub async fn deliver_due(&self) -> Result<usize, VBError> {
+    let now = chrono::Utc::now().timestamp();
+    let due = self.engine.run(move |engine| engine.repo.due_deliveries(now, BATCH_SIZE)).await?;
+    let mut delivered = 0;
+    for delivery in due {
+      let outcome = match self.secrets.get(&delivery.url) {
+        Some(secret) => {
+          let (agent, secret, post) = (self.agent.clone(), secret.clone(), delivery.clone());
+          tokio::task::spawn_blocking(move || send(&agent, &post, &secret))
+            .await
+            .map_err(|e| VBError::Other(format!("Webhook task failed: {}", e)))?
+        }
+        // queued for a webhook since removed from the configuration
+        None => {
+          self.engine.run(move |engine| engine.repo.delete_delivery(delivery.id)).await?;
+          continue;
+        }
+      };
+      if outcome.is_ok() {
+        delivered += 1;
+      }
+      self
+        .engine
+        .run(move |engine| match outcome {
+          Ok(()) => engine.repo.delete_delivery(delivery.id),
+          Err(e) if delivery.attempts + 1 >= MAX_ATTEMPTS => {
+            warn!(url = %delivery.url, error = %e, "Giving up on webhook after {} attempts", MAX_ATTEMPTS);
+            engine.repo.delete_delivery(delivery.id)
+          }
+          Err(e) => engine.repo.retry_delivery(delivery.id, now + backoff(delivery.attempts), &e),
+        })
+        .await?;
+    }
+    Ok(delivered)
+  }
+
+  /// Deliver until the process exits: right after each event, and every
+  /// `interval` for retries
+  pub async fn run(self, interval: Duration) {
+    let mut events = self.engine.events.subscribe();
+    loop {
+      if let Err(e) = self.deliver_due().await {
+        warn!(error = %e, "Webhook delivery failed");
+      }
+      tokio::select! {
+        _ = tokio::time::sleep(interval) => {}
+        _ = events.recv() => {}
+      }
+    }
+  }
+}
+
+fn send(agent: &ureq::Agent, delivery: &Delivery, secret: &str) -> Result<(), String> {
+  let timestamp = chrono::Utc::now().timestamp();
+  agent
+    .post(&delivery.url)
+    .set("content-type", "application/json")
+    .set(TIMESTAMP_HEADER, &timestamp.to_string())
+    .set(SIGNATURE_HEADER, &signature(secret, timestamp, &delivery.body))
+    .send_bytes(&delivery.body)
+    .map(|_| ())
+    .map_err(|e| match e {
+      ureq::Error::Status(code, _) => format!("HTTP {}", code),
+      e => e.to_string(),
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_signature_and_backoff() {
+    // what a receiver computes with HMAC-SHA256("whsec", "1767225600.<body>")
+    assert_eq!(
+      signature("whsec", 1767225600, br#"{"kind":"anchored"}"#),
+      "sha256=bb2528865a006047127b6d1cd140985cd8d82981034f95db1b4016222b1e92ab"
+    );
+    assert_eq!((0..5).map(backoff).collect::<Vec<_>>(), vec![10, 20, 40, 80, 160]);
+    assert_eq!(backoff(30), 3600);
+  }
+}
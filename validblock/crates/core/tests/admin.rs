@@ -0,0 +1,127 @@
+mod common;
+
+use common::{anchor, request, TestServer};
+use tonic::transport::Channel;
+use tonic::Code;
+use validblock_core::auth::{hash_secret, Role};
+use validblock_core::proto::admin_service_client::AdminServiceClient;
+use validblock_core::proto::anchor_service_client::AnchorServiceClient;
+use validblock_core::proto::verify_service_client::VerifyServiceClient;
+use validblock_core::proto::{
+    CreateKeyRequest, ListAnchorsRequest, ListKeysRequest, MaintenanceRequest, ReloadConfigRequest, RevokeKeyRequest,
+    WalletStatusRequest,
+};
+
+/// Serve `AnchorService`, `VerifyService` and `AdminService` with a tenant
+/// "acme", a viewer key "viewer-key" for it, and `args` as the server's
+/// command line
+async fn serve(args: Vec<String>) -> Channel {
+    TestServer::new()
+        .engine(|engine| {
+            engine.create_tenant("acme", "Acme Corp", None).unwrap();
+            engine
+        })
+        .key("viewer-key", "acme", Role::Viewer)
+        .anchor()
+        .verify()
+        .admin(args)
+        .serve()
+        .await
+}
+
+fn create_key(owner: &str, role: &str) -> CreateKeyRequest {
+    CreateKeyRequest { owner: owner.into(), role: role.into() }
+}
+
+#[tokio::test]
+async fn test_roles_checked_on_every_service() {
+    let channel = serve(vec![]).await;
+    let mut admin = AdminServiceClient::new(channel.clone());
+    let mut anchors = AnchorServiceClient::new(channel.clone());
+    let mut verify = VerifyServiceClient::new(channel);
+
+    // a viewer may look anchors up, but neither anchor nor administer
+    verify.list_anchors(request(ListAnchorsRequest::default(), "viewer-key")).await.unwrap();
+    let err = anchors.anchor(request(anchor(b"memo"), "viewer-key")).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    let err = admin.list_keys(request(ListKeysRequest {}, "viewer-key")).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    // an admin key issued by the operator can issue keys of its own
+    let issued = admin.create_key(request(create_key("", "admin"), "app-token")).await.unwrap().into_inner();
+    let anchorer = admin.create_key(request(create_key("acme", "anchorer"), &issued.secret)).await.unwrap();
+    let anchorer = anchorer.into_inner();
+    anchors.anchor(request(anchor(b"memo"), &anchorer.secret)).await.unwrap();
+    let err = admin.list_keys(request(ListKeysRequest {}, &anchorer.secret)).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    let keys = admin.list_keys(request(ListKeysRequest {}, "app-token")).await.unwrap().into_inner().keys;
+    let mut roles: Vec<_> = keys.iter().map(|key| (key.owner.as_str(), key.role.as_str())).collect();
+    roles.sort();
+    assert_eq!(roles, vec![("acme", "anchorer"), ("default", "admin")]);
+    assert!(keys.iter().any(|key| key.id == hash_secret(&anchorer.secret).to_string()));
+
+    let revoke = RevokeKeyRequest { id: anchorer.key.unwrap().id };
+    admin.revoke_key(request(revoke.clone(), "app-token")).await.unwrap();
+    let err = anchors.anchor(request(anchor(b"memo 2"), &anchorer.secret)).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    let err = admin.revoke_key(request(revoke, "app-token")).await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let err = admin.create_key(request(create_key("acme", "root"), "app-token")).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    let err = admin.create_key(request(create_key("Acme Corp", "viewer"), "app-token")).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    let err = admin.create_key(request(create_key("globex", "viewer"), "app-token")).await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+    // a tenant's key could otherwise administer every other tenant
+    let err = admin.create_key(request(create_key("acme", "admin"), "app-token")).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_wallet_status_and_maintenance() {
+    let mut admin = AdminServiceClient::new(serve(vec![]).await);
+
+    let status = admin.wallet_status(request(WalletStatusRequest::default(), "app-token")).await.unwrap().into_inner();
+    assert!(status.reachable);
+    assert_eq!(status.network, "bitcoin");
+    let err = admin.wallet_status(request(WalletStatusRequest { tenant: "hr".into() }, "app-token")).await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    admin.checkpoint(request(MaintenanceRequest {}, "app-token")).await.unwrap();
+    let vacuumed = admin.vacuum(request(MaintenanceRequest {}, "app-token")).await.unwrap().into_inner();
+    assert!(vacuumed.size_bytes > 0);
+    let check = admin.integrity_check(request(MaintenanceRequest {}, "app-token")).await.unwrap().into_inner();
+    assert!(check.ok);
+    assert!(check.problems.is_empty());
+}
+
+#[tokio::test]
+async fn test_reload_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let keys = dir.path().join("keys");
+    let config = dir.path().join("validblock.toml");
+    std::fs::write(&keys, format!("{} ci viewer\n", hash_secret("ci-key"))).unwrap();
+    std::fs::write(&config, format!("[auth]\ntoken = \"app-token\"\napi_keys = {:?}\n", keys)).unwrap();
+    let args = vec!["--config".to_string(), config.to_str().unwrap().to_string()];
+    let channel = serve(args).await;
+    let mut admin = AdminServiceClient::new(channel.clone());
+    let mut verify = VerifyServiceClient::new(channel);
+
+    let list_as = |secret: &str| request(ListAnchorsRequest::default(), secret);
+    let err = verify.list_anchors(list_as("ci-key")).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    let reloaded = admin.reload_config(request(ReloadConfigRequest {}, "app-token")).await.unwrap().into_inner();
+    assert_eq!(reloaded.api_keys, 1);
+    verify.list_anchors(list_as("ci-key")).await.unwrap();
+    // keys only the old configuration knew are gone
+    let err = verify.list_anchors(list_as("viewer-key")).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    // a broken configuration changes nothing
+    std::fs::write(&config, "[auth\n").unwrap();
+    let err = admin.reload_config(request(ReloadConfigRequest {}, "app-token")).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    verify.list_anchors(list_as("ci-key")).await.unwrap();
+}
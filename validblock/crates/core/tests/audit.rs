@@ -0,0 +1,70 @@
+mod common;
+
+use common::{anchor, request, TestServer};
+use tokio_stream::StreamExt;
+use tonic::Code;
+use validblock_core::auth::{hash_secret, key_id, Role};
+use validblock_core::proto::admin_service_client::AdminServiceClient;
+use validblock_core::proto::anchor_service_client::AnchorServiceClient;
+use validblock_core::proto::verify_service_client::VerifyServiceClient;
+use validblock_core::proto::{ExistDigestRequest, ExportAuditRequest, ListAuditRequest, VerifyAuditRequest};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_calls_are_audited_and_chained() {
+    let channel = TestServer::new()
+        .key("viewer-key", "acme", Role::Viewer)
+        .audited()
+        .anchor()
+        .verify()
+        .admin(vec![])
+        .serve()
+        .await;
+    let mut anchors = AnchorServiceClient::new(channel.clone());
+    let mut verify = VerifyServiceClient::new(channel.clone());
+    let mut admin = AdminServiceClient::new(channel);
+
+    let digest = anchors.anchor(request(anchor(b"contract"), "app-token")).await.unwrap().into_inner().digest;
+    let err = anchors.anchor(request(anchor(b"contract"), "viewer-key")).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    let exists = ExistDigestRequest { digest: digest.clone() };
+    verify.exist_digest(request(exists.clone(), "viewer-key")).await.unwrap();
+    verify.exist_digest(request(exists, "stolen-key")).await.unwrap_err();
+
+    let entries = admin.list_audit(request(ListAuditRequest::default(), "app-token")).await.unwrap().into_inner().entries;
+    let summary: Vec<_> = entries
+        .iter()
+        .map(|e| (e.actor.as_str(), e.action.as_str(), e.target.as_str(), e.outcome.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("default", "AnchorService/Anchor", digest.as_str(), "OK"),
+            ("acme", "AnchorService/Anchor", "", "PERMISSION_DENIED"),
+            ("acme", "VerifyService/ExistDigest", digest.as_str(), "OK"),
+            ("", "VerifyService/ExistDigest", "", "UNAUTHENTICATED"),
+        ]
+    );
+    assert_eq!(entries[0].credential, "token");
+    assert_eq!(entries[1].credential, key_id(&hash_secret("viewer-key")));
+    assert!(entries.iter().all(|e| e.source.starts_with("127.0.0.1:") && e.timestamp > 0));
+
+    // the listing itself was an admin action
+    let filter = ListAuditRequest { action: "AdminService/ListAudit".into(), ..Default::default() };
+    let listed = admin.list_audit(request(filter, "app-token")).await.unwrap().into_inner().entries;
+    assert_eq!(listed.len(), 1);
+
+    let verified = admin.verify_audit(request(VerifyAuditRequest {}, "app-token")).await.unwrap().into_inner();
+    assert!(verified.ok);
+    assert_eq!(verified.entries, 6);
+
+    let mut export = admin.export_audit(request(ExportAuditRequest::default(), "app-token")).await.unwrap().into_inner();
+    let mut data = vec![];
+    while let Some(chunk) = export.next().await {
+        data.extend(chunk.unwrap().data);
+    }
+    let lines: Vec<serde_json::Value> =
+        String::from_utf8(data).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), 7);
+    assert_eq!(lines[0]["target"], digest);
+    assert_eq!(lines[5]["hash"], verified.head);
+}
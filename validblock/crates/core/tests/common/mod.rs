@@ -0,0 +1,158 @@
+//! gRPC server the integration tests run against: a chosen set of services
+//! over an in-memory engine, with the bearer token "app-token" and any API
+//! keys a test adds
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Channel, Endpoint, Server};
+use tower::util::{option_layer, MapResponseLayer};
+use validblock_core::audit::AuditLayer;
+use validblock_core::auth::{hash_secret, ApiKey, Authenticator, Role};
+use validblock_core::limits::{oversize_exhausted, Limits, LimitsConfig};
+use validblock_core::proto::admin_service_server::AdminServiceServer;
+use validblock_core::proto::anchor_service_server::AnchorServiceServer;
+use validblock_core::proto::tenant_service_server::TenantServiceServer;
+use validblock_core::proto::verify_service_server::VerifyServiceServer;
+use validblock_core::proto::{AnchorRequest, Policy};
+use validblock_core::services::{AdminServiceImpl, AnchorServiceImpl, TenantServiceImpl, VerifyServiceImpl};
+use validblock_core::wallet::mock::MockWallet;
+use validblock_core::{AnchorEngine, AsyncEngine};
+use validblock_storage::AnchorRepo;
+
+/// Which services to serve and how
+pub struct TestServer {
+    engine: AnchorEngine<MockWallet>,
+    auth: Authenticator,
+    limits: Arc<Limits>,
+    max_message: Option<usize>,
+    audited: bool,
+    anchor: bool,
+    verify: bool,
+    tenants: bool,
+    admin: Option<Vec<String>>,
+}
+
+impl TestServer {
+    /// No services yet, over a fresh in-memory engine
+    pub fn new() -> Self {
+        TestServer {
+            engine: AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet),
+            auth: Authenticator::default().with_token("app-token"),
+            limits: Arc::default(),
+            max_message: None,
+            audited: false,
+            anchor: false,
+            verify: false,
+            tenants: false,
+            admin: None,
+        }
+    }
+
+    /// Configure the engine further
+    pub fn engine(mut self, f: impl FnOnce(AnchorEngine<MockWallet>) -> AnchorEngine<MockWallet>) -> Self {
+        self.engine = f(self.engine);
+        self
+    }
+
+    /// Accept `secret` as `owner`'s key in `role`
+    pub fn key(mut self, secret: &str, owner: &str, role: Role) -> Self {
+        self.auth = self.auth.with_key(hash_secret(secret), ApiKey { owner: owner.into(), role });
+        self
+    }
+
+    /// Enforce `config`, on the engine and on every call
+    pub fn limits(mut self, config: LimitsConfig) -> Self {
+        self.limits = Arc::new(Limits::new(config));
+        self.engine = self.engine.with_limits(self.limits.clone());
+        self
+    }
+
+    /// Decode messages up to `bytes` only
+    pub fn max_message(mut self, bytes: usize) -> Self {
+        self.max_message = Some(bytes);
+        self
+    }
+
+    /// Put every call through the audit layer
+    pub fn audited(mut self) -> Self {
+        self.audited = true;
+        self
+    }
+
+    pub fn anchor(mut self) -> Self {
+        self.anchor = true;
+        self
+    }
+
+    pub fn verify(mut self) -> Self {
+        self.verify = true;
+        self
+    }
+
+    pub fn tenants(mut self) -> Self {
+        self.tenants = true;
+        self
+    }
+
+    /// Serve `AdminService`, with `args` as the server's command line
+    pub fn admin(mut self, args: Vec<String>) -> Self {
+        self.admin = Some(args);
+        self
+    }
+
+    /// Start serving on a free local port and connect to it
+    pub async fn serve(self) -> Channel {
+        let engine = AsyncEngine::new(self.engine);
+        let auth = Arc::new(self.auth);
+        let limits = self.limits;
+        let guard = |role| limits.interceptor(auth.interceptor(role));
+        let max_message = self.max_message.unwrap_or(4 * 1024 * 1024);
+
+        let anchor = self.anchor.then(|| {
+            let service = AnchorServiceServer::new(AnchorServiceImpl::new(engine.clone())).max_decoding_message_size(max_message);
+            InterceptedService::new(service, guard(Role::Anchorer))
+        });
+        let verify = self.verify.then(|| {
+            let service = VerifyServiceServer::new(VerifyServiceImpl::new(engine.clone())).max_decoding_message_size(max_message);
+            InterceptedService::new(service, guard(Role::Viewer))
+        });
+        let tenants = self.tenants.then(|| {
+            let service = TenantServiceServer::new(TenantServiceImpl::new(engine.clone(), auth.clone()));
+            InterceptedService::new(service, guard(Role::Admin))
+        });
+        let admin = self.admin.map(|args| {
+            let service = AdminServiceServer::new(
+                AdminServiceImpl::new(engine.clone(), auth.clone()).with_limits(limits.clone()).with_args(args),
+            );
+            InterceptedService::new(service, guard(Role::Admin))
+        });
+        let audit = self.audited.then(|| AuditLayer::new(engine.clone(), auth.clone()));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = Server::builder()
+            .layer(MapResponseLayer::new(oversize_exhausted))
+            .layer(option_layer(audit))
+            .add_optional_service(anchor)
+            .add_optional_service(verify)
+            .add_optional_service(tenants)
+            .add_optional_service(admin);
+        tokio::spawn(server.serve_with_incoming(TcpListenerStream::new(listener)));
+        Endpoint::from_shared(url).unwrap().connect().await.unwrap()
+    }
+}
+
+/// `message` with `secret` as its bearer credential
+pub fn request<T>(message: T, secret: &str) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request.metadata_mut().insert("authorization", format!("Bearer {}", secret).parse().unwrap());
+    request
+}
+
+/// Local-only anchor of `content`
+pub fn anchor(content: &[u8]) -> AnchorRequest {
+    AnchorRequest { file_content: content.to_vec(), memo: String::new(), policy: Policy::LocalOnly.into() }
+}
@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use tokio::sync::Mutex;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::StreamExt;
+use tonic::transport::{Endpoint, Server};
+use validblock_core::auth::{hash_secret, ApiKey, Authenticator, Role};
+use validblock_core::events::Event;
+use validblock_core::proto::verify_service_client::VerifyServiceClient;
+use validblock_core::proto::verify_service_server::VerifyServiceServer;
+use validblock_core::proto::SubscribeEventsRequest;
+use validblock_core::services::VerifyServiceImpl;
+use validblock_core::wallet::mock::MockWallet;
+use validblock_core::webhooks::{signature, Dispatcher, WebhookConfig, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use validblock_core::{AnchorEngine, AsyncEngine, MemoPolicy};
+use validblock_storage::AnchorRepo;
+
+type Engine = AsyncEngine<MockWallet>;
+
+fn anchor(engine: &AnchorEngine<MockWallet>, owner: &str, content: &[u8]) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file");
+    std::fs::write(&path, content).unwrap();
+    engine.anchor(&path, MemoPolicy::LocalOnly, owner, None).unwrap();
+}
+
+#[tokio::test]
+async fn test_subscribe_events() {
+    let engine: Engine = AsyncEngine::new(AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet));
+    let auth = Arc::new(
+        Authenticator::default()
+            .with_token("app-token")
+            .with_key(hash_secret("acme-key"), ApiKey { owner: "acme".into(), role: Role::Viewer }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = Server::builder()
+        .add_service(VerifyServiceServer::with_interceptor(VerifyServiceImpl::new(engine.clone()), auth.interceptor(Role::Viewer)));
+    tokio::spawn(server.serve_with_incoming(TcpListenerStream::new(listener)));
+
+    let channel = Endpoint::from_shared(url).unwrap().connect().await.unwrap();
+    let subscribe = |secret: &str, kinds: &[&str]| {
+        let mut request = tonic::Request::new(SubscribeEventsRequest { kinds: kinds.iter().map(|k| k.to_string()).collect() });
+        request.metadata_mut().insert("authorization", format!("Bearer {}", secret).parse().unwrap());
+        let mut client = VerifyServiceClient::new(channel.clone());
+        async move { client.subscribe_events(request).await.map(|res| res.into_inner()) }
+    };
+    let mut everything = subscribe("app-token", &[]).await.unwrap();
+    let mut acme = subscribe("acme-key", &["anchored"]).await.unwrap();
+    assert!(subscribe("acme-key", &["mined"]).await.is_err());
+
+    anchor(&engine, "globex", b"globex file");
+    anchor(&engine, "acme", b"acme file");
+    let first = everything.next().await.unwrap().unwrap();
+    assert_eq!((first.kind.as_str(), first.owner.as_str()), ("anchored", "globex"));
+    assert_eq!(everything.next().await.unwrap().unwrap().owner, "acme");
+    // acme only sees its own
+    let own = acme.next().await.unwrap().unwrap();
+    assert_eq!((own.kind.as_str(), own.owner.as_str()), ("anchored", "acme"));
+}
+
+/// Headers and body of a webhook POST
+type Post = (HeaderMap, Vec<u8>);
+
+#[derive(Clone, Default)]
+struct Receiver {
+    calls: Arc<AtomicUsize>,
+    received: Arc<Mutex<Vec<Post>>>,
+}
+
+/// Fails the first delivery, accepts the rest
+async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: axum::body::Bytes) -> StatusCode {
+    if receiver.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+        return StatusCode::SERVICE_UNAVAILABLE;
+    }
+    receiver.received.lock().await.push((headers, body.to_vec()));
+    StatusCode::NO_CONTENT
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_webhook_delivery() {
+    let receiver = Receiver::default();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let app = Router::new().route("/hook", post(receive)).with_state(receiver.clone());
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+    let hooks = vec![WebhookConfig { url: url.clone(), secret: "whsec".into() }];
+    let engine: Engine =
+        AsyncEngine::new(AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet).with_webhooks(vec![url.clone()]));
+    let dispatcher = Dispatcher::new(engine.clone(), &hooks);
+
+    anchor(&engine, "acme", b"first");
+    assert_eq!(dispatcher.deliver_due().await.unwrap(), 0);
+    // the failure stays queued, due again after the backoff
+    let queued = engine.repo.due_deliveries(i64::MAX, 10).unwrap();
+    assert_eq!((queued.len(), queued[0].attempts), (1, 1));
+    assert_eq!(dispatcher.deliver_due().await.unwrap(), 0);
+
+    anchor(&engine, "acme", b"second");
+    assert_eq!(dispatcher.deliver_due().await.unwrap(), 1);
+    let received = receiver.received.lock().await;
+    let (headers, body) = &received[0];
+    let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+    assert_eq!(headers[SIGNATURE_HEADER].to_str().unwrap(), signature("whsec", timestamp, body));
+    let event: Event = serde_json::from_slice(body).unwrap();
+    assert_eq!(event.owner, "acme");
+    assert_eq!(engine.repo.due_deliveries(i64::MAX, 10).unwrap().len(), 1);
+}
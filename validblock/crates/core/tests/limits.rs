@@ -0,0 +1,54 @@
+mod common;
+
+use common::{anchor, TestServer};
+use tonic::transport::Channel;
+use tonic::Code;
+use validblock_core::auth::Role;
+use validblock_core::limits::LimitsConfig;
+use validblock_core::proto::anchor_service_client::AnchorServiceClient;
+use validblock_core::proto::{AnchorRequest, GetQuotaRequest, Policy};
+
+/// Serve `AnchorService` under `config`, decoding messages up to
+/// `max_message` bytes
+async fn serve(config: LimitsConfig, max_message: usize) -> Channel {
+    TestServer::new().key("acme-key", "acme", Role::Anchorer).limits(config).max_message(max_message).anchor().serve().await
+}
+
+fn request<T>(message: T) -> tonic::Request<T> {
+    common::request(message, "acme-key")
+}
+
+fn on_chain(content: &[u8]) -> AnchorRequest {
+    AnchorRequest { policy: Policy::OnChain.into(), ..anchor(content) }
+}
+
+#[tokio::test]
+async fn test_quota_and_upload_cap() {
+    let config = LimitsConfig { max_upload_bytes: 16, daily_on_chain: Some(1), ..Default::default() };
+    let mut client = AnchorServiceClient::new(serve(config, 1024).await);
+
+    let quota = client.get_quota(request(GetQuotaRequest {})).await.unwrap().into_inner();
+    assert_eq!((quota.used, quota.limit, quota.remaining), (0, 1, 1));
+    client.anchor(request(on_chain(b"first"))).await.unwrap();
+    let err = client.anchor(request(on_chain(b"second"))).await.unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted);
+    let quota = client.get_quota(request(GetQuotaRequest {})).await.unwrap().into_inner();
+    assert_eq!((quota.used, quota.remaining), (1, 0));
+    assert!(quota.resets_at > chrono::Utc::now().timestamp());
+
+    let err = client.anchor(request(on_chain(&[0; 17]))).await.unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted);
+}
+
+#[tokio::test]
+async fn test_rate_limit_and_message_size() {
+    let config = LimitsConfig { requests_per_minute: Some(2), ..Default::default() };
+    let mut client = AnchorServiceClient::new(serve(config, 64).await);
+
+    // over the decoding limit, refused before the handler runs
+    let err = client.anchor(request(on_chain(&[0; 128]))).await.unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted);
+    client.get_quota(request(GetQuotaRequest {})).await.unwrap();
+    let err = client.get_quota(request(GetQuotaRequest {})).await.unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted);
+}
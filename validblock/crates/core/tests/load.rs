@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use validblock_core::wallet::mock::MockWallet;
+use validblock_core::wallet::{AnchorTx, WalletAdapter};
+use validblock_core::limits::{Limits, LimitsConfig};
+use validblock_core::{AnchorEngine, AsyncEngine, MemoPolicy, VBError, DEFAULT_OWNER};
+use validblock_storage::AnchorRepo;
+
+/// Mock wallet whose broadcasts take `delay`, like a round trip to a node
+struct SlowWallet {
+    delay: Duration,
+    /// Broadcasts under way
+    busy: Arc<AtomicUsize>,
+    /// Broadcasts made
+    sent: Arc<AtomicUsize>,
+}
+
+impl SlowWallet {
+    fn new(delay: Duration) -> Self {
+        SlowWallet { delay, busy: Arc::default(), sent: Arc::default() }
+    }
+}
+
+impl WalletAdapter for SlowWallet {
+    fn anchor(&mut self, payload: &[u8]) -> Result<AnchorTx, VBError> {
+        self.busy.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(self.delay);
+        self.busy.fetch_sub(1, Ordering::SeqCst);
+        self.sent.fetch_add(1, Ordering::SeqCst);
+        MockWallet.anchor(payload)
+    }
+}
+
+fn content(owner: &str, n: usize) -> Vec<u8> {
+    format!("{} file {}", owner, n).into_bytes()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_lookups_never_wait_for_a_broadcast() {
+    let wallet = SlowWallet::new(Duration::from_secs(2));
+    let busy = wallet.busy.clone();
+    let engine = AsyncEngine::new(
+        AnchorEngine::new(AnchorRepo::memory().unwrap(), wallet)
+            .with_tenant_wallet("globex", SlowWallet::new(Duration::ZERO)),
+    );
+    engine.anchor(b"anchored".to_vec(), MemoPolicy::LocalOnly, DEFAULT_OWNER.into(), None, None).await.unwrap();
+
+    let stuck = {
+        let engine = engine.clone();
+        tokio::spawn(async move { engine.anchor(b"slow".to_vec(), MemoPolicy::OnChain, DEFAULT_OWNER.into(), None, None).await })
+    };
+    while busy.load(Ordering::SeqCst) == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    // while the default wallet broadcasts: lookups, local anchors and
+    // anchors paid by another wallet all go through
+    let lookups: Vec<_> = (0..100)
+        .map(|_| {
+            let engine = engine.clone();
+            tokio::spawn(async move { engine.verify(b"anchored".to_vec(), None).await })
+        })
+        .collect();
+    for lookup in lookups {
+        assert_eq!(lookup.await.unwrap().unwrap().1.len(), 1);
+    }
+    assert_eq!(engine.list(None, 10, 0).await.unwrap().len(), 1);
+    engine.anchor(b"local".to_vec(), MemoPolicy::LocalOnly, DEFAULT_OWNER.into(), None, None).await.unwrap();
+    engine.anchor(b"globex".to_vec(), MemoPolicy::OnChain, "globex".into(), None, None).await.unwrap();
+    assert!(!stuck.is_finished());
+
+    assert!(stuck.await.unwrap().unwrap().record.txid.is_some());
+    assert_eq!(engine.list(None, 10, 0).await.unwrap().len(), 4);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_throughput() {
+    const TENANTS: usize = 8;
+    const ANCHORS_PER_TENANT: usize = 4;
+    const VERIFIES: usize = 256;
+    let delay = Duration::from_millis(100);
+
+    let tenants: Vec<String> = (0..TENANTS).map(|n| format!("tenant-{}", n)).collect();
+    let engine = AsyncEngine::new(tenants.iter().fold(
+        AnchorEngine::new(AnchorRepo::memory().unwrap(), SlowWallet::new(delay)),
+        |engine, id| engine.with_tenant_wallet(id, SlowWallet::new(delay)),
+    ));
+    engine.anchor(b"anchored".to_vec(), MemoPolicy::LocalOnly, DEFAULT_OWNER.into(), None, None).await.unwrap();
+
+    let start = Instant::now();
+    let mut anchors = vec![];
+    for owner in &tenants {
+        for n in 0..ANCHORS_PER_TENANT {
+            let (engine, owner) = (engine.clone(), owner.clone());
+            anchors.push(tokio::spawn(async move {
+                engine.anchor(content(&owner, n), MemoPolicy::OnChain, owner, None, None).await
+            }));
+        }
+    }
+    let verifies: Vec<_> = (0..VERIFIES)
+        .map(|_| {
+            let engine = engine.clone();
+            tokio::spawn(async move { engine.verify(b"anchored".to_vec(), None).await })
+        })
+        .collect();
+    for anchor in anchors {
+        assert!(!anchor.await.unwrap().unwrap().existing);
+    }
+    for verify in verifies {
+        assert_eq!(verify.await.unwrap().unwrap().1.len(), 1);
+    }
+    let elapsed = start.elapsed();
+
+    // each wallet takes its anchors in turn, but the wallets work side by
+    // side: one engine-wide lock would need every delay back to back
+    let serial = delay * (TENANTS * ANCHORS_PER_TENANT) as u32;
+    assert!(elapsed < serial / 2, "took {:?}, serially {:?}", elapsed, serial);
+    let anchored = TENANTS * ANCHORS_PER_TENANT;
+    let throughput = (anchored + VERIFIES) as f64 / elapsed.as_secs_f64();
+    println!("{} anchors and {} verifies in {:?} ({:.0} ops/s)", anchored, VERIFIES, elapsed, throughput);
+    for owner in &tenants {
+        assert_eq!(engine.list(Some(owner.clone()), 100, 0).await.unwrap().len(), ANCHORS_PER_TENANT);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_anchors_spend_once_within_quota() {
+    let wallet = SlowWallet::new(Duration::from_millis(50));
+    let sent = wallet.sent.clone();
+    let limits = Arc::new(Limits::new(LimitsConfig { daily_on_chain: Some(3), ..Default::default() }));
+    let engine = AsyncEngine::new(
+        AnchorEngine::new(AnchorRepo::memory().unwrap(), wallet).with_idempotent(true).with_limits(limits),
+    );
+    let anchor = |content: Vec<u8>| {
+        let engine = engine.clone();
+        tokio::spawn(async move { engine.anchor(content, MemoPolicy::OnChain, "acme".into(), None, None).await })
+    };
+
+    // the same file from many clients at once is paid for once
+    let same: Vec<_> = (0..16).map(|_| anchor(b"contract".to_vec())).collect();
+    let mut anchored = 0;
+    for result in same {
+        match result.await.unwrap() {
+            Ok(done) => anchored += usize::from(!done.existing),
+            Err(e) => assert!(matches!(e, VBError::DbDuplicate), "{}", e),
+        }
+    }
+    assert_eq!((anchored, sent.load(Ordering::SeqCst)), (1, 1));
+
+    // two more fit in the quota, however many race for them
+    let racing: Vec<_> = (0..16).map(|n| anchor(content("acme", n))).collect();
+    let mut refused = 0;
+    for result in racing {
+        match result.await.unwrap() {
+            Ok(_) => {}
+            Err(VBError::ResourceExhausted(_)) => refused += 1,
+            Err(e) => panic!("{}", e),
+        }
+    }
+    assert_eq!((refused, sent.load(Ordering::SeqCst)), (14, 3));
+}
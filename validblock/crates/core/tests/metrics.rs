@@ -0,0 +1,78 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Endpoint, Server};
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+use validblock_core::health::{watch_readiness, Readiness};
+use validblock_core::metrics::{self, metrics};
+use validblock_core::wallet::mock::MockWallet;
+use validblock_core::{AnchorEngine, AsyncEngine, MemoPolicy, VBError};
+use validblock_storage::AnchorRepo;
+
+type Engine = AsyncEngine<MockWallet>;
+
+/// The value of the sample starting with `series` in `text`
+fn sample(text: &str, series: &str) -> f64 {
+    text.lines()
+        .find_map(|line| line.strip_prefix(series).and_then(|rest| rest.trim().parse().ok()))
+        .unwrap_or_else(|| panic!("{} missing from\n{}", series, text))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_metrics_and_readiness() {
+    let engine: Engine = AsyncEngine::new(AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet));
+    let (reporter, health) = tonic_health::server::health_reporter();
+    let readiness = Arc::new(Readiness::new(reporter));
+
+    let grpc = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let grpc_url = format!("http://{}", grpc.local_addr().unwrap());
+    tokio::spawn(Server::builder().add_service(health).serve_with_incoming(TcpListenerStream::new(grpc)));
+    let http = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", http.local_addr().unwrap());
+    let app = metrics::router(engine.clone(), readiness.clone());
+    tokio::spawn(axum::Server::from_tcp(http).unwrap().serve(app.into_make_service()));
+
+    let client = reqwest::Client::new();
+    assert_eq!(client.get(format!("{}/healthz", url)).send().await.unwrap().status(), StatusCode::OK);
+    assert_eq!(client.get(format!("{}/readyz", url)).send().await.unwrap().status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // ready once the database and wallet answer
+    tokio::spawn(watch_readiness(engine.clone(), readiness.clone(), Duration::from_millis(50)));
+    while !readiness.is_ready() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(client.get(format!("{}/readyz", url)).send().await.unwrap().status(), StatusCode::OK);
+    let channel = Endpoint::from_shared(grpc_url).unwrap().connect().await.unwrap();
+    let mut health = HealthClient::new(channel);
+    for service in ["", "validblock.AnchorService"] {
+        let res = health.check(HealthCheckRequest { service: service.into() }).await.unwrap().into_inner();
+        assert_eq!(res.status(), ServingStatus::Serving);
+    }
+
+    let content = b"twelve bytes".to_vec();
+    let anchor = || engine.anchor(content.clone(), MemoPolicy::OnChain, "acme".into(), None, None);
+    anchor().await.unwrap();
+    assert!(matches!(anchor().await, Err(VBError::DbDuplicate)));
+    assert_eq!(engine.verify(content.clone(), None).await.unwrap().1.len(), 1);
+    validblock_core::status::to_status(VBError::DbDuplicate);
+
+    let res = client.get(format!("{}/metrics", url)).send().await.unwrap();
+    assert!(res.headers()["content-type"].to_str().unwrap().starts_with("text/plain"));
+    let text = res.text().await.unwrap();
+    assert_eq!(sample(&text, r#"validblock_operations_total{operation="anchor",outcome="ok"}"#), 1.0);
+    assert_eq!(sample(&text, r#"validblock_operations_total{operation="anchor",outcome="error"}"#), 1.0);
+    assert_eq!(sample(&text, r#"validblock_operations_total{operation="verify",outcome="ok"}"#), 1.0);
+    assert_eq!(sample(&text, r#"validblock_operation_duration_seconds_count{operation="anchor"}"#), 2.0);
+    assert_eq!(sample(&text, "validblock_hashed_bytes_total"), 36.0);
+    assert_eq!(sample(&text, r#"validblock_errors_total{code="DUPLICATE_DIGEST"}"#), 1.0);
+    // the mock wallet broadcasts and never confirms
+    assert_eq!(sample(&text, "validblock_pending_confirmations"), 1.0);
+    assert!(sample(&text, "validblock_db_size_bytes") > 0.0);
+
+    metrics().sample_wallet(&engine).unwrap();
+    assert_eq!(sample(&metrics().render(), "validblock_wallet_balance_sats"), 0.0);
+}
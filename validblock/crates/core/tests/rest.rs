@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use base64::Engine as _;
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Endpoint, Server};
+use validblock_core::auth::{hash_secret, ApiKey, Authenticator, Role};
+use validblock_core::proto::verify_service_client::VerifyServiceClient;
+use validblock_core::proto::verify_service_server::VerifyServiceServer;
+use validblock_core::proto::ExistDigestRequest;
+use validblock_core::rest::{self, AnchorReply, ListReply, RestLayer, VerifyReply};
+use validblock_core::services::VerifyServiceImpl;
+use validblock_core::wallet::mock::MockWallet;
+use validblock_core::{AnchorEngine, AsyncEngine};
+use validblock_storage::AnchorRepo;
+
+/// Serve the REST gateway and `VerifyService` on one free port
+async fn serve() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let engine = AsyncEngine::new(AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet));
+    let auth = Arc::new(
+        Authenticator::default()
+            .with_token("app-token")
+            .with_key(hash_secret("acme-key"), ApiKey { owner: "acme".into(), role: Role::Anchorer }),
+    );
+    let server = Server::builder()
+        .accept_http1(true)
+        .layer(RestLayer::new(rest::router(engine.clone(), auth.clone(), Arc::default())))
+        .add_service(VerifyServiceServer::with_interceptor(VerifyServiceImpl::new(engine), auth.interceptor(Role::Viewer)));
+    tokio::spawn(server.serve_with_incoming(TcpListenerStream::new(listener)));
+    format!("http://{}", addr)
+}
+
+fn content(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+#[tokio::test]
+async fn test_rest_gateway() {
+    let url = serve().await;
+    let client = Client::new();
+
+    // anchor, then the same file again
+    let anchor = |token: &str| {
+        client
+            .post(format!("{}/anchors", url))
+            .bearer_auth(token)
+            .json(&json!({ "content": content(b"contract v1"), "memo": "signed copy" }))
+    };
+    let res = anchor("acme-key").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let anchored: AnchorReply = res.json().await.unwrap();
+    assert_eq!(anchored.status, "local");
+    assert!(!anchored.existing);
+    let res = anchor("acme-key").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let error: Value = res.json().await.unwrap();
+    assert_eq!(error["error"]["code"], "DUPLICATE_DIGEST");
+    assert_eq!(anchor("wrong").send().await.unwrap().status(), StatusCode::UNAUTHORIZED);
+
+    // look it up by digest and by content
+    let res = client.get(format!("{}/anchors/{}", url, anchored.digest)).bearer_auth("acme-key").send().await.unwrap();
+    let events: VerifyReply = res.json().await.unwrap();
+    assert_eq!(events.events[0].owner, "acme");
+    assert_eq!(events.events[0].memo.as_deref(), Some("signed copy"));
+    let res = client.get(format!("{}/anchors/{}", url, "00".repeat(32))).bearer_auth("acme-key").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let res = client.get(format!("{}/anchors/nothex", url)).bearer_auth("acme-key").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let verify = |bytes: &[u8]| {
+        client.post(format!("{}/verify", url)).bearer_auth("acme-key").json(&json!({ "content": content(bytes) }))
+    };
+    let found: VerifyReply = verify(b"contract v1").send().await.unwrap().json().await.unwrap();
+    assert!(found.verified);
+    assert_eq!(found.digest, anchored.digest);
+    let missing: VerifyReply = verify(b"contract v2").send().await.unwrap().json().await.unwrap();
+    assert!(!missing.verified);
+    assert!(missing.events.is_empty());
+    let res = client.post(format!("{}/verify", url)).bearer_auth("acme-key").json(&json!({})).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // list pages
+    anchor("app-token").send().await.unwrap();
+    let list = |token: &str, query: &str| client.get(format!("{}/anchors?{}", url, query)).bearer_auth(token.to_string());
+    let all: ListReply = list("app-token", "").send().await.unwrap().json().await.unwrap();
+    assert_eq!(all.events.len(), 2);
+    let page: ListReply = list("app-token", "limit=1&offset=1").send().await.unwrap().json().await.unwrap();
+    assert_eq!(page.events.len(), 1);
+    let own: ListReply = list("acme-key", "").send().await.unwrap().json().await.unwrap();
+    assert_eq!(own.events.len(), 1);
+
+    // the document covers every route
+    let doc: Value = client.get(format!("{}/openapi.json", url)).send().await.unwrap().json().await.unwrap();
+    for path in ["/anchors", "/anchors/{digest}", "/verify"] {
+        assert!(doc["paths"][path].is_object(), "{} missing", path);
+    }
+    assert!(doc["components"]["securitySchemes"]["bearer"].is_object());
+
+    // gRPC still answers on the same port
+    let channel = Endpoint::from_shared(url).unwrap().connect().await.unwrap();
+    let mut request = tonic::Request::new(ExistDigestRequest { digest: anchored.digest });
+    request.metadata_mut().insert("authorization", "Bearer app-token".parse().unwrap());
+    assert!(VerifyServiceClient::new(channel).exist_digest(request).await.unwrap().into_inner().exists);
+}
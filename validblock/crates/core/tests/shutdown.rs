@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tonic::transport::{Channel, Endpoint};
+use validblock_core::config::ServerConfig;
+use validblock_core::proto::anchor_service_client::AnchorServiceClient;
+use validblock_core::proto::{AnchorRequest, Policy};
+use validblock_core::server::serve_until;
+use validblock_core::wallet::mock::MockWallet;
+use validblock_core::{Digest256, DEFAULT_OWNER};
+use validblock_storage::{AnchorIntent, AnchorRepo};
+
+async fn connect(url: &str) -> Channel {
+    for _ in 0..100 {
+        if let Ok(channel) = Endpoint::from_shared(url.to_string()).unwrap().connect().await {
+            return channel;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("server did not come up at {}", url);
+}
+
+fn request<T>(message: T) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request.metadata_mut().insert("authorization", "Bearer app-token".parse().unwrap());
+    request
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_recovers_on_start_and_stops_on_signal() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("anchors.db").to_str().unwrap().to_string();
+    // a previous run died with an anchor between wallet and record
+    let interrupted = AnchorIntent {
+        digest: Digest256([7; 32]),
+        owner: DEFAULT_OWNER.to_string(),
+        ts: 1,
+        memo: None,
+        payload: vec![7; 34],
+    };
+    AnchorRepo::new(Some(&db)).unwrap().insert_intent(&interrupted).unwrap();
+
+    let listen = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let mut config = ServerConfig { listen, db: Some(db.clone()), shutdown_grace: 5, ..Default::default() };
+    config.auth.token = Some("app-token".into());
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(serve_until(config, MockWallet, HashMap::new(), async {
+        let _ = stopped.await;
+    }));
+
+    let url = format!("http://{}", listen);
+    let mut client = AnchorServiceClient::new(connect(&url).await);
+    let anchor = AnchorRequest { file_content: b"contract".to_vec(), memo: String::new(), policy: Policy::OnChain.into() };
+    client.anchor(request(anchor)).await.unwrap();
+
+    stop.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(10), server).await.unwrap().unwrap().unwrap();
+    assert!(Endpoint::from_shared(url).unwrap().connect().await.is_err());
+
+    // the mock wallet never broadcast the interrupted anchor, so it was
+    // dropped; the new one was recorded and left no intent behind
+    let repo = AnchorRepo::new(Some(&db)).unwrap();
+    assert!(repo.intents().unwrap().is_empty());
+    assert!(repo.get_owned(&interrupted.digest, DEFAULT_OWNER).unwrap().is_none());
+    assert_eq!(repo.all().unwrap().len(), 1);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{anchor, request, TestServer};
+use tonic::Code;
+use validblock_core::proto::anchor_service_client::AnchorServiceClient;
+use validblock_core::proto::tenant_service_client::TenantServiceClient;
+use validblock_core::proto::{
+    CreateTenantKeyRequest, CreateTenantRequest, DisableTenantRequest, EnableTenantRequest, ListTenantsRequest,
+};
+
+#[tokio::test]
+async fn test_tenant_lifecycle() {
+    // multi-tenant mode
+    let channel = TestServer::new().engine(|engine| engine.with_required_tenants(true)).anchor().tenants().serve().await;
+    let mut tenants = TenantServiceClient::new(channel.clone());
+    let mut anchors = AnchorServiceClient::new(channel);
+
+    let create = CreateTenantRequest { id: "hr".into(), name: "Human Resources".into(), fee_rate: 5 };
+    let hr = tenants.create_tenant(request(create.clone(), "app-token")).await.unwrap().into_inner();
+    assert_eq!((hr.id.as_str(), hr.fee_rate, hr.disabled), ("hr", 5, false));
+    let err = tenants.create_tenant(request(create, "app-token")).await.unwrap_err();
+    assert_eq!(err.code(), Code::AlreadyExists);
+
+    let key = CreateTenantKeyRequest { tenant: "hr".into(), role: "anchorer".into() };
+    let secret = tenants.create_tenant_key(request(key, "app-token")).await.unwrap().into_inner().secret;
+    let key = CreateTenantKeyRequest { tenant: "legal".into(), role: "anchorer".into() };
+    assert_eq!(tenants.create_tenant_key(request(key, "app-token")).await.unwrap_err().code(), Code::NotFound);
+    let key = CreateTenantKeyRequest { tenant: "hr".into(), role: "admin".into() };
+    assert_eq!(tenants.create_tenant_key(request(key, "app-token")).await.unwrap_err().code(), Code::InvalidArgument);
+
+    // the new key works at once, and only as hr
+    anchors.anchor(request(anchor(b"offer letter"), &secret)).await.unwrap();
+    let err = tenants.list_tenants(request(ListTenantsRequest {}, &secret)).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    let disabled = tenants.disable_tenant(request(DisableTenantRequest { id: "hr".into() }, "app-token")).await.unwrap();
+    assert!(disabled.into_inner().disabled);
+    let err = anchors.anchor(request(anchor(b"resignation"), &secret)).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    tenants.enable_tenant(request(EnableTenantRequest { id: "hr".into() }, "app-token")).await.unwrap();
+    anchors.anchor(request(anchor(b"resignation"), &secret)).await.unwrap();
+
+    let listed = tenants.list_tenants(request(ListTenantsRequest {}, "app-token")).await.unwrap().into_inner();
+    assert_eq!(listed.tenants.len(), 1);
+    assert!(!listed.tenants[0].disabled);
+}
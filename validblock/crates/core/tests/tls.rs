@@ -0,0 +1,57 @@
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Channel, Endpoint, Server};
+use validblock_core::proto::verify_service_client::VerifyServiceClient;
+use validblock_core::proto::verify_service_server::VerifyServiceServer;
+use validblock_core::proto::ExistDigestRequest;
+use validblock_core::services::VerifyServiceImpl;
+use validblock_core::tls::{client_config, SelfSigned, TlsConfig};
+use validblock_core::wallet::mock::MockWallet;
+use validblock_core::{AnchorEngine, AsyncEngine};
+use validblock_storage::AnchorRepo;
+
+/// Serve `VerifyService` over TLS on a free port, returning its address
+async fn serve(tls: TlsConfig) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let engine = AsyncEngine::new(AnchorEngine::new(AnchorRepo::memory().unwrap(), MockWallet));
+    let server = Server::builder()
+        .tls_config(tls.server_config().unwrap())
+        .unwrap()
+        .add_service(VerifyServiceServer::new(VerifyServiceImpl::new(engine)));
+    tokio::spawn(server.serve_with_incoming(TcpListenerStream::new(listener)));
+    format!("https://localhost:{}", addr.port())
+}
+
+async fn exists(channel: Channel) -> Result<bool, tonic::Status> {
+    let request = ExistDigestRequest { digest: "00".repeat(32) };
+    Ok(VerifyServiceClient::new(channel).exist_digest(request).await?.into_inner().exists)
+}
+
+#[tokio::test]
+async fn test_tls_and_mutual_tls() {
+    let dir = tempfile::tempdir().unwrap();
+    let (server_cert, server_key) = SelfSigned::generate(&["localhost"]).unwrap().write(dir.path(), "server").unwrap();
+    let (client_cert, client_key) = SelfSigned::generate(&["ci-runner"]).unwrap().write(dir.path(), "client").unwrap();
+
+    // server-authenticated TLS
+    let url = serve(TlsConfig { cert: server_cert.clone(), key: server_key.clone(), client_ca: None }).await;
+    let tls = client_config(&server_cert, None).unwrap();
+    let channel = Endpoint::from_shared(url.clone()).unwrap().tls_config(tls).unwrap().connect().await.unwrap();
+    assert!(!exists(channel).await.unwrap());
+    // a client that doesn't trust the certificate can't connect
+    let untrusted = client_config(&client_cert, None).unwrap();
+    let endpoint = Endpoint::from_shared(url).unwrap().tls_config(untrusted).unwrap();
+    assert!(endpoint.connect().await.is_err());
+
+    // mutual TLS: only clients presenting a certificate from the CA get in
+    let url = serve(TlsConfig { cert: server_cert.clone(), key: server_key, client_ca: Some(client_cert.clone()) }).await;
+    let anonymous = client_config(&server_cert, None).unwrap();
+    let rejected = match Endpoint::from_shared(url.clone()).unwrap().tls_config(anonymous).unwrap().connect().await {
+        Ok(channel) => exists(channel).await.is_err(),
+        Err(_) => true,
+    };
+    assert!(rejected);
+    let identified = client_config(&server_cert, Some((&client_cert, &client_key))).unwrap();
+    let channel = Endpoint::from_shared(url).unwrap().tls_config(identified).unwrap().connect().await.unwrap();
+    assert!(!exists(channel).await.unwrap());
+}
@@ -1,27 +1,378 @@
 #![forbid(unsafe_code)]
 
-use rusqlite::{params, Connection, OptionalExtension};
-use validblock_types::{AnchorRecord, Digest256, VBError};
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use validblock_hasher::hash_reader;
+use validblock_types::{AnchorRecord, AnchorStatus, Digest256, VBError};
+
+/// Schema upgrades applied in order on open; `PRAGMA user_version` records
+/// how many have run. Never edit an entry, only append.
+const MIGRATIONS: &[&str] = &[
+  // 1: anchor status and PSBTs awaiting an external signature
+  "ALTER TABLE anchors ADD COLUMN status TEXT NOT NULL DEFAULT 'local';
+   CREATE TABLE psbts (
+     digest BLOB PRIMARY KEY REFERENCES anchors(digest),
+     psbt BLOB NOT NULL
+   );",
+  // 2: txids replaced by fee bumps
+  "CREATE TABLE anchor_txids (
+     digest BLOB NOT NULL REFERENCES anchors(digest),
+     seq INTEGER NOT NULL,
+     txid TEXT NOT NULL,
+     PRIMARY KEY (digest, seq)
+   );
+   CREATE INDEX anchor_txids_txid ON anchor_txids(txid);",
+  // 3: client idempotency keys and the request they were first used with
+  "CREATE TABLE idempotency_keys (
+     key TEXT PRIMARY KEY,
+     fingerprint BLOB NOT NULL,
+     digest BLOB NOT NULL REFERENCES anchors(digest),
+     ts INTEGER NOT NULL
+   );",
+  // 4: one anchor per (digest, owner) instead of per digest; the tables
+  // keyed by digest follow
+  "CREATE TABLE anchors_v4 (
+     id INTEGER PRIMARY KEY AUTOINCREMENT,
+     digest BLOB NOT NULL,
+     owner TEXT NOT NULL DEFAULT 'default',
+     ts INTEGER NOT NULL,
+     memo BLOB NULL,
+     txid TEXT NULL,
+     status TEXT NOT NULL DEFAULT 'local',
+     UNIQUE (digest, owner)
+   );
+   INSERT INTO anchors_v4 (digest, ts, memo, txid, status)
+     SELECT digest, ts, memo, txid, status FROM anchors ORDER BY ts;
+   DROP TABLE anchors;
+   ALTER TABLE anchors_v4 RENAME TO anchors;
+   CREATE INDEX anchors_txid ON anchors(txid);
+
+   CREATE TABLE psbts_v4 (
+     digest BLOB NOT NULL,
+     owner TEXT NOT NULL DEFAULT 'default',
+     psbt BLOB NOT NULL,
+     PRIMARY KEY (digest, owner),
+     FOREIGN KEY (digest, owner) REFERENCES anchors(digest, owner)
+   );
+   INSERT INTO psbts_v4 (digest, psbt) SELECT digest, psbt FROM psbts;
+   DROP TABLE psbts;
+   ALTER TABLE psbts_v4 RENAME TO psbts;
+
+   CREATE TABLE anchor_txids_v4 (
+     digest BLOB NOT NULL,
+     owner TEXT NOT NULL DEFAULT 'default',
+     seq INTEGER NOT NULL,
+     txid TEXT NOT NULL,
+     PRIMARY KEY (digest, owner, seq),
+     FOREIGN KEY (digest, owner) REFERENCES anchors(digest, owner)
+   );
+   INSERT INTO anchor_txids_v4 (digest, seq, txid) SELECT digest, seq, txid FROM anchor_txids;
+   DROP TABLE anchor_txids;
+   ALTER TABLE anchor_txids_v4 RENAME TO anchor_txids;
+   CREATE INDEX anchor_txids_txid ON anchor_txids(txid);
+
+   CREATE TABLE idempotency_keys_v4 (
+     owner TEXT NOT NULL DEFAULT 'default',
+     key TEXT NOT NULL,
+     fingerprint BLOB NOT NULL,
+     digest BLOB NOT NULL,
+     ts INTEGER NOT NULL,
+     PRIMARY KEY (owner, key)
+   );
+   INSERT INTO idempotency_keys_v4 (key, fingerprint, digest, ts)
+     SELECT key, fingerprint, digest, ts FROM idempotency_keys;
+   DROP TABLE idempotency_keys;
+   ALTER TABLE idempotency_keys_v4 RENAME TO idempotency_keys;",
+  // 5: outbound webhook deliveries waiting for their (next) attempt
+  "CREATE TABLE webhook_deliveries (
+     id INTEGER PRIMARY KEY AUTOINCREMENT,
+     url TEXT NOT NULL,
+     body BLOB NOT NULL,
+     attempts INTEGER NOT NULL DEFAULT 0,
+     next_at INTEGER NOT NULL,
+     last_error TEXT NULL
+   );
+   CREATE INDEX webhook_deliveries_next_at ON webhook_deliveries(next_at);",
+  // 6: tenants, whose ids are the owners of their anchors, and the API keys
+  // issued to them
+  "CREATE TABLE tenants (
+     id TEXT PRIMARY KEY,
+     name TEXT NOT NULL,
+     disabled INTEGER NOT NULL DEFAULT 0,
+     fee_rate INTEGER NULL,
+     created_at INTEGER NOT NULL
+   );
+   CREATE TABLE tenant_keys (
+     digest BLOB PRIMARY KEY,
+     tenant TEXT NOT NULL REFERENCES tenants(id),
+     scopes TEXT NOT NULL,
+     created_at INTEGER NOT NULL
+   );",
+  // 7: API keys of any owner, each with a role; tenant keys granted the
+  // anchor scope become anchorers, the rest viewers
+  "CREATE TABLE api_keys (
+     digest BLOB PRIMARY KEY,
+     owner TEXT NOT NULL,
+     role TEXT NOT NULL,
+     created_at INTEGER NOT NULL
+   );
+   INSERT INTO api_keys (digest, owner, role, created_at)
+     SELECT digest, tenant, CASE WHEN instr(scopes, 'anchor') > 0 THEN 'anchorer' ELSE 'viewer' END, created_at
+     FROM tenant_keys;
+   DROP TABLE tenant_keys;",
+  // 8: audit trail, each entry hash-chained to the one before
+  "CREATE TABLE audit_log (
+     seq INTEGER PRIMARY KEY,
+     ts INTEGER NOT NULL,
+     actor TEXT NOT NULL,
+     credential TEXT NOT NULL,
+     source TEXT NOT NULL,
+     action TEXT NOT NULL,
+     target TEXT NOT NULL,
+     outcome TEXT NOT NULL,
+     hash BLOB NOT NULL
+   );
+   CREATE INDEX audit_log_ts ON audit_log(ts);",
+  // 9: on-chain anchors between the wallet being called and the record being
+  // written, so a crash in between can be reconciled on the next start
+  "CREATE TABLE anchor_intents (
+     digest BLOB NOT NULL,
+     owner TEXT NOT NULL,
+     ts INTEGER NOT NULL,
+     memo BLOB NULL,
+     payload BLOB NOT NULL,
+     PRIMARY KEY (digest, owner)
+   );",
+];
+
+const RECORD_COLUMNS: &str = "digest, ts, memo, txid, status,
+  (SELECT group_concat(txid) FROM (SELECT txid FROM anchor_txids h
+    WHERE h.digest = anchors.digest AND h.owner = anchors.owner ORDER BY seq)),
+  owner";
+
+/// Earliest first; insertion order breaks ties
+const RECORD_ORDER: &str = "ORDER BY ts, id";
+
+fn record_from_row(row: &Row<'_>) -> rusqlite::Result<AnchorRecord> {
+  let status: String = row.get(4)?;
+  Ok(AnchorRecord {
+    digest: Digest256(row.get(0)?),
+    owner: row.get(6)?,
+    ts: row.get(1)?,
+    memo: row.get(2)?,
+    txid: row.get(3)?,
+    status: status.parse().map_err(|e: VBError| {
+      rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+    })?,
+    txid_history: row
+      .get::<_, Option<String>>(5)?
+      .map(|s| s.split(',').map(String::from).collect())
+      .unwrap_or_default(),
+  })
+}
+
+/// A webhook POST waiting in the delivery queue
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Delivery {
+  pub id: i64,
+  pub url: String,
+  pub body: Vec<u8>,
+  /// Failed attempts so far
+  pub attempts: u32,
+}
+
+/// An on-chain anchor whose transaction may have been broadcast but whose
+/// record isn't written yet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnchorIntent {
+  pub digest: Digest256,
+  pub owner: String,
+  pub ts: i64,
+  pub memo: Option<Vec<u8>>,
+  /// OP_RETURN payload handed to the wallet
+  pub payload: Vec<u8>,
+}
+
+/// A department or customer sharing the server; its id is the owner of its
+/// anchors
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tenant {
+  pub id: String,
+  pub name: String,
+  /// Its keys are refused and it may not anchor
+  pub disabled: bool,
+  /// Fee rate of its anchor transactions, sat/vB; the wallet's own when unset
+  pub fee_rate: Option<u16>,
+  pub created_at: i64,
+}
+
+fn tenant_from_row(row: &Row<'_>) -> rusqlite::Result<Tenant> {
+  Ok(Tenant { id: row.get(0)?, name: row.get(1)?, disabled: row.get(2)?, fee_rate: row.get(3)?, created_at: row.get(4)? })
+}
+
+/// An issued API key, stored by the digest of its secret
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyRecord {
+  pub digest: Digest256,
+  /// Owner the key acts for: a tenant, or the default owner
+  pub owner: String,
+  /// Role name: viewer, anchorer or admin
+  pub role: String,
+  pub created_at: i64,
+}
+
+/// One entry of the audit trail
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+  /// Position in the trail, from 1; assigned on append
+  pub seq: i64,
+  pub ts: i64,
+  /// Owner the caller acted as; empty when the credentials named nobody
+  pub actor: String,
+  /// `token`, `key:<digest prefix>`, or empty
+  pub credential: String,
+  /// Peer address of the call
+  pub source: String,
+  pub action: String,
+  /// Digest, tenant or key acted on, when known
+  pub target: String,
+  /// `OK` or the gRPC status code name
+  pub outcome: String,
+  /// Chain hash; assigned on append, see [`audit_hash`]
+  pub hash: Digest256,
+}
+
+/// Which audit entries to return
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditFilter {
+  pub actor: Option<String>,
+  pub action: Option<String>,
+  /// Inclusive lower bound on `ts`
+  pub since: Option<i64>,
+  /// Exclusive upper bound on `ts`
+  pub until: Option<i64>,
+  /// All matching entries when unset
+  pub limit: Option<u32>,
+  pub offset: u32,
+}
+
+/// Hash chaining `entry` to the entry before it, whose hash is `prev` (all
+/// zeroes for the first): SHA-256 over `prev`, then `seq` and `ts` as 8-byte
+/// little-endian integers, then each text field, in declaration order, as its
+/// 8-byte little-endian length followed by its UTF-8 bytes
+pub fn audit_hash(prev: &Digest256, entry: &AuditEntry) -> Digest256 {
+  let mut bytes = prev.0.to_vec();
+  bytes.extend_from_slice(&entry.seq.to_le_bytes());
+  bytes.extend_from_slice(&entry.ts.to_le_bytes());
+  for field in [&entry.actor, &entry.credential, &entry.source, &entry.action, &entry.target, &entry.outcome] {
+    bytes.extend_from_slice(&(field.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(field.as_bytes());
+  }
+  hash_reader(bytes.as_slice()).expect("hashing a byte slice cannot fail")
+}
+
+const AUDIT_COLUMNS: &str = "seq, ts, actor, credential, source, action, target, outcome, hash";
+
+fn audit_from_row(row: &Row<'_>) -> rusqlite::Result<AuditEntry> {
+  Ok(AuditEntry {
+    seq: row.get(0)?,
+    ts: row.get(1)?,
+    actor: row.get(2)?,
+    credential: row.get(3)?,
+    source: row.get(4)?,
+    action: row.get(5)?,
+    target: row.get(6)?,
+    outcome: row.get(7)?,
+    hash: Digest256(row.get(8)?),
+  })
+}
+
+/// Insert `rec` and its txid history over `conn`
+fn insert_record(conn: &Connection, rec: &AnchorRecord) -> Result<(), VBError> {
+  let res = conn.execute(
+    "INSERT INTO anchors (digest, owner, ts, memo, txid, status) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    params![&rec.digest.0, &rec.owner, rec.ts, &rec.memo, &rec.txid, rec.status.as_str()],
+  );
+  match res {
+    Ok(_) => {
+      for (seq, txid) in rec.txid_history.iter().enumerate() {
+        conn.execute(
+          "INSERT INTO anchor_txids (digest, owner, seq, txid) VALUES (?1, ?2, ?3, ?4)",
+          params![&rec.digest.0, &rec.owner, seq as i64, txid],
+        ).map_err(|e| VBError::Db(e.to_string()))?;
+      }
+      Ok(())
+    }
+    Err(rusqlite::Error::SqliteFailure(e, _)) if e.extended_code == 2067 || e.extended_code == 1555 => {
+        // 2067: SQLITE_CONSTRAINT_UNIQUE, 1555: SQLITE_CONSTRAINT_PRIMARYKEY
+        Err(VBError::DbDuplicate)
+    }
+    Err(e) => Err(VBError::Db(e.to_string())),
+  }
+}
+
+fn insert_intent(conn: &Connection, intent: &AnchorIntent) -> Result<(), VBError> {
+  let res = conn.execute(
+    "INSERT INTO anchor_intents (digest, owner, ts, memo, payload) VALUES (?1, ?2, ?3, ?4, ?5)",
+    params![&intent.digest.0, &intent.owner, intent.ts, &intent.memo, &intent.payload],
+  );
+  match res {
+    Ok(_) => Ok(()),
+    Err(rusqlite::Error::SqliteFailure(e, _)) if e.extended_code == 1555 => Err(VBError::DbDuplicate),
+    Err(e) => Err(VBError::Db(e.to_string())),
+  }
+}
+
+fn intent_exists(conn: &Connection, digest: &Digest256, owner: &str) -> Result<bool, VBError> {
+  conn
+    .query_row(
+      "SELECT EXISTS (SELECT 1 FROM anchor_intents WHERE digest = ?1 AND owner = ?2)",
+      params![&digest.0, owner],
+      |row| row.get(0),
+    )
+    .map_err(|e| VBError::Db(e.to_string()))
+}
+
+fn on_chain_count(conn: &Connection, owner: &str, since: i64) -> Result<u32, VBError> {
+  conn
+    .query_row(
+      "SELECT (SELECT COUNT(*) FROM anchors WHERE owner = ?1 AND ts >= ?2 AND txid IS NOT NULL)
+        + (SELECT COUNT(*) FROM anchor_intents WHERE owner = ?1 AND ts >= ?2)",
+      params![owner, since],
+      |row| row.get(0),
+    )
+    .map_err(|e| VBError::Db(e.to_string()))
+}
+
+/// The anchors database. Safe to share between threads: each call holds the
+/// connection only for its own statements
 #[derive(Debug)]
 pub struct AnchorRepo {
-  conn: Connection,
+  conn: Mutex<Connection>,
 }
 
 impl AnchorRepo {
   /// Open or create anchors.db in cwd
   pub fn new(path: Option<&str>) -> Result<Self, VBError> {
     let db_path = path.unwrap_or("anchors.db");
+    tracing::debug!(path = db_path, "Opening anchors database");
     let conn = Connection::open(db_path).map_err(|e| VBError::Db(e.to_string()))?;
     Self::init_schema(&conn)?;
-    Ok(Self { conn })
+    Ok(Self { conn: Mutex::new(conn) })
   }
 
   /// For in-memory DB (for tests)
   pub fn memory() -> Result<Self, VBError> {
     let conn = Connection::open_in_memory().map_err(|e| VBError::Db(e.to_string()))?;
     Self::init_schema(&conn)?;
-    Ok(Self { conn })
+    Ok(Self { conn: Mutex::new(conn) })
+  }
+
+  /// The connection, for one call's statements. A panic mid-call leaves
+  /// nothing half-done that SQLite wouldn't roll back, so poisoning is ignored
+  fn conn(&self) -> MutexGuard<'_, Connection> {
+    self.conn.lock().unwrap_or_else(PoisonError::into_inner)
   }
 
   fn init_schema(conn: &Connection) -> Result<(), VBError> {
@@ -34,39 +385,78 @@ impl AnchorRepo {
       )",
       [],
     ).map_err(|e| VBError::Db(e.to_string()))?;
+    Self::migrate(conn)
+  }
+
+  fn migrate(conn: &Connection) -> Result<(), VBError> {
+    let version: usize = conn
+      .query_row("PRAGMA user_version", [], |row| row.get(0))
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+      conn.execute_batch(&format!("BEGIN; {} PRAGMA user_version = {}; COMMIT;", sql, i + 1))
+        .map_err(|e| VBError::Db(e.to_string()))?;
+      tracing::info!(version = i + 1, "Migrated anchors database");
+    }
     Ok(())
   }
 
-  /// Upsert anchor record
+  /// Insert anchor record; `DbDuplicate` if its owner already anchored the
+  /// digest or has an on-chain anchor of it under way
   pub fn insert(&self, rec: &AnchorRecord) -> Result<(), VBError> {
-    let res = self.conn.execute(
-      "INSERT INTO anchors (digest, ts, memo, txid) VALUES (?1, ?2, ?3, ?4)",
-      params![&rec.digest.0, rec.ts, &rec.memo, &rec.txid],
-    );
-    match res {
-      Ok(_) => Ok(()),
-      Err(rusqlite::Error::SqliteFailure(e, _)) if e.extended_code == 2067 || e.extended_code == 1555 => {
-          // 2067: SQLITE_CONSTRAINT_UNIQUE, 1555: SQLITE_CONSTRAINT_PRIMARYKEY
-          Err(VBError::DbDuplicate)
-      }
-      Err(e) => Err(VBError::Db(e.to_string())),
+    let conn = self.conn();
+    let tx = conn.unchecked_transaction().map_err(|e| VBError::Db(e.to_string()))?;
+    if intent_exists(&tx, &rec.digest, &rec.owner)? {
+      return Err(VBError::DbDuplicate);
     }
+    insert_record(&tx, rec)?;
+    tx.commit().map_err(|e| VBError::Db(e.to_string()))
   }
 
-  /// Get anchor by digest
+  /// Earliest anchor of `digest`, whoever made it
   pub fn get(&self, digest: &Digest256) -> Result<Option<AnchorRecord>, VBError> {
-    self.conn
+    self.conn()
       .query_row(
-        "SELECT digest, ts, memo, txid FROM anchors WHERE digest = ?1",
+        &format!("SELECT {} FROM anchors WHERE digest = ?1 {} LIMIT 1", RECORD_COLUMNS, RECORD_ORDER),
         params![&digest.0],
-        |row| {
-            Ok(AnchorRecord {
-                digest: Digest256(row.get(0)?),
-                ts: row.get(1)?,
-                memo: row.get(2)?,
-                txid: row.get(3)?,
-            })
-        },
+        record_from_row,
+      )
+      .optional()
+      .map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// `owner`'s anchor of `digest`
+  pub fn get_owned(&self, digest: &Digest256, owner: &str) -> Result<Option<AnchorRecord>, VBError> {
+    self.conn()
+      .query_row(
+        &format!("SELECT {} FROM anchors WHERE digest = ?1 AND owner = ?2", RECORD_COLUMNS),
+        params![&digest.0, owner],
+        record_from_row,
+      )
+      .optional()
+      .map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Every anchor of `digest`, earliest first
+  pub fn events(&self, digest: &Digest256) -> Result<Vec<AnchorRecord>, VBError> {
+    let conn = self.conn();
+    let mut stmt = conn
+      .prepare(&format!("SELECT {} FROM anchors WHERE digest = ?1 {}", RECORD_COLUMNS, RECORD_ORDER))
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt.query_map(params![&digest.0], record_from_row).map_err(|e| VBError::Db(e.to_string()))?;
+    rows.collect::<Result<_, _>>().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Get anchor by transaction id, current or replaced
+  pub fn get_by_txid(&self, txid: &str) -> Result<Option<AnchorRecord>, VBError> {
+    self.conn()
+      .query_row(
+        &format!(
+          "SELECT {} FROM anchors WHERE txid = ?1
+           OR (digest, owner) IN (SELECT digest, owner FROM anchor_txids WHERE txid = ?1)",
+          RECORD_COLUMNS
+        ),
+        params![txid],
+        record_from_row,
       )
       .optional()
       .map_err(|e| VBError::Db(e.to_string()))
@@ -74,15 +464,9 @@ impl AnchorRepo {
 
   /// Get all anchors
   pub fn all(&self) -> Result<Vec<AnchorRecord>, VBError> {
-    let mut stmt = self.conn.prepare("SELECT digest, ts, memo, txid FROM anchors").map_err(|e| VBError::Db(e.to_string()))?;
-    let rows = stmt.query_map([], |row| {
-      Ok(AnchorRecord {
-        digest: Digest256(row.get(0)?),
-        ts: row.get(1)?,
-        memo: row.get(2)?,
-        txid: row.get(3)?,
-      })
-    }).map_err(|e| VBError::Db(e.to_string()))?;
+    let conn = self.conn();
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM anchors {}", RECORD_COLUMNS, RECORD_ORDER)).map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt.query_map([], record_from_row).map_err(|e| VBError::Db(e.to_string()))?;
     let mut out = Vec::new();
     for r in rows {
       out.push(r.map_err(|e| VBError::Db(e.to_string()))?);
@@ -90,15 +474,454 @@ impl AnchorRepo {
     Ok(out)
   }
 
-  /// WAL checkpoint (stub ok)
-  pub fn checkpoint(&self) -> Result<(), VBError> {
-    self.conn.execute("PRAGMA wal_checkpoint(TRUNCATE)", []).map_err(|e| VBError::Db(e.to_string()))?;
+  /// One page of anchors, earliest first, optionally only `owner`'s
+  pub fn list(&self, owner: Option<&str>, limit: u32, offset: u32) -> Result<Vec<AnchorRecord>, VBError> {
+    let conn = self.conn();
+    let mut stmt = conn
+      .prepare(&format!(
+        "SELECT {} FROM anchors WHERE ?1 IS NULL OR owner = ?1 {} LIMIT ?2 OFFSET ?3",
+        RECORD_COLUMNS, RECORD_ORDER
+      ))
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt
+      .query_map(params![owner, limit, offset], record_from_row)
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    rows.collect::<Result<_, _>>().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Move `owner`'s anchor of `digest` to `status`
+  pub fn set_status(&self, digest: &Digest256, owner: &str, status: AnchorStatus) -> Result<(), VBError> {
+    self.conn().execute(
+      "UPDATE anchors SET status = ?3 WHERE digest = ?1 AND owner = ?2",
+      params![&digest.0, owner, status.as_str()],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
     Ok(())
   }
 
+  /// How many of `owner`'s anchors since `since` carry a transaction or are
+  /// on their way on-chain
+  pub fn on_chain_count(&self, owner: &str, since: i64) -> Result<u32, VBError> {
+    on_chain_count(&self.conn(), owner, since)
+  }
+
+  /// Anchors in `status`, earliest first
+  pub fn with_status(&self, status: AnchorStatus) -> Result<Vec<AnchorRecord>, VBError> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM anchors WHERE status = ?1 {}", RECORD_COLUMNS, RECORD_ORDER))
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt.query_map(params![status.as_str()], record_from_row).map_err(|e| VBError::Db(e.to_string()))?;
+    rows.collect::<Result<_, _>>().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Make `txid` the current transaction of `owner`'s anchor of `digest`,
+  /// moving the previous one into its history
+  pub fn replace_txid(&self, digest: &Digest256, owner: &str, txid: &str, status: AnchorStatus) -> Result<(), VBError> {
+    let conn = self.conn();
+    let tx = conn.unchecked_transaction().map_err(|e| VBError::Db(e.to_string()))?;
+    let old: Option<String> = tx
+      .query_row(
+        "SELECT txid FROM anchors WHERE digest = ?1 AND owner = ?2",
+        params![&digest.0, owner],
+        |row| row.get(0),
+      )
+      .optional()
+      .map_err(|e| VBError::Db(e.to_string()))?
+      .ok_or_else(|| VBError::Db("No such anchor".into()))?;
+    tx.execute(
+      "DELETE FROM anchor_txids WHERE digest = ?1 AND owner = ?2 AND txid = ?3",
+      params![&digest.0, owner, txid],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    if let Some(old) = old.filter(|old| old != txid) {
+      tx.execute(
+        "INSERT INTO anchor_txids (digest, owner, seq, txid)
+         SELECT ?1, ?2, COALESCE(MAX(seq) + 1, 0), ?3 FROM anchor_txids WHERE digest = ?1 AND owner = ?2",
+        params![&digest.0, owner, old],
+      ).map_err(|e| VBError::Db(e.to_string()))?;
+    }
+    tx.execute(
+      "UPDATE anchors SET txid = ?3, status = ?4 WHERE digest = ?1 AND owner = ?2",
+      params![&digest.0, owner, txid, status.as_str()],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    tx.commit().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Store the serialized PSBT awaiting signature for `owner`'s anchor of `digest`
+  pub fn put_psbt(&self, digest: &Digest256, owner: &str, psbt: &[u8]) -> Result<(), VBError> {
+    self.conn().execute(
+      "INSERT OR REPLACE INTO psbts (digest, owner, psbt) VALUES (?1, ?2, ?3)",
+      params![&digest.0, owner, psbt],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(())
+  }
+
+  /// Serialized PSBT awaiting signature for `owner`'s anchor of `digest`
+  pub fn get_psbt(&self, digest: &Digest256, owner: &str) -> Result<Option<Vec<u8>>, VBError> {
+    self.conn()
+      .query_row(
+        "SELECT psbt FROM psbts WHERE digest = ?1 AND owner = ?2",
+        params![&digest.0, owner],
+        |row| row.get(0),
+      )
+      .optional()
+      .map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  pub fn delete_psbt(&self, digest: &Digest256, owner: &str) -> Result<(), VBError> {
+    self.conn().execute("DELETE FROM psbts WHERE digest = ?1 AND owner = ?2", params![&digest.0, owner])
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(())
+  }
+
+  /// Record that `intent` is about to go to the wallet; `DbDuplicate` if the
+  /// same anchor is already in flight
+  pub fn insert_intent(&self, intent: &AnchorIntent) -> Result<(), VBError> {
+    insert_intent(&self.conn(), intent)
+  }
+
+  /// Claim an on-chain anchor before it goes to the wallet: insert `intent`
+  /// unless its owner already anchored the digest or has it under way
+  /// (`DbDuplicate`), or, with `quota` as `(limit, since)`, already made
+  /// `limit` on-chain anchors since `since` (`ResourceExhausted`). Checks and
+  /// insert are one transaction, so concurrent claims can't both pass
+  pub fn reserve_intent(&self, intent: &AnchorIntent, quota: Option<(u32, i64)>) -> Result<(), VBError> {
+    let conn = self.conn();
+    let tx = conn.unchecked_transaction().map_err(|e| VBError::Db(e.to_string()))?;
+    let anchored: bool = tx
+      .query_row(
+        "SELECT EXISTS (SELECT 1 FROM anchors WHERE digest = ?1 AND owner = ?2)",
+        params![&intent.digest.0, &intent.owner],
+        |row| row.get(0),
+      )
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    if anchored || intent_exists(&tx, &intent.digest, &intent.owner)? {
+      return Err(VBError::DbDuplicate);
+    }
+    if let Some((limit, since)) = quota {
+      if on_chain_count(&tx, &intent.owner, since)? >= limit {
+        return Err(VBError::ResourceExhausted(format!("Daily quota of {} on-chain anchors used up", limit)));
+      }
+    }
+    insert_intent(&tx, intent)?;
+    tx.commit().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Intents left unfinished, oldest first
+  pub fn intents(&self) -> Result<Vec<AnchorIntent>, VBError> {
+    let conn = self.conn();
+    let mut stmt = conn
+      .prepare("SELECT digest, owner, ts, memo, payload FROM anchor_intents ORDER BY ts")
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt
+      .query_map([], |row| {
+        Ok(AnchorIntent {
+          digest: Digest256(row.get(0)?),
+          owner: row.get(1)?,
+          ts: row.get(2)?,
+          memo: row.get(3)?,
+          payload: row.get(4)?,
+        })
+      })
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    rows.collect::<Result<_, _>>().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Forget an intent whose transaction never left the wallet
+  pub fn delete_intent(&self, digest: &Digest256, owner: &str) -> Result<(), VBError> {
+    self.conn().execute("DELETE FROM anchor_intents WHERE digest = ?1 AND owner = ?2", params![&digest.0, owner])
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(())
+  }
+
+  /// Insert `rec`, with the PSBT awaiting its signature if any, and drop its
+  /// intent, all or nothing. The transaction is paid for by now, so should the
+  /// owner's anchor of the digest exist after all, it is recorded there
+  pub fn finalize_intent(&self, rec: &AnchorRecord, psbt: Option<&[u8]>) -> Result<(), VBError> {
+    let conn = self.conn();
+    let tx = conn.unchecked_transaction().map_err(|e| VBError::Db(e.to_string()))?;
+    match insert_record(&tx, rec) {
+      Err(VBError::DbDuplicate) => {
+        tx.execute(
+          "UPDATE anchors SET txid = ?3, status = ?4 WHERE digest = ?1 AND owner = ?2",
+          params![&rec.digest.0, &rec.owner, &rec.txid, rec.status.as_str()],
+        ).map_err(|e| VBError::Db(e.to_string()))?;
+      }
+      result => result?,
+    }
+    if let Some(psbt) = psbt {
+      tx.execute(
+        "INSERT OR REPLACE INTO psbts (digest, owner, psbt) VALUES (?1, ?2, ?3)",
+        params![&rec.digest.0, &rec.owner, psbt],
+      ).map_err(|e| VBError::Db(e.to_string()))?;
+    }
+    tx.execute("DELETE FROM anchor_intents WHERE digest = ?1 AND owner = ?2", params![&rec.digest.0, &rec.owner])
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    tx.commit().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Remember that `owner`'s `key` produced the anchor for `digest` from a
+  /// request fingerprinted as `fingerprint`. A key is bound once; later calls
+  /// are ignored
+  pub fn put_idempotency_key(
+    &self,
+    owner: &str,
+    key: &str,
+    fingerprint: &[u8],
+    digest: &Digest256,
+    ts: i64,
+  ) -> Result<(), VBError> {
+    self.conn().execute(
+      "INSERT OR IGNORE INTO idempotency_keys (owner, key, fingerprint, digest, ts) VALUES (?1, ?2, ?3, ?4, ?5)",
+      params![owner, key, fingerprint, &digest.0, ts],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(())
+  }
+
+  /// Fingerprint and digest bound to `owner`'s `key`
+  pub fn get_idempotency_key(&self, owner: &str, key: &str) -> Result<Option<(Vec<u8>, Digest256)>, VBError> {
+    self.conn()
+      .query_row(
+        "SELECT fingerprint, digest FROM idempotency_keys WHERE owner = ?1 AND key = ?2",
+        params![owner, key],
+        |row| Ok((row.get(0)?, Digest256(row.get(1)?))),
+      )
+      .optional()
+      .map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Queue `body` for POSTing to `url`, first attempt at `at`
+  pub fn enqueue_delivery(&self, url: &str, body: &[u8], at: i64) -> Result<i64, VBError> {
+    let conn = self.conn();
+    conn.execute(
+      "INSERT INTO webhook_deliveries (url, body, next_at) VALUES (?1, ?2, ?3)",
+      params![url, body, at],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(conn.last_insert_rowid())
+  }
+
+  /// Up to `limit` deliveries whose next attempt is due at `now`, oldest first
+  pub fn due_deliveries(&self, now: i64, limit: u32) -> Result<Vec<Delivery>, VBError> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare(
+      "SELECT id, url, body, attempts FROM webhook_deliveries WHERE next_at <= ?1 ORDER BY next_at, id LIMIT ?2",
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt
+      .query_map(params![now, limit], |row| {
+        Ok(Delivery { id: row.get(0)?, url: row.get(1)?, body: row.get(2)?, attempts: row.get(3)? })
+      })
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    rows.collect::<Result<_, _>>().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Record a failed attempt and when to try again
+  pub fn retry_delivery(&self, id: i64, next_at: i64, error: &str) -> Result<(), VBError> {
+    self.conn().execute(
+      "UPDATE webhook_deliveries SET attempts = attempts + 1, next_at = ?2, last_error = ?3 WHERE id = ?1",
+      params![id, next_at, error],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(())
+  }
+
+  /// Drop a delivery, done or given up on
+  pub fn delete_delivery(&self, id: i64) -> Result<(), VBError> {
+    self.conn().execute("DELETE FROM webhook_deliveries WHERE id = ?1", params![id])
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(())
+  }
+
+  /// Register `tenant`; `DbDuplicate` if its id is taken
+  pub fn insert_tenant(&self, tenant: &Tenant) -> Result<(), VBError> {
+    let res = self.conn().execute(
+      "INSERT INTO tenants (id, name, disabled, fee_rate, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+      params![&tenant.id, &tenant.name, tenant.disabled, tenant.fee_rate, tenant.created_at],
+    );
+    match res {
+      Ok(_) => Ok(()),
+      Err(rusqlite::Error::SqliteFailure(e, _)) if e.extended_code == 1555 => Err(VBError::DbDuplicate),
+      Err(e) => Err(VBError::Db(e.to_string())),
+    }
+  }
+
+  pub fn get_tenant(&self, id: &str) -> Result<Option<Tenant>, VBError> {
+    self.conn()
+      .query_row(
+        "SELECT id, name, disabled, fee_rate, created_at FROM tenants WHERE id = ?1",
+        params![id],
+        tenant_from_row,
+      )
+      .optional()
+      .map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Every tenant, by id
+  pub fn tenants(&self) -> Result<Vec<Tenant>, VBError> {
+    let conn = self.conn();
+    let mut stmt = conn
+      .prepare("SELECT id, name, disabled, fee_rate, created_at FROM tenants ORDER BY id")
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt.query_map([], tenant_from_row).map_err(|e| VBError::Db(e.to_string()))?;
+    rows.collect::<Result<_, _>>().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Disable or re-enable tenant `id`; false if there is no such tenant
+  pub fn set_tenant_disabled(&self, id: &str, disabled: bool) -> Result<bool, VBError> {
+    let changed = self.conn().execute(
+      "UPDATE tenants SET disabled = ?2 WHERE id = ?1",
+      params![id, disabled],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(changed > 0)
+  }
+
+  /// Store an issued key
+  pub fn insert_key(&self, key: &KeyRecord) -> Result<(), VBError> {
+    self.conn().execute(
+      "INSERT INTO api_keys (digest, owner, role, created_at) VALUES (?1, ?2, ?3, ?4)",
+      params![&key.digest.0, &key.owner, &key.role, key.created_at],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(())
+  }
+
+  /// Every issued key, oldest first
+  pub fn keys(&self) -> Result<Vec<KeyRecord>, VBError> {
+    let conn = self.conn();
+    let mut stmt = conn
+      .prepare("SELECT digest, owner, role, created_at FROM api_keys ORDER BY created_at, owner")
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt
+      .query_map([], |row| {
+        Ok(KeyRecord { digest: Digest256(row.get(0)?), owner: row.get(1)?, role: row.get(2)?, created_at: row.get(3)? })
+      })
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    rows.collect::<Result<_, _>>().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Revoke a key; false if there is no such key
+  pub fn delete_key(&self, digest: &Digest256) -> Result<bool, VBError> {
+    let changed = self.conn()
+      .execute("DELETE FROM api_keys WHERE digest = ?1", params![&digest.0])
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(changed > 0)
+  }
+
+  /// Append `entry` to the audit trail, chained to the latest entry; its
+  /// `seq` and `hash` are ignored and returned assigned
+  pub fn append_audit(&self, entry: &AuditEntry) -> Result<AuditEntry, VBError> {
+    let conn = self.conn();
+    let tx = conn.unchecked_transaction().map_err(|e| VBError::Db(e.to_string()))?;
+    let last: Option<(i64, Vec<u8>)> = tx
+      .query_row("SELECT seq, hash FROM audit_log ORDER BY seq DESC LIMIT 1", [], |row| Ok((row.get(0)?, row.get(1)?)))
+      .optional()
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    let (seq, prev) = match last {
+      Some((seq, hash)) => (seq + 1, Digest256(hash.try_into().map_err(|_| VBError::Db("Malformed audit hash".into()))?)),
+      None => (1, Digest256([0; 32])),
+    };
+    let mut entry = AuditEntry { seq, ..entry.clone() };
+    entry.hash = audit_hash(&prev, &entry);
+    tx.execute(
+      &format!("INSERT INTO audit_log ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)", AUDIT_COLUMNS),
+      params![
+        entry.seq, entry.ts, &entry.actor, &entry.credential, &entry.source, &entry.action, &entry.target,
+        &entry.outcome, &entry.hash.0
+      ],
+    ).map_err(|e| VBError::Db(e.to_string()))?;
+    tx.commit().map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(entry)
+  }
+
+  /// Audit entries matching `filter`, oldest first
+  pub fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, VBError> {
+    let mut conditions = vec![];
+    let mut values: Vec<rusqlite::types::Value> = vec![];
+    if let Some(actor) = &filter.actor {
+      values.push(actor.clone().into());
+      conditions.push(format!("actor = ?{}", values.len()));
+    }
+    if let Some(action) = &filter.action {
+      values.push(action.clone().into());
+      conditions.push(format!("action = ?{}", values.len()));
+    }
+    if let Some(since) = filter.since {
+      values.push(since.into());
+      conditions.push(format!("ts >= ?{}", values.len()));
+    }
+    if let Some(until) = filter.until {
+      values.push(until.into());
+      conditions.push(format!("ts < ?{}", values.len()));
+    }
+    let mut sql = format!("SELECT {} FROM audit_log", AUDIT_COLUMNS);
+    if !conditions.is_empty() {
+      sql += &format!(" WHERE {}", conditions.join(" AND "));
+    }
+    sql += &format!(" ORDER BY seq LIMIT {} OFFSET {}", filter.limit.map_or(-1, i64::from), filter.offset);
+    let conn = self.conn();
+    let mut stmt = conn.prepare(&sql).map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt.query_map(params_from_iter(values), audit_from_row).map_err(|e| VBError::Db(e.to_string()))?;
+    rows.collect::<Result<_, _>>().map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Walk the audit trail recomputing its hash chain. Returns the number of
+  /// entries and the seq of the first entry that was altered, inserted or
+  /// removed, if any
+  pub fn verify_audit(&self) -> Result<(u64, Option<i64>), VBError> {
+    let conn = self.conn();
+    let mut stmt = conn
+      .prepare(&format!("SELECT {} FROM audit_log ORDER BY seq", AUDIT_COLUMNS))
+      .map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt.query_map([], audit_from_row).map_err(|e| VBError::Db(e.to_string()))?;
+    let (mut count, mut prev) = (0u64, Digest256([0; 32]));
+    for entry in rows {
+      let entry = entry.map_err(|e| VBError::Db(e.to_string()))?;
+      count += 1;
+      if entry.seq != count as i64 || audit_hash(&prev, &entry) != entry.hash {
+        return Ok((count, Some(entry.seq)));
+      }
+      prev = entry.hash;
+    }
+    Ok((count, None))
+  }
+
+  /// WAL checkpoint, truncating the log; a no-op outside WAL mode
+  pub fn checkpoint(&self) -> Result<(), VBError> {
+    self.conn()
+      .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+      .map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Rebuild the database file, returning free pages to the filesystem
+  pub fn vacuum(&self) -> Result<(), VBError> {
+    self.conn().execute_batch("VACUUM").map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Problems `PRAGMA integrity_check` finds; empty when the database is sound
+  pub fn integrity_check(&self) -> Result<Vec<String>, VBError> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare("PRAGMA integrity_check").map_err(|e| VBError::Db(e.to_string()))?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0)).map_err(|e| VBError::Db(e.to_string()))?;
+    let problems = rows.collect::<Result<Vec<_>, _>>().map_err(|e| VBError::Db(e.to_string()))?;
+    Ok(problems.into_iter().filter(|p| p != "ok").collect())
+  }
+
+  /// Fail unless the database answers a query
+  pub fn ping(&self) -> Result<(), VBError> {
+    self.conn().query_row("SELECT 1", [], |_| Ok(())).map_err(|e| VBError::Db(e.to_string()))
+  }
+
+  /// Bytes the database takes up
+  pub fn size_bytes(&self) -> Result<u64, VBError> {
+    self
+      .conn()
+      .query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get(0),
+      )
+      .map_err(|e| VBError::Db(e.to_string()))
+  }
+
   /// Check if a digest exists in the DB
   pub fn exists_digest(&self, digest: &Digest256) -> Result<bool, VBError> {
-    let mut stmt = self.conn.prepare("SELECT 1 FROM anchors WHERE digest = ?1 LIMIT 1")
+    let conn = self.conn();
+    let mut stmt = conn.prepare("SELECT 1 FROM anchors WHERE digest = ?1 LIMIT 1")
         .map_err(|e| VBError::Db(e.to_string()))?;
 
     let mut rows = stmt.query(params![&digest.0])
@@ -115,20 +938,24 @@ impl AnchorRepo {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use validblock_types::Digest256;
+  use validblock_types::{Digest256, DEFAULT_OWNER};
 
   #[test]
   fn test_insert_get() {
     let repo = AnchorRepo::memory().unwrap();
     let rec = AnchorRecord {
       digest: Digest256([1; 32]),
+      owner: DEFAULT_OWNER.to_string(),
       ts: 42,
       memo: Some(vec![1, 2, 3]),
       txid: Some("txid123".to_string()),
+      status: AnchorStatus::Broadcast,
+      txid_history: vec![],
     };
     repo.insert(&rec).unwrap();
     let got = repo.get(&rec.digest).unwrap().unwrap();
     assert_eq!(got, rec);
+    assert_eq!(repo.get_by_txid("txid123").unwrap().unwrap(), rec);
   }
 
   #[test]
@@ -136,9 +963,12 @@ mod tests {
     let repo = AnchorRepo::memory().unwrap();
     let rec = AnchorRecord {
       digest: Digest256([2; 32]),
+      owner: DEFAULT_OWNER.to_string(),
       ts: 99,
       memo: None,
       txid: None,
+      status: AnchorStatus::Local,
+      txid_history: vec![],
     };
     repo.insert(&rec).unwrap();
     let err = repo.insert(&rec).unwrap_err();
@@ -147,4 +977,324 @@ mod tests {
       _ => panic!("Expected DbDuplicate error"),
     }
   }
-} 
\ No newline at end of file
+
+  #[test]
+  fn test_anchor_events_per_owner() {
+    let repo = AnchorRepo::memory().unwrap();
+    let rec = |owner: &str, ts| AnchorRecord {
+      digest: Digest256([8; 32]),
+      owner: owner.to_string(),
+      ts,
+      memo: Some(owner.as_bytes().to_vec()),
+      txid: None,
+      status: AnchorStatus::Local,
+      txid_history: vec![],
+    };
+    repo.insert(&rec("globex", 20)).unwrap();
+    repo.insert(&rec("acme", 10)).unwrap();
+    assert!(matches!(repo.insert(&rec("acme", 30)), Err(VBError::DbDuplicate)));
+
+    assert_eq!(repo.get(&Digest256([8; 32])).unwrap().unwrap(), rec("acme", 10));
+    assert_eq!(repo.get_owned(&Digest256([8; 32]), "globex").unwrap().unwrap(), rec("globex", 20));
+    assert!(repo.get_owned(&Digest256([8; 32]), "initech").unwrap().is_none());
+    let owners: Vec<_> = repo.events(&Digest256([8; 32])).unwrap().into_iter().map(|r| r.owner).collect();
+    assert_eq!(owners, vec!["acme", "globex"]);
+  }
+
+  #[test]
+  fn test_list_pages() {
+    let repo = AnchorRepo::memory().unwrap();
+    for (i, owner) in ["acme", "globex", "acme"].into_iter().enumerate() {
+      repo.insert(&AnchorRecord {
+        digest: Digest256([i as u8; 32]),
+        owner: owner.to_string(),
+        ts: i as i64,
+        memo: None,
+        txid: None,
+        status: AnchorStatus::Local,
+        txid_history: vec![],
+      }).unwrap();
+    }
+    let ts = |recs: Vec<AnchorRecord>| recs.into_iter().map(|r| r.ts).collect::<Vec<_>>();
+    assert_eq!(ts(repo.list(None, 2, 0).unwrap()), vec![0, 1]);
+    assert_eq!(ts(repo.list(None, 2, 2).unwrap()), vec![2]);
+    assert_eq!(ts(repo.list(Some("acme"), 10, 0).unwrap()), vec![0, 2]);
+  }
+
+  #[test]
+  fn test_on_chain_count() {
+    let repo = AnchorRepo::memory().unwrap();
+    for (i, (owner, txid)) in [("acme", Some("t1")), ("acme", None), ("globex", Some("t2")), ("acme", Some("t3"))].into_iter().enumerate() {
+      repo.insert(&AnchorRecord {
+        digest: Digest256([i as u8; 32]),
+        owner: owner.to_string(),
+        ts: i as i64 * 10,
+        memo: None,
+        txid: txid.map(String::from),
+        status: AnchorStatus::Broadcast,
+        txid_history: vec![],
+      }).unwrap();
+    }
+    assert_eq!(repo.on_chain_count("acme", 0).unwrap(), 2);
+    assert_eq!(repo.on_chain_count("acme", 1).unwrap(), 1);
+    assert_eq!(repo.on_chain_count("initech", 0).unwrap(), 0);
+
+    // anchors under way count; a claim past the quota or of an anchored
+    // digest fails
+    let intent = |digest: u8| AnchorIntent {
+      digest: Digest256([digest; 32]),
+      owner: "acme".into(),
+      ts: 40,
+      memo: None,
+      payload: vec![],
+    };
+    repo.reserve_intent(&intent(9), Some((3, 0))).unwrap();
+    assert_eq!(repo.on_chain_count("acme", 0).unwrap(), 3);
+    assert!(matches!(repo.reserve_intent(&intent(8), Some((3, 0))), Err(VBError::ResourceExhausted(_))));
+    assert!(matches!(repo.reserve_intent(&intent(9), None), Err(VBError::DbDuplicate)));
+    assert!(matches!(repo.reserve_intent(&intent(0), None), Err(VBError::DbDuplicate)));
+    repo.reserve_intent(&intent(8), Some((3, 41))).unwrap();
+  }
+
+  #[test]
+  fn test_psbt_lifecycle() {
+    let repo = AnchorRepo::memory().unwrap();
+    let rec = AnchorRecord {
+      digest: Digest256([3; 32]),
+      owner: DEFAULT_OWNER.to_string(),
+      ts: 7,
+      memo: None,
+      txid: Some("ab".repeat(32)),
+      status: AnchorStatus::AwaitingSignature,
+      txid_history: vec![],
+    };
+    repo.insert(&rec).unwrap();
+    repo.put_psbt(&rec.digest, &rec.owner, b"psbt\xff").unwrap();
+    assert_eq!(repo.get_psbt(&rec.digest, &rec.owner).unwrap().unwrap(), b"psbt\xff");
+    repo.set_status(&rec.digest, &rec.owner, AnchorStatus::Broadcast).unwrap();
+    repo.delete_psbt(&rec.digest, &rec.owner).unwrap();
+    assert!(repo.get_psbt(&rec.digest, &rec.owner).unwrap().is_none());
+    assert_eq!(repo.get(&rec.digest).unwrap().unwrap().status, AnchorStatus::Broadcast);
+  }
+
+  #[test]
+  fn test_replace_txid_keeps_history() {
+    let repo = AnchorRepo::memory().unwrap();
+    let rec = AnchorRecord {
+      digest: Digest256([5; 32]),
+      owner: DEFAULT_OWNER.to_string(),
+      ts: 1,
+      memo: None,
+      txid: Some("aa".into()),
+      status: AnchorStatus::Broadcast,
+      txid_history: vec![],
+    };
+    repo.insert(&rec).unwrap();
+    repo.replace_txid(&rec.digest, &rec.owner, "bb", AnchorStatus::Broadcast).unwrap();
+    repo.replace_txid(&rec.digest, &rec.owner, "cc", AnchorStatus::AwaitingSignature).unwrap();
+    let got = repo.get(&rec.digest).unwrap().unwrap();
+    assert_eq!(got.txid.as_deref(), Some("cc"));
+    assert_eq!(got.txid_history, vec!["aa", "bb"]);
+    assert_eq!(got.status, AnchorStatus::AwaitingSignature);
+    assert_eq!(repo.get_by_txid("aa").unwrap().unwrap().digest, rec.digest);
+
+    // the original confirmed after all
+    repo.replace_txid(&rec.digest, &rec.owner, "aa", AnchorStatus::Broadcast).unwrap();
+    assert_eq!(repo.get(&rec.digest).unwrap().unwrap().txid_history, vec!["bb", "cc"]);
+  }
+
+  #[test]
+  fn test_idempotency_key_binds_once() {
+    let repo = AnchorRepo::memory().unwrap();
+    let rec = AnchorRecord {
+      digest: Digest256([6; 32]),
+      owner: DEFAULT_OWNER.to_string(),
+      ts: 1,
+      memo: None,
+      txid: None,
+      status: AnchorStatus::Local,
+      txid_history: vec![],
+    };
+    repo.insert(&rec).unwrap();
+    assert!(repo.get_idempotency_key(&rec.owner, "k1").unwrap().is_none());
+    repo.put_idempotency_key(&rec.owner, "k1", b"fp", &rec.digest, 1).unwrap();
+    repo.put_idempotency_key(&rec.owner, "k1", b"other", &rec.digest, 2).unwrap();
+    assert_eq!(repo.get_idempotency_key(&rec.owner, "k1").unwrap(), Some((b"fp".to_vec(), rec.digest)));
+  }
+
+  #[test]
+  fn test_delivery_queue() {
+    let repo = AnchorRepo::memory().unwrap();
+    let first = repo.enqueue_delivery("https://a.example/hook", b"{}", 10).unwrap();
+    repo.enqueue_delivery("https://b.example/hook", b"[]", 20).unwrap();
+    assert!(repo.due_deliveries(9, 10).unwrap().is_empty());
+    let due = repo.due_deliveries(10, 10).unwrap();
+    assert_eq!(due, vec![Delivery { id: first, url: "https://a.example/hook".into(), body: b"{}".to_vec(), attempts: 0 }]);
+
+    repo.retry_delivery(first, 30, "HTTP 503").unwrap();
+    let urls = |now| repo.due_deliveries(now, 10).unwrap().into_iter().map(|d| d.url).collect::<Vec<_>>();
+    assert_eq!(urls(20), vec!["https://b.example/hook"]);
+    assert_eq!(repo.due_deliveries(30, 1).unwrap()[0].attempts, 0);
+    assert_eq!(repo.due_deliveries(40, 10).unwrap()[1].attempts, 1);
+    repo.delete_delivery(first).unwrap();
+    assert_eq!(urls(40), vec!["https://b.example/hook"]);
+  }
+
+  #[test]
+  fn test_tenants_and_keys() {
+    let repo = AnchorRepo::memory().unwrap();
+    let hr = Tenant { id: "hr".into(), name: "Human Resources".into(), disabled: false, fee_rate: Some(5), created_at: 1 };
+    repo.insert_tenant(&hr).unwrap();
+    assert!(matches!(repo.insert_tenant(&hr), Err(VBError::DbDuplicate)));
+    assert_eq!(repo.get_tenant("hr").unwrap(), Some(hr.clone()));
+    assert!(repo.get_tenant("legal").unwrap().is_none());
+
+    assert!(repo.set_tenant_disabled("hr", true).unwrap());
+    assert!(!repo.set_tenant_disabled("legal", true).unwrap());
+    assert!(repo.tenants().unwrap()[0].disabled);
+
+    let key = KeyRecord { digest: Digest256([9; 32]), owner: "hr".into(), role: "anchorer".into(), created_at: 2 };
+    repo.insert_key(&key).unwrap();
+    assert_eq!(repo.keys().unwrap(), vec![key]);
+    assert!(repo.delete_key(&Digest256([9; 32])).unwrap());
+    assert!(!repo.delete_key(&Digest256([9; 32])).unwrap());
+    assert!(repo.keys().unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_audit_chain() {
+    let repo = AnchorRepo::memory().unwrap();
+    let entry = |ts: i64, actor: &str, action: &str| AuditEntry {
+      seq: 0,
+      ts,
+      actor: actor.into(),
+      credential: "token".into(),
+      source: "127.0.0.1:5000".into(),
+      action: action.into(),
+      target: String::new(),
+      outcome: "OK".into(),
+      hash: Digest256([0; 32]),
+    };
+    let first = repo.append_audit(&entry(10, "acme", "anchor")).unwrap();
+    let second = repo.append_audit(&entry(20, "globex", "verify")).unwrap();
+    repo.append_audit(&entry(30, "acme", "verify")).unwrap();
+    assert_eq!((first.seq, second.seq), (1, 2));
+    assert_eq!(first.hash, audit_hash(&Digest256([0; 32]), &first));
+    assert_eq!(second.hash, audit_hash(&first.hash, &second));
+    assert_eq!(repo.verify_audit().unwrap(), (3, None));
+
+    let acme = AuditFilter { actor: Some("acme".into()), ..Default::default() };
+    assert_eq!(repo.audit_entries(&acme).unwrap().len(), 2);
+    let window = AuditFilter { action: Some("verify".into()), since: Some(20), until: Some(30), ..Default::default() };
+    assert_eq!(repo.audit_entries(&window).unwrap(), vec![second]);
+    let page = AuditFilter { limit: Some(1), offset: 2, ..Default::default() };
+    assert_eq!(repo.audit_entries(&page).unwrap()[0].seq, 3);
+
+    // rewriting history breaks the chain at the rewritten entry
+    repo.conn().execute("UPDATE audit_log SET outcome = 'PERMISSION_DENIED' WHERE seq = 2", []).unwrap();
+    assert_eq!(repo.verify_audit().unwrap(), (2, Some(2)));
+  }
+
+  #[test]
+  fn test_intent_lifecycle() {
+    let repo = AnchorRepo::memory().unwrap();
+    let intent = |digest: u8| AnchorIntent {
+      digest: Digest256([digest; 32]),
+      owner: DEFAULT_OWNER.to_string(),
+      ts: digest as i64,
+      memo: None,
+      payload: vec![digest; 34],
+    };
+    repo.insert_intent(&intent(1)).unwrap();
+    repo.insert_intent(&intent(2)).unwrap();
+    assert!(matches!(repo.insert_intent(&intent(1)), Err(VBError::DbDuplicate)));
+    assert_eq!(repo.intents().unwrap(), vec![intent(1), intent(2)]);
+
+    let rec = AnchorRecord {
+      digest: Digest256([1; 32]),
+      owner: DEFAULT_OWNER.to_string(),
+      ts: 1,
+      memo: None,
+      txid: Some("ab".repeat(32)),
+      status: AnchorStatus::AwaitingSignature,
+      txid_history: vec![],
+    };
+    repo.finalize_intent(&rec, Some(b"psbt")).unwrap();
+    assert_eq!(repo.get_psbt(&rec.digest, DEFAULT_OWNER).unwrap(), Some(b"psbt".to_vec()));
+    // an anchor under way blocks local anchors of the digest
+    let local = AnchorRecord { digest: Digest256([2; 32]), txid: None, status: AnchorStatus::Local, ..rec.clone() };
+    assert!(matches!(repo.insert(&local), Err(VBError::DbDuplicate)));
+    // a paid transaction lands on the record already there
+    repo.insert_intent(&intent(1)).unwrap();
+    let broadcast = AnchorRecord { txid: Some("cd".repeat(32)), status: AnchorStatus::Broadcast, ..rec.clone() };
+    repo.finalize_intent(&broadcast, None).unwrap();
+    let stored = repo.get_owned(&rec.digest, DEFAULT_OWNER).unwrap().unwrap();
+    assert_eq!((stored.txid, stored.status), (broadcast.txid, AnchorStatus::Broadcast));
+    assert_eq!(repo.intents().unwrap(), vec![intent(2)]);
+
+    repo.delete_intent(&Digest256([2; 32]), DEFAULT_OWNER).unwrap();
+    assert!(repo.intents().unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_shared_between_threads() {
+    let repo = AnchorRepo::memory().unwrap();
+    std::thread::scope(|scope| {
+      for thread in 0..4u8 {
+        let repo = &repo;
+        scope.spawn(move || {
+          for i in 0..25u8 {
+            let rec = AnchorRecord {
+              digest: Digest256([thread * 25 + i; 32]),
+              owner: DEFAULT_OWNER.to_string(),
+              ts: i as i64,
+              memo: None,
+              txid: None,
+              status: AnchorStatus::Local,
+              txid_history: vec![],
+            };
+            repo.insert(&rec).unwrap();
+            assert!(repo.exists_digest(&rec.digest).unwrap());
+          }
+        });
+      }
+    });
+    assert_eq!(repo.all().unwrap().len(), 100);
+  }
+
+  #[test]
+  fn test_maintenance() {
+    let repo = AnchorRepo::memory().unwrap();
+    let rec = AnchorRecord {
+      digest: Digest256([1; 32]),
+      owner: DEFAULT_OWNER.to_string(),
+      ts: 1,
+      memo: None,
+      txid: None,
+      status: AnchorStatus::Local,
+      txid_history: vec![],
+    };
+    repo.insert(&rec).unwrap();
+    repo.checkpoint().unwrap();
+    repo.vacuum().unwrap();
+    assert!(repo.integrity_check().unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_migrates_original_schema() {
+    let dir = std::env::temp_dir().join(format!("vb-migrate-{}.db", std::process::id()));
+    {
+      let conn = Connection::open(&dir).unwrap();
+      conn.execute_batch(
+        "CREATE TABLE anchors (digest BLOB PRIMARY KEY, ts INTEGER NOT NULL, memo BLOB NULL, txid TEXT NULL);
+         INSERT INTO anchors VALUES (x'0404040404040404040404040404040404040404040404040404040404040404', 1, NULL, NULL);",
+      ).unwrap();
+    }
+    let repo = AnchorRepo::new(dir.to_str()).unwrap();
+    let got = repo.get(&Digest256([4; 32])).unwrap().unwrap();
+    assert_eq!(got.status, AnchorStatus::Local);
+    assert_eq!(got.owner, DEFAULT_OWNER);
+    drop(repo);
+    std::fs::remove_file(&dir).unwrap();
+  }
+}
@@ -5,6 +5,9 @@ use std::fmt;
 use std::str::FromStr;
 use thiserror::Error;
 
+pub mod payload;
+pub use payload::{AnchorPayload, HashAlg, PayloadError};
+
 // ============================================================================
 // Core Data Types
 // ============================================================================
@@ -14,13 +17,43 @@ use thiserror::Error;
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub struct Digest256(pub [u8; 32]);
 
-/// Anchor record
+/// Owner of anchors made without a tenant
+pub const DEFAULT_OWNER: &str = "default";
+
+/// Anchor record: one anchoring of `digest` by `owner`. A digest may be
+/// anchored once per owner
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub struct AnchorRecord {
   pub digest: Digest256,
+  /// Tenant that made the anchor
+  #[serde(default = "default_owner")]
+  pub owner: String,
   pub ts: i64,
   pub memo: Option<Vec<u8>>,
   pub txid: Option<String>,
+  #[serde(default)]
+  pub status: AnchorStatus,
+  /// Earlier txids of this anchor replaced by fee bumps, oldest first
+  #[serde(default)]
+  pub txid_history: Vec<String>,
+}
+
+fn default_owner() -> String {
+  DEFAULT_OWNER.to_string()
+}
+
+/// Where an anchor's transaction is in its lifecycle
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub enum AnchorStatus {
+  /// Recorded locally, no transaction
+  #[default]
+  Local,
+  /// Transaction built as a PSBT, waiting for an external signer
+  AwaitingSignature,
+  /// Transaction signed and broadcast
+  Broadcast,
+  /// Transaction mined into a block
+  Confirmed,
 }
 
 /// Memo policy enum
@@ -40,18 +73,72 @@ pub enum MemoPolicy {
 pub enum VBError {
   #[error("IO error: {0}")]
   Io(#[from] std::io::Error),
-  #[error("Hash error")]
-  Hash,
-  #[error("Wallet error")]
-  Wallet,
+  #[error("Invalid digest: {0}")]
+  InvalidDigest(String),
+  #[error("Wallet error: {0}")]
+  Wallet(String),
+  /// The transaction may have reached the network anyway, as when the
+  /// backend's reply to a broadcast was lost
+  #[error("Broadcast outcome unknown: {0}")]
+  BroadcastUnknown(String),
   #[error("Database error: {0}")]
   Db(String),
-  #[error("Duplicate record")]
+  #[error("Digest already anchored")]
   DbDuplicate,
+  #[error("Invalid anchor payload: {0}")]
+  Payload(#[from] PayloadError),
+  #[error("Invalid argument: {0}")]
+  InvalidArgument(String),
+  #[error("Not found: {0}")]
+  NotFound(String),
+  #[error("Unauthenticated: {0}")]
+  Unauthenticated(String),
+  #[error("Permission denied: {0}")]
+  PermissionDenied(String),
+  #[error("Resource exhausted: {0}")]
+  ResourceExhausted(String),
   #[error("Other error: {0}")]
   Other(String),
 }
 
+/// Stable, machine-readable error codes. The strings go over the wire and
+/// must not change once released
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ErrorCode {
+  Io,
+  InvalidDigest,
+  Wallet,
+  Database,
+  DuplicateDigest,
+  InvalidPayload,
+  InvalidArgument,
+  NotFound,
+  Unauthenticated,
+  PermissionDenied,
+  ResourceExhausted,
+  Internal,
+}
+
+impl VBError {
+  pub fn code(&self) -> ErrorCode {
+    match self {
+      VBError::Io(_) => ErrorCode::Io,
+      VBError::InvalidDigest(_) => ErrorCode::InvalidDigest,
+      VBError::Wallet(_) => ErrorCode::Wallet,
+      VBError::BroadcastUnknown(_) => ErrorCode::Wallet,
+      VBError::Db(_) => ErrorCode::Database,
+      VBError::DbDuplicate => ErrorCode::DuplicateDigest,
+      VBError::Payload(_) => ErrorCode::InvalidPayload,
+      VBError::InvalidArgument(_) => ErrorCode::InvalidArgument,
+      VBError::NotFound(_) => ErrorCode::NotFound,
+      VBError::Unauthenticated(_) => ErrorCode::Unauthenticated,
+      VBError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+      VBError::ResourceExhausted(_) => ErrorCode::ResourceExhausted,
+      VBError::Other(_) => ErrorCode::Internal,
+    }
+  }
+}
+
 // ============================================================================
 // Traits
 // ============================================================================
@@ -91,16 +178,97 @@ impl FromStr for Digest256 {
   fn from_str(s: &str) -> Result<Self, Self::Err> {
     let s = s.trim();
     if s.len() != 64 {
-      return Err(VBError::Hash);
+      return Err(VBError::InvalidDigest(format!("expected 64 hex characters, got {}", s.len())));
     }
     let mut bytes = [0u8; 32];
     for (i, byte) in bytes.iter_mut().enumerate() {
-      *byte = u8::from_str_radix(&s[2*i..2*i+2], 16).map_err(|_| VBError::Hash)?;
+      *byte = u8::from_str_radix(s.get(2*i..2*i+2).unwrap_or_default(), 16)
+        .map_err(|_| VBError::InvalidDigest(format!("not hex: {}", s)))?;
     }
     Ok(Digest256(bytes))
   }
 }
 
+const ERROR_CODES: [ErrorCode; 12] = [
+  ErrorCode::Io,
+  ErrorCode::InvalidDigest,
+  ErrorCode::Wallet,
+  ErrorCode::Database,
+  ErrorCode::DuplicateDigest,
+  ErrorCode::InvalidPayload,
+  ErrorCode::InvalidArgument,
+  ErrorCode::NotFound,
+  ErrorCode::Unauthenticated,
+  ErrorCode::PermissionDenied,
+  ErrorCode::ResourceExhausted,
+  ErrorCode::Internal,
+];
+
+impl ErrorCode {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ErrorCode::Io => "IO",
+      ErrorCode::InvalidDigest => "INVALID_DIGEST",
+      ErrorCode::Wallet => "WALLET",
+      ErrorCode::Database => "DATABASE",
+      ErrorCode::DuplicateDigest => "DUPLICATE_DIGEST",
+      ErrorCode::InvalidPayload => "INVALID_PAYLOAD",
+      ErrorCode::InvalidArgument => "INVALID_ARGUMENT",
+      ErrorCode::NotFound => "NOT_FOUND",
+      ErrorCode::Unauthenticated => "UNAUTHENTICATED",
+      ErrorCode::PermissionDenied => "PERMISSION_DENIED",
+      ErrorCode::ResourceExhausted => "RESOURCE_EXHAUSTED",
+      ErrorCode::Internal => "INTERNAL",
+    }
+  }
+}
+
+impl fmt::Display for ErrorCode {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for ErrorCode {
+  type Err = VBError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    ERROR_CODES
+      .into_iter()
+      .find(|code| code.as_str() == s)
+      .ok_or_else(|| VBError::Other(format!("Unknown error code: {}", s)))
+  }
+}
+
+impl AnchorStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AnchorStatus::Local => "local",
+      AnchorStatus::AwaitingSignature => "awaiting_signature",
+      AnchorStatus::Broadcast => "broadcast",
+      AnchorStatus::Confirmed => "confirmed",
+    }
+  }
+}
+
+impl fmt::Display for AnchorStatus {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for AnchorStatus {
+  type Err = VBError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "local" => Ok(AnchorStatus::Local),
+      "awaiting_signature" => Ok(AnchorStatus::AwaitingSignature),
+      "broadcast" => Ok(AnchorStatus::Broadcast),
+      "confirmed" => Ok(AnchorStatus::Confirmed),
+      _ => Err(VBError::Other(format!("Unknown anchor status: {}", s))),
+    }
+  }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -108,7 +276,6 @@ impl FromStr for Digest256 {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use serde_json;
 
   #[test]
   fn test_digest256_fromstr_and_display() {
@@ -137,15 +304,44 @@ mod tests {
   fn test_serde_roundtrip_anchorrecord() {
     let rec = AnchorRecord {
       digest: Digest256([1; 32]),
+      owner: "acme".to_string(),
       ts: 1234567890,
       memo: Some(vec![1,2,3]),
       txid: Some("txid123".to_string()),
+      status: AnchorStatus::Broadcast,
+      txid_history: vec!["txid122".to_string()],
     };
     let ser = serde_json::to_string(&rec).unwrap();
     let de: AnchorRecord = serde_json::from_str(&ser).unwrap();
     assert_eq!(rec, de);
   }
 
+  #[test]
+  fn test_anchorrecord_owner_defaults() {
+    let json = r#"{"digest":[2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2],"ts":1,"memo":null,"txid":null}"#;
+    let rec: AnchorRecord = serde_json::from_str(json).unwrap();
+    assert_eq!(rec.owner, DEFAULT_OWNER);
+  }
+
+  #[test]
+  fn test_anchorstatus_str_roundtrip() {
+    for status in [AnchorStatus::Local, AnchorStatus::AwaitingSignature, AnchorStatus::Broadcast] {
+      assert_eq!(AnchorStatus::from_str(status.as_str()).unwrap(), status);
+    }
+    assert!(AnchorStatus::from_str("mined").is_err());
+  }
+
+  #[test]
+  fn test_error_codes_stable() {
+    for code in ERROR_CODES {
+      assert_eq!(ErrorCode::from_str(code.as_str()).unwrap(), code);
+    }
+    assert_eq!(VBError::DbDuplicate.code().as_str(), "DUPLICATE_DIGEST");
+    let err = Digest256::from_str("deadbeef").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidDigest);
+    assert!(err.to_string().contains("64 hex"));
+  }
+
   #[test]
   fn test_bytelen_trait() {
     let v = vec![1,2,3,4];
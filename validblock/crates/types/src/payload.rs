@@ -0,0 +1,220 @@
+//! On-chain anchor payload carried in an OP_RETURN output.
+//!
+//! Layout, version 1:
+//!
+//! | bytes  | field                               |
+//! |--------|-------------------------------------|
+//! | 0..4   | magic `VBLK`                        |
+//! | 4      | version (`1`)                       |
+//! | 5      | hash algorithm id (`1` = SHA-256)   |
+//! | 6..38  | digest                              |
+//! | 38..   | memo, optional, up to 42 bytes      |
+//!
+//! The memo runs to the end of the payload; a payload ending right after
+//! the digest has no memo. Decoding is strict: anything that doesn't
+//! re-encode to the same bytes is rejected.
+
+use crate::Digest256;
+use thiserror::Error;
+
+/// Marks an OP_RETURN payload as a ValidBlock anchor
+pub const MAGIC: &[u8; 4] = b"VBLK";
+
+/// Payload version written by this build
+pub const PAYLOAD_VERSION: u8 = 1;
+
+/// Largest OP_RETURN payload relayed under default standardness rules
+pub const MAX_PAYLOAD_LEN: usize = 80;
+
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// Room left for a memo next to a 32-byte digest
+pub const MAX_MEMO_LEN: usize = MAX_PAYLOAD_LEN - HEADER_LEN - 32;
+
+/// Hash algorithm that produced the anchored digest
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum HashAlg {
+  Sha256,
+}
+
+impl HashAlg {
+  pub fn id(&self) -> u8 {
+    match self {
+      HashAlg::Sha256 => 1,
+    }
+  }
+
+  pub fn from_id(id: u8) -> Result<Self, PayloadError> {
+    match id {
+      1 => Ok(HashAlg::Sha256),
+      other => Err(PayloadError::UnknownHashAlg(other)),
+    }
+  }
+
+  pub fn digest_len(&self) -> usize {
+    match self {
+      HashAlg::Sha256 => 32,
+    }
+  }
+}
+
+/// Why bytes are not a valid anchor payload
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum PayloadError {
+  #[error("not a ValidBlock payload")]
+  BadMagic,
+  #[error("unsupported payload version {0}")]
+  UnsupportedVersion(u8),
+  #[error("unknown hash algorithm id {0}")]
+  UnknownHashAlg(u8),
+  #[error("payload truncated at {0} bytes")]
+  Truncated(usize),
+  #[error("memo of {0} bytes exceeds {max}", max = MAX_MEMO_LEN)]
+  MemoTooLong(usize),
+}
+
+/// Decoded anchor payload
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct AnchorPayload {
+  pub alg: HashAlg,
+  pub digest: Digest256,
+  pub memo: Option<Vec<u8>>,
+}
+
+impl AnchorPayload {
+  /// SHA-256 digest without a memo
+  pub fn new(digest: Digest256) -> Self {
+    AnchorPayload { alg: HashAlg::Sha256, digest, memo: None }
+  }
+
+  /// Attach a memo; an empty memo is the same as none
+  pub fn with_memo(mut self, memo: &[u8]) -> Result<Self, PayloadError> {
+    if memo.len() > MAX_MEMO_LEN {
+      return Err(PayloadError::MemoTooLong(memo.len()));
+    }
+    self.memo = (!memo.is_empty()).then(|| memo.to_vec());
+    Ok(self)
+  }
+
+  /// Serialized form; never longer than `MAX_PAYLOAD_LEN`
+  pub fn encode(&self) -> Vec<u8> {
+    let memo = self.memo.as_deref().unwrap_or_default();
+    let mut out = Vec::with_capacity(HEADER_LEN + self.digest.0.len() + memo.len());
+    out.extend_from_slice(MAGIC);
+    out.push(PAYLOAD_VERSION);
+    out.push(self.alg.id());
+    out.extend_from_slice(&self.digest.0);
+    out.extend_from_slice(memo);
+    out
+  }
+
+  pub fn decode(bytes: &[u8]) -> Result<Self, PayloadError> {
+    if !Self::is_tagged(bytes) {
+      return Err(PayloadError::BadMagic);
+    }
+    let Some(&[version, alg]) = bytes.get(MAGIC.len()..HEADER_LEN) else {
+      return Err(PayloadError::Truncated(bytes.len()));
+    };
+    if version != PAYLOAD_VERSION {
+      return Err(PayloadError::UnsupportedVersion(version));
+    }
+    let alg = HashAlg::from_id(alg)?;
+    let body = &bytes[HEADER_LEN..];
+    if body.len() < alg.digest_len() {
+      return Err(PayloadError::Truncated(bytes.len()));
+    }
+    let (digest, memo) = body.split_at(alg.digest_len());
+    if memo.len() > MAX_MEMO_LEN {
+      return Err(PayloadError::MemoTooLong(memo.len()));
+    }
+    Ok(AnchorPayload {
+      alg,
+      digest: Digest256(digest.try_into().map_err(|_| PayloadError::Truncated(bytes.len()))?),
+      memo: (!memo.is_empty()).then(|| memo.to_vec()),
+    })
+  }
+
+  /// Whether `bytes` claim to be a ValidBlock payload, valid or not
+  pub fn is_tagged(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+  }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use proptest::prelude::*;
+
+  fn payload(memo: &[u8]) -> Vec<u8> {
+    AnchorPayload::new(Digest256([7; 32])).with_memo(memo).unwrap().encode()
+  }
+
+  #[test]
+  fn test_layout() {
+    let bytes = payload(b"hi");
+    assert_eq!(&bytes[..6], b"VBLK\x01\x01");
+    assert_eq!(&bytes[6..38], &[7; 32]);
+    assert_eq!(&bytes[38..], b"hi");
+    assert_eq!(payload(&[1; MAX_MEMO_LEN]).len(), MAX_PAYLOAD_LEN);
+  }
+
+  #[test]
+  fn test_memo_limits() {
+    let anchor = AnchorPayload::new(Digest256([7; 32]));
+    assert_eq!(anchor.clone().with_memo(b"").unwrap().memo, None);
+    assert_eq!(
+      anchor.with_memo(&[1; MAX_MEMO_LEN + 1]),
+      Err(PayloadError::MemoTooLong(MAX_MEMO_LEN + 1))
+    );
+  }
+
+  #[test]
+  fn test_decode_rejects_malformed() {
+    let good = payload(b"");
+    assert_eq!(AnchorPayload::decode(&[7; 38]), Err(PayloadError::BadMagic));
+    assert_eq!(AnchorPayload::decode(b"VBLK\x01"), Err(PayloadError::Truncated(5)));
+    assert_eq!(AnchorPayload::decode(&good[..37]), Err(PayloadError::Truncated(37)));
+
+    let mut bad = good.clone();
+    bad[4] = 2;
+    assert_eq!(AnchorPayload::decode(&bad), Err(PayloadError::UnsupportedVersion(2)));
+    bad[4] = 1;
+    bad[5] = 0;
+    assert_eq!(AnchorPayload::decode(&bad), Err(PayloadError::UnknownHashAlg(0)));
+
+    let long = [&good[..], &[1; MAX_MEMO_LEN + 1]].concat();
+    assert_eq!(AnchorPayload::decode(&long), Err(PayloadError::MemoTooLong(MAX_MEMO_LEN + 1)));
+  }
+
+  proptest! {
+    #[test]
+    fn fuzz_roundtrip(digest in any::<[u8; 32]>(), memo in proptest::collection::vec(any::<u8>(), 0..=MAX_MEMO_LEN)) {
+      let anchor = AnchorPayload::new(Digest256(digest)).with_memo(&memo).unwrap();
+      let bytes = anchor.encode();
+      prop_assert!(bytes.len() <= MAX_PAYLOAD_LEN);
+      prop_assert_eq!(AnchorPayload::decode(&bytes).unwrap(), anchor);
+    }
+
+    #[test]
+    fn fuzz_decode_is_strict(bytes in proptest::collection::vec(any::<u8>(), 0..120)) {
+      // never panics, and anything accepted is canonical and standard-sized
+      if let Ok(anchor) = AnchorPayload::decode(&bytes) {
+        prop_assert_eq!(anchor.encode(), bytes.clone());
+        prop_assert!(bytes.len() <= MAX_PAYLOAD_LEN);
+      }
+    }
+
+    #[test]
+    fn fuzz_decode_tagged_bytes(tail in proptest::collection::vec(any::<u8>(), 0..90)) {
+      let bytes = [&MAGIC[..], &[PAYLOAD_VERSION, HashAlg::Sha256.id()], &tail].concat();
+      match AnchorPayload::decode(&bytes) {
+        Ok(anchor) => prop_assert_eq!(anchor.encode(), bytes),
+        Err(e) => prop_assert!(matches!(e, PayloadError::Truncated(_) | PayloadError::MemoTooLong(_))),
+      }
+    }
+  }
+}
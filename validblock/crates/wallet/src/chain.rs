@@ -0,0 +1,104 @@
+use bitcoin::block::{Block, Header};
+use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::{BlockHash, OutPoint, Script, Transaction, TxOut, Txid};
+use std::collections::HashSet;
+use validblock_types::VBError;
+
+use crate::tx::op_return_payload;
+
+/// Unspent output the wallet can spend
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Utxo {
+  pub outpoint: OutPoint,
+  pub txout: TxOut,
+}
+
+/// Merkle branch placing a transaction in a block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+  pub block_hash: BlockHash,
+  pub height: u32,
+  /// Position of the transaction in the block
+  pub pos: u32,
+  /// Sibling hashes from the leaf up to the root
+  pub merkle: Vec<TxMerkleNode>,
+}
+
+/// Read/write access to the Bitcoin network
+pub trait ChainBackend {
+  /// Unspent outputs locked to `script_pubkey`
+  fn unspent(&self, script_pubkey: &Script) -> Result<Vec<Utxo>, VBError>;
+
+  /// Submit a fully signed transaction to the network
+  fn broadcast(&self, tx: &Transaction) -> Result<Txid, VBError>;
+
+  /// A transaction in the mempool or a block, `None` if unknown
+  fn transaction(&self, txid: &Txid) -> Result<Option<Transaction>, VBError>;
+
+  /// Height of the block confirming `txid`, `None` while unconfirmed
+  fn confirmed_height(&self, txid: &Txid) -> Result<Option<u32>, VBError>;
+
+  /// Merkle branch for a confirmed transaction, `None` while unconfirmed
+  fn merkle_proof(&self, txid: &Txid) -> Result<Option<MerkleProof>, VBError>;
+
+  /// Header of the block `hash`, `None` if unknown
+  fn block_header(&self, hash: &BlockHash) -> Result<Option<Header>, VBError>;
+
+  /// Every transaction paying to or spending from `script_pubkey`,
+  /// confirmed or in the mempool
+  fn script_history(&self, script_pubkey: &Script) -> Result<Vec<Txid>, VBError>;
+
+  /// Hash of the active-chain block at `height`, `None` above the tip
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>, VBError>;
+
+  /// Full block `hash`, `None` if unknown
+  fn block(&self, hash: &BlockHash) -> Result<Option<Block>, VBError>;
+}
+
+/// Outputs spent by `tx`, in input order
+pub fn prevouts<C: ChainBackend + ?Sized>(chain: &C, tx: &Transaction) -> Result<Vec<TxOut>, VBError> {
+  tx.input
+    .iter()
+    .map(|input| {
+      let prev = input.previous_output;
+      chain
+        .transaction(&prev.txid)?
+        .and_then(|parent| parent.output.get(prev.vout as usize).cloned())
+        .ok_or_else(|| VBError::Wallet(format!("Unknown input {}", prev)))
+    })
+    .collect()
+}
+
+/// `txid` if it is known and still unconfirmed, so its fee can be bumped
+pub fn unconfirmed_tx<C: ChainBackend + ?Sized>(chain: &C, txid: &Txid) -> Result<Transaction, VBError> {
+  let tx = chain
+    .transaction(txid)?
+    .ok_or_else(|| VBError::NotFound(format!("Transaction {} not found", txid)))?;
+  if chain.confirmed_height(txid)?.is_some() {
+    return Err(VBError::Wallet(format!("Transaction {} is already confirmed", txid)));
+  }
+  Ok(tx)
+}
+
+/// A transaction paying to or spending from one of `scripts` whose OP_RETURN
+/// carries exactly `payload`
+pub fn find_payload<'a, C: ChainBackend + ?Sized>(
+  chain: &C,
+  scripts: impl IntoIterator<Item = &'a Script>,
+  payload: &[u8],
+) -> Result<Option<Txid>, VBError> {
+  let mut seen = HashSet::new();
+  for script in scripts {
+    for txid in chain.script_history(script)? {
+      if !seen.insert(txid) {
+        continue;
+      }
+      if let Some(tx) = chain.transaction(&txid)? {
+        if op_return_payload(&tx).as_deref() == Some(payload) {
+          return Ok(Some(txid));
+        }
+      }
+    }
+  }
+  Ok(None)
+}
@@ -0,0 +1,233 @@
+use crate::chain::{ChainBackend, MerkleProof, Utxo};
+use bitcoin::consensus::encode::{deserialize, serialize_hex};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::block::{Block, Header};
+use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::{BlockHash, OutPoint, Script, Transaction, TxOut, Txid};
+use serde::Deserialize;
+use std::io::Read;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use validblock_types::VBError;
+
+/// Chain backend speaking the Esplora REST API (blockstream.info, mempool.space)
+#[derive(Clone, Debug)]
+pub struct EsploraBackend {
+  base_url: String,
+  agent: ureq::Agent,
+}
+
+#[derive(Deserialize)]
+struct EsploraUtxo {
+  txid: String,
+  vout: u32,
+  value: u64,
+}
+
+#[derive(Deserialize)]
+struct EsploraTx {
+  txid: String,
+}
+
+/// Confirmed transactions per page of `/scripthash/:hash/txs/chain`
+const CHAIN_PAGE: usize = 25;
+
+#[derive(Deserialize)]
+struct EsploraMerkleProof {
+  block_height: u32,
+  merkle: Vec<String>,
+  pos: u32,
+}
+
+#[derive(Deserialize)]
+struct EsploraTxStatus {
+  confirmed: bool,
+  block_height: Option<u32>,
+}
+
+impl EsploraBackend {
+  pub fn new(base_url: &str) -> Self {
+    let agent = ureq::AgentBuilder::new()
+      .timeout(Duration::from_secs(30))
+      .build();
+    Self {
+      base_url: base_url.trim_end_matches('/').to_string(),
+      agent,
+    }
+  }
+
+  fn get(&self, path: &str) -> Result<ureq::Response, VBError> {
+    debug!(path, "Esplora request");
+    self.agent
+      .get(&format!("{}{}", self.base_url, path))
+      .call()
+      .map_err(|e| VBError::Wallet(format!("Esplora request failed: {}", e)))
+  }
+
+  /// Like `get`, but a 404 is `None`
+  fn get_optional(&self, path: &str) -> Result<Option<ureq::Response>, VBError> {
+    debug!(path, "Esplora request");
+    match self.agent.get(&format!("{}{}", self.base_url, path)).call() {
+      Ok(res) => Ok(Some(res)),
+      Err(ureq::Error::Status(404, _)) => Ok(None),
+      Err(e) => Err(VBError::Wallet(format!("Esplora request failed: {}", e))),
+    }
+  }
+}
+
+/// Electrum-style script hash: sha256 of the script, hex encoded in reverse
+fn script_hash(script: &Script) -> String {
+  let mut hash = sha256::Hash::hash(script.as_bytes()).to_byte_array();
+  hash.reverse();
+  hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl ChainBackend for EsploraBackend {
+  fn unspent(&self, script_pubkey: &Script) -> Result<Vec<Utxo>, VBError> {
+    let utxos: Vec<EsploraUtxo> = self
+      .get(&format!("/scripthash/{}/utxo", script_hash(script_pubkey)))?
+      .into_json()
+      .map_err(|e| VBError::Other(format!("Invalid Esplora response: {}", e)))?;
+    utxos
+      .into_iter()
+      .map(|u| {
+        let txid = Txid::from_str(&u.txid).map_err(|_| VBError::Other("Invalid txid from backend".into()))?;
+        Ok(Utxo {
+          outpoint: OutPoint::new(txid, u.vout),
+          txout: TxOut { value: u.value, script_pubkey: script_pubkey.to_owned() },
+        })
+      })
+      .collect()
+  }
+
+  fn broadcast(&self, tx: &Transaction) -> Result<Txid, VBError> {
+    let body = self.agent
+      .post(&format!("{}/tx", self.base_url))
+      .send_string(&serialize_hex(tx))
+      .map_err(|e| match e {
+        ureq::Error::Status(..) => VBError::Wallet(format!("Broadcast rejected: {}", e)),
+        // the request may have been delivered before the connection failed
+        ureq::Error::Transport(_) => VBError::BroadcastUnknown(e.to_string()),
+      })?
+      .into_string()
+      .map_err(|e| VBError::BroadcastUnknown(format!("Accepted, but the reply was lost: {}", e)))?;
+    // accepted whatever the reply says, so a garbled one is no failure
+    let txid = tx.txid();
+    if Txid::from_str(body.trim()).ok() != Some(txid) {
+      warn!(%txid, reply = body.trim(), "Backend acknowledged the broadcast with a different txid");
+    }
+    info!(%txid, "Broadcast transaction");
+    Ok(txid)
+  }
+
+  fn transaction(&self, txid: &Txid) -> Result<Option<Transaction>, VBError> {
+    let Some(res) = self.get_optional(&format!("/tx/{}/raw", txid))? else {
+      return Ok(None);
+    };
+    let mut raw = Vec::new();
+    res.into_reader().read_to_end(&mut raw)?;
+    deserialize(&raw).map(Some).map_err(|_| VBError::Other("Invalid transaction from Esplora".into()))
+  }
+
+  fn confirmed_height(&self, txid: &Txid) -> Result<Option<u32>, VBError> {
+    let Some(res) = self.get_optional(&format!("/tx/{}/status", txid))? else {
+      return Ok(None);
+    };
+    let status: EsploraTxStatus = res
+      .into_json()
+      .map_err(|e| VBError::Other(format!("Invalid Esplora response: {}", e)))?;
+    Ok(status.block_height.filter(|_| status.confirmed))
+  }
+
+  fn merkle_proof(&self, txid: &Txid) -> Result<Option<MerkleProof>, VBError> {
+    if self.confirmed_height(txid)?.is_none() {
+      return Ok(None);
+    }
+    let proof: EsploraMerkleProof = self
+      .get(&format!("/tx/{}/merkle-proof", txid))?
+      .into_json()
+      .map_err(|e| VBError::Other(format!("Invalid Esplora response: {}", e)))?;
+    let hash = self.get(&format!("/block-height/{}", proof.block_height))?.into_string()?;
+    let merkle = proof
+      .merkle
+      .iter()
+      .map(|h| TxMerkleNode::from_str(h))
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|_| VBError::Other("Invalid Merkle proof from Esplora".into()))?;
+    Ok(Some(MerkleProof {
+      block_hash: BlockHash::from_str(hash.trim()).map_err(|_| VBError::Other("Invalid block hash from backend".into()))?,
+      height: proof.block_height,
+      pos: proof.pos,
+      merkle,
+    }))
+  }
+
+  fn block_header(&self, hash: &BlockHash) -> Result<Option<Header>, VBError> {
+    let Some(res) = self.get_optional(&format!("/block/{}/header", hash))? else {
+      return Ok(None);
+    };
+    let raw = Vec::<u8>::from_hex(res.into_string()?.trim())
+      .map_err(|_| VBError::Other("Invalid block header from Esplora".into()))?;
+    deserialize(&raw).map(Some).map_err(|_| VBError::Other("Invalid block header from Esplora".into()))
+  }
+
+  fn script_history(&self, script_pubkey: &Script) -> Result<Vec<Txid>, VBError> {
+    let hash = script_hash(script_pubkey);
+    let parse = |txs: Vec<EsploraTx>| -> Result<Vec<Txid>, VBError> {
+      txs.iter().map(|t| Txid::from_str(&t.txid).map_err(|_| VBError::Other("Invalid txid from backend".into()))).collect()
+    };
+    let mut history = parse(
+      self
+        .get(&format!("/scripthash/{}/txs/mempool", hash))?
+        .into_json()
+        .map_err(|e| VBError::Other(format!("Invalid Esplora response: {}", e)))?,
+    )?;
+    let mut last_seen: Option<Txid> = None;
+    loop {
+      let path = match last_seen {
+        Some(txid) => format!("/scripthash/{}/txs/chain/{}", hash, txid),
+        None => format!("/scripthash/{}/txs/chain", hash),
+      };
+      let page = parse(
+        self
+          .get(&path)?
+          .into_json()
+          .map_err(|e| VBError::Other(format!("Invalid Esplora response: {}", e)))?,
+      )?;
+      last_seen = page.last().copied();
+      let done = page.len() < CHAIN_PAGE;
+      history.extend(page);
+      if done {
+        return Ok(history);
+      }
+    }
+  }
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>, VBError> {
+    let Some(res) = self.get_optional(&format!("/block-height/{}", height))? else {
+      return Ok(None);
+    };
+    BlockHash::from_str(res.into_string()?.trim()).map(Some).map_err(|_| VBError::Other("Invalid block hash from backend".into()))
+  }
+
+  fn block(&self, hash: &BlockHash) -> Result<Option<Block>, VBError> {
+    let Some(res) = self.get_optional(&format!("/block/{}/raw", hash))? else {
+      return Ok(None);
+    };
+    let mut raw = Vec::new();
+    res.into_reader().read_to_end(&mut raw)?;
+    deserialize(&raw).map(Some).map_err(|_| VBError::Other("Invalid block from Esplora".into()))
+  }
+}
+
+/// Public Esplora instance for `network`; regtest has none
+pub fn default_url(network: bitcoin::Network) -> Option<&'static str> {
+  match network {
+    bitcoin::Network::Bitcoin => Some("https://blockstream.info/api"),
+    bitcoin::Network::Testnet => Some("https://blockstream.info/testnet/api"),
+    bitcoin::Network::Signet => Some("https://mempool.space/signet/api"),
+    _ => None,
+  }
+}
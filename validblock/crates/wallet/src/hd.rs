@@ -0,0 +1,547 @@
+use bip39::Mnemonic;
+use bitcoin::base58;
+use bitcoin::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey, ExtendedPubKey, Fingerprint};
+use bitcoin::psbt::PartiallySignedTransaction as Psbt;
+use bitcoin::secp256k1::{All, Secp256k1};
+use bitcoin::{ecdsa, Address, Network, PrivateKey, PublicKey, Script, ScriptBuf, Transaction, Witness};
+use std::fmt;
+use std::str::FromStr;
+use validblock_types::VBError;
+use zeroize::Zeroizing;
+
+use crate::chain::{ChainBackend, Utxo};
+use crate::key_network_matches;
+use crate::tx::p2wpkh_sighash;
+
+/// Consecutive unused receive addresses handed out before we refuse more (BIP-44)
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// SLIP-132 version bytes for BIP-84 extended private keys
+const ZPRV_VERSION: [u8; 4] = [0x04, 0xb2, 0x43, 0x0c];
+const VPRV_VERSION: [u8; 4] = [0x04, 0x5f, 0x18, 0xbc];
+const XPRV_VERSION: [u8; 4] = [0x04, 0x88, 0xad, 0xe4];
+const TPRV_VERSION: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+const ZPUB_VERSION: [u8; 4] = [0x04, 0xb2, 0x47, 0x46];
+const VPUB_VERSION: [u8; 4] = [0x04, 0x5f, 0x1c, 0xf6];
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+const TPUB_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+
+/// BIP-32 chain below the account key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyChain {
+  /// Receive addresses (`/0/*`)
+  External,
+  /// Change addresses (`/1/*`)
+  Internal,
+}
+
+impl KeyChain {
+  fn idx(self) -> usize {
+    match self {
+      KeyChain::External => 0,
+      KeyChain::Internal => 1,
+    }
+  }
+}
+
+#[derive(Clone)]
+enum AccountKey {
+  Private(ExtendedPrivKey),
+  /// Watch-only: addresses can be derived but nothing can be signed
+  Public(ExtendedPubKey),
+}
+
+impl Drop for AccountKey {
+  fn drop(&mut self) {
+    if let AccountKey::Private(xprv) = self {
+      xprv.private_key.non_secure_erase();
+    }
+  }
+}
+
+/// BIP-84 native segwit account derived from an xprv/zprv or BIP-39 mnemonic,
+/// or watch-only from an xpub/zpub
+#[derive(Clone)]
+pub struct HdWallet {
+  secp: Secp256k1<All>,
+  account: AccountKey,
+  origin: Option<(Fingerprint, DerivationPath)>,
+  gap_limit: u32,
+  next: [u32; 2],
+  last_used: [Option<u32>; 2],
+}
+
+impl HdWallet {
+  /// Import a BIP-39 mnemonic (with optional passphrase) for `network`
+  pub fn from_mnemonic(phrase: &str, passphrase: &str, network: Network) -> Result<Self, VBError> {
+    let mnemonic = Mnemonic::parse(phrase).map_err(|e| VBError::Wallet(format!("Invalid mnemonic: {}", e)))?;
+    let seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+    let mut master = ExtendedPrivKey::new_master(network, &*seed).map_err(|_| VBError::Wallet("Invalid seed".into()))?;
+    let wallet = Self::from_master(master);
+    master.private_key.non_secure_erase();
+    wallet
+  }
+
+  /// Derive the first BIP-84 account (`m/84'/coin'/0'`) from a master key
+  pub fn from_master(master: ExtendedPrivKey) -> Result<Self, VBError> {
+    let secp = Secp256k1::new();
+    let path = account_path(master.network);
+    let account = master.derive_priv(&secp, &path).map_err(|_| VBError::Wallet("Account key derivation failed".into()))?;
+    let origin = Some((master.fingerprint(&secp), path));
+    Ok(Self::with_account(secp, AccountKey::Private(account), origin))
+  }
+
+  /// Import an xprv/tprv or SLIP-132 zprv/vprv for `network`, either master
+  /// (depth 0) or account level (depth 3)
+  pub fn from_xprv(s: &str, network: Network) -> Result<Self, VBError> {
+    let mut key = decode_xprv(s)?;
+    if !key_network_matches(key.network, network) {
+      return Err(VBError::Wallet("Key is for another network".into()));
+    }
+    // tprv covers testnet, signet and regtest alike
+    key.network = network;
+    let wallet = match key.depth {
+      0 => Self::from_master(key),
+      3 => Ok(Self::from_account(key)),
+      _ => Err(VBError::Wallet("Extended key must be a master or account key".into())),
+    };
+    key.private_key.non_secure_erase();
+    wallet
+  }
+
+  /// Watch-only account from an xpub/tpub or SLIP-132 zpub/vpub at account
+  /// level, optionally prefixed with its origin: `[d34db33f/84h/0h/0h]zpub...`
+  pub fn watch_only(s: &str, network: Network) -> Result<Self, VBError> {
+    let s = s.trim();
+    let (origin, key) = match s.strip_prefix('[') {
+      Some(rest) => {
+        let (origin, key) = rest.split_once(']').ok_or_else(|| VBError::Wallet("Unterminated key origin".into()))?;
+        let (fp, path) = origin.split_once('/').unwrap_or((origin, ""));
+        let fp = Fingerprint::from_str(fp).map_err(|_| VBError::Wallet("Invalid key origin fingerprint".into()))?;
+        let path = DerivationPath::from_str(&format!("m/{}", path.replace('h', "'")))
+          .map_err(|_| VBError::Wallet("Invalid key origin path".into()))?;
+        (Some((fp, path)), key)
+      }
+      None => (None, s),
+    };
+    let mut xpub = decode_xpub(key)?;
+    if !key_network_matches(xpub.network, network) || xpub.depth != 3 {
+      return Err(VBError::Wallet("Expected an account-level xpub for this network".into()));
+    }
+    xpub.network = network;
+    Ok(Self::with_account(Secp256k1::new(), AccountKey::Public(xpub), origin))
+  }
+
+  /// Use an already-derived account key, origin unknown
+  pub fn from_account(account: ExtendedPrivKey) -> Self {
+    Self::with_account(Secp256k1::new(), AccountKey::Private(account), None)
+  }
+
+  fn with_account(
+    secp: Secp256k1<All>,
+    account: AccountKey,
+    origin: Option<(Fingerprint, DerivationPath)>,
+  ) -> Self {
+    Self {
+      secp,
+      account,
+      origin,
+      gap_limit: DEFAULT_GAP_LIMIT,
+      next: [0; 2],
+      last_used: [None; 2],
+    }
+  }
+
+  pub fn with_gap_limit(mut self, gap_limit: u32) -> Self {
+    self.gap_limit = gap_limit;
+    self
+  }
+
+  pub fn network(&self) -> Network {
+    match &self.account {
+      AccountKey::Private(k) => k.network,
+      AccountKey::Public(k) => k.network,
+    }
+  }
+
+  /// True when built from an xpub and unable to sign
+  pub fn is_watch_only(&self) -> bool {
+    matches!(self.account, AccountKey::Public(_))
+  }
+
+  pub fn gap_limit(&self) -> u32 {
+    self.gap_limit
+  }
+
+  /// Account-level extended public key
+  pub fn account_xpub(&self) -> ExtendedPubKey {
+    match &self.account {
+      AccountKey::Private(k) => ExtendedPubKey::from_priv(&self.secp, k),
+      AccountKey::Public(k) => *k,
+    }
+  }
+
+  fn derive_priv(&self, chain: KeyChain, index: u32) -> Result<PrivateKey, VBError> {
+    let AccountKey::Private(account) = &self.account else {
+      return Err(VBError::Wallet("Watch-only wallet cannot sign".into()));
+    };
+    let child = account
+      .derive_priv(&self.secp, &child_path(chain, index)?)
+      .map_err(|_| VBError::Wallet("Key derivation failed".into()))?;
+    Ok(child.to_priv())
+  }
+
+  fn public_key(&self, chain: KeyChain, index: u32) -> Result<PublicKey, VBError> {
+    let child = self
+      .account_xpub()
+      .derive_pub(&self.secp, &child_path(chain, index)?)
+      .map_err(|_| VBError::Wallet("Key derivation failed".into()))?;
+    Ok(child.to_pub())
+  }
+
+  /// BIP-32 origin of `chain/index` for PSBT key paths: full path from the
+  /// master when known, otherwise relative to the account key
+  pub fn key_source(&self, chain: KeyChain, index: u32) -> Result<(PublicKey, (Fingerprint, DerivationPath)), VBError> {
+    let pk = self.public_key(chain, index)?;
+    let child = child_path(chain, index)?;
+    let source = match &self.origin {
+      Some((fp, path)) => (*fp, path.extend(child)),
+      None => (self.account_xpub().fingerprint(), DerivationPath::from(child.to_vec())),
+    };
+    Ok((pk, source))
+  }
+
+  /// P2WPKH address at `chain/index`
+  pub fn address(&self, chain: KeyChain, index: u32) -> Result<Address, VBError> {
+    let pk = self.public_key(chain, index)?;
+    Address::p2wpkh(&pk, self.network()).map_err(|_| VBError::Wallet("Invalid address key".into()))
+  }
+
+  /// Reserve the next unused index on `chain`.
+  ///
+  /// Receive addresses are capped at `gap_limit` unused addresses past the
+  /// last one seen on chain so a rescan can always find every deposit.
+  pub fn next_index(&mut self, chain: KeyChain) -> Result<u32, VBError> {
+    let i = chain.idx();
+    let first_unused = self.last_used[i].map_or(0, |u| u + 1);
+    if chain == KeyChain::External && self.next[i] - first_unused >= self.gap_limit {
+      return Err(VBError::ResourceExhausted(format!(
+        "Gap limit of {} unused receive addresses reached",
+        self.gap_limit
+      )));
+    }
+    let index = self.next[i];
+    self.next[i] += 1;
+    Ok(index)
+  }
+
+  /// Fresh address on `chain`
+  pub fn next_address(&mut self, chain: KeyChain) -> Result<Address, VBError> {
+    let index = self.next_index(chain)?;
+    self.address(chain, index)
+  }
+
+  /// Record that `chain/index` has been seen on chain
+  pub fn mark_used(&mut self, chain: KeyChain, index: u32) {
+    let i = chain.idx();
+    self.last_used[i] = Some(self.last_used[i].map_or(index, |u| u.max(index)));
+    self.next[i] = self.next[i].max(index + 1);
+  }
+
+  /// Every script that may hold funds: all handed-out indices plus the gap
+  pub fn watched_scripts(&self) -> Result<Vec<(KeyChain, u32, ScriptBuf)>, VBError> {
+    let mut out = Vec::new();
+    for chain in [KeyChain::External, KeyChain::Internal] {
+      let i = chain.idx();
+      let first_unused = self.last_used[i].map_or(0, |u| u + 1);
+      let end = self.next[i].max(first_unused + self.gap_limit);
+      for index in 0..end {
+        out.push((chain, index, self.address(chain, index)?.script_pubkey()));
+      }
+    }
+    Ok(out)
+  }
+
+  /// Key chain and index owning `script_pubkey`, among the watched scripts
+  pub fn owner_of(&self, script_pubkey: &Script) -> Result<Option<(KeyChain, u32)>, VBError> {
+    Ok(self
+      .watched_scripts()?
+      .into_iter()
+      .find(|(_, _, script)| script.as_script() == script_pubkey)
+      .map(|(chain, index, _)| (chain, index)))
+  }
+
+  /// Look up coins on every watched script, marking funded indices as used
+  pub fn scan<C: ChainBackend>(&mut self, chain: &C) -> Result<Vec<(Utxo, KeyChain, u32)>, VBError> {
+    let mut owned = Vec::new();
+    for (key_chain, index, script) in self.watched_scripts()? {
+      let utxos = chain.unspent(&script)?;
+      if !utxos.is_empty() {
+        self.mark_used(key_chain, index);
+      }
+      owned.extend(utxos.into_iter().map(|u| (u, key_chain, index)));
+    }
+    Ok(owned)
+  }
+
+  /// Output descriptor for `chain`, e.g. `wpkh([d34db33f/84h/0h/0h]xpub.../0/*)#checksum`
+  pub fn descriptor(&self, chain: KeyChain) -> String {
+    let origin = match &self.origin {
+      Some((fp, path)) => {
+        let path = path.to_string().replace('\'', "h");
+        format!("[{}{}]", fp, path.trim_start_matches('m'))
+      }
+      None => String::new(),
+    };
+    let desc = format!("wpkh({}{}/{}/*)", origin, self.account_xpub(), chain.idx());
+    format!("{}#{}", desc, descriptor_checksum(&desc))
+  }
+
+  /// Sign P2WPKH input `input_index` spending `value` sats locked to `chain/index`
+  pub fn sign_input(
+    &self,
+    tx: &mut Transaction,
+    input_index: usize,
+    value: u64,
+    chain: KeyChain,
+    index: u32,
+  ) -> Result<(), VBError> {
+    let sk = self.derive_priv(chain, index)?;
+    let pk = sk.public_key(&self.secp);
+    let msg = p2wpkh_sighash(tx, input_index, &pk, value)?;
+    let sig = ecdsa::Signature::sighash_all(self.secp.sign_ecdsa(&msg, &sk.inner));
+    tx.input[input_index].witness = Witness::from_slice(&[sig.to_vec(), pk.to_bytes()]);
+    Ok(())
+  }
+
+  /// Add partial signatures to every PSBT input whose key path belongs to
+  /// this account, returning how many inputs were signed
+  pub fn sign_psbt(&self, psbt: &mut Psbt) -> Result<usize, VBError> {
+    let mut signed = 0;
+    for i in 0..psbt.inputs.len() {
+      let Some(value) = psbt.inputs[i].witness_utxo.as_ref().map(|u| u.value) else {
+        continue;
+      };
+      let ours = psbt.inputs[i].bip32_derivation.iter().find_map(|(pk, (fp, path))| {
+        let (chain, index) = chain_index(path)?;
+        let (own_pk, (own_fp, own_path)) = self.key_source(chain, index).ok()?;
+        (own_pk.inner == *pk && own_fp == *fp && own_path == *path).then_some((chain, index))
+      });
+      let Some((chain, index)) = ours else { continue };
+      let sk = self.derive_priv(chain, index)?;
+      let pk = sk.public_key(&self.secp);
+      let msg = p2wpkh_sighash(&psbt.unsigned_tx, i, &pk, value)?;
+      let sig = ecdsa::Signature::sighash_all(self.secp.sign_ecdsa(&msg, &sk.inner));
+      psbt.inputs[i].partial_sigs.insert(pk, sig);
+      signed += 1;
+    }
+    Ok(signed)
+  }
+}
+
+impl FromStr for HdWallet {
+  type Err = VBError;
+  /// Like [`HdWallet::from_xprv`], taking the network from the key prefix
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let network = decode_xprv(s)?.network;
+    Self::from_xprv(s, network)
+  }
+}
+
+impl fmt::Debug for HdWallet {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("HdWallet")
+      .field("account_xpub", &self.account_xpub())
+      .field("gap_limit", &self.gap_limit)
+      .field("next", &self.next)
+      .field("last_used", &self.last_used)
+      .finish()
+  }
+}
+
+/// Decode an extended private key, mapping SLIP-132 versions onto BIP-32 ones
+fn decode_xprv(s: &str) -> Result<ExtendedPrivKey, VBError> {
+  let mut data = Zeroizing::new(base58::decode_check(s.trim()).map_err(|_| VBError::Wallet("Invalid xprv".into()))?);
+  if data.len() < 4 {
+    return Err(VBError::Wallet("Invalid xprv".into()));
+  }
+  if data[0..4] == ZPRV_VERSION {
+    data[0..4].copy_from_slice(&XPRV_VERSION);
+  } else if data[0..4] == VPRV_VERSION {
+    data[0..4].copy_from_slice(&TPRV_VERSION);
+  }
+  ExtendedPrivKey::decode(&data).map_err(|_| VBError::Wallet("Invalid xprv".into()))
+}
+
+/// Decode an extended public key, mapping SLIP-132 versions onto BIP-32 ones
+fn decode_xpub(s: &str) -> Result<ExtendedPubKey, VBError> {
+  let mut data = base58::decode_check(s.trim()).map_err(|_| VBError::Wallet("Invalid xpub".into()))?;
+  if data.len() < 4 {
+    return Err(VBError::Wallet("Invalid xpub".into()));
+  }
+  if data[0..4] == ZPUB_VERSION {
+    data[0..4].copy_from_slice(&XPUB_VERSION);
+  } else if data[0..4] == VPUB_VERSION {
+    data[0..4].copy_from_slice(&TPUB_VERSION);
+  }
+  ExtendedPubKey::decode(&data).map_err(|_| VBError::Wallet("Invalid xpub".into()))
+}
+
+/// `chain/index` below the account key
+fn child_path(chain: KeyChain, index: u32) -> Result<[ChildNumber; 2], VBError> {
+  Ok([
+    ChildNumber::from_normal_idx(chain.idx() as u32).map_err(|_| VBError::Wallet("Invalid derivation index".into()))?,
+    ChildNumber::from_normal_idx(index).map_err(|_| VBError::Wallet("Invalid derivation index".into()))?,
+  ])
+}
+
+/// Inverse of `child_path` on the last two steps of a full key path
+fn chain_index(path: &DerivationPath) -> Option<(KeyChain, u32)> {
+  let steps: Vec<ChildNumber> = path.into_iter().copied().collect();
+  match steps.as_slice() {
+    [.., ChildNumber::Normal { index: 0 }, ChildNumber::Normal { index }] => Some((KeyChain::External, *index)),
+    [.., ChildNumber::Normal { index: 1 }, ChildNumber::Normal { index }] => Some((KeyChain::Internal, *index)),
+    _ => None,
+  }
+}
+
+/// `m/84'/0'/0'` on mainnet, `m/84'/1'/0'` everywhere else
+fn account_path(network: Network) -> DerivationPath {
+  let coin = if network == Network::Bitcoin { 0 } else { 1 };
+  DerivationPath::from(vec![
+    ChildNumber::Hardened { index: 84 },
+    ChildNumber::Hardened { index: coin },
+    ChildNumber::Hardened { index: 0 },
+  ])
+}
+
+/// BIP-380 descriptor checksum
+pub fn descriptor_checksum(desc: &str) -> String {
+  const INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+  const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+  fn polymod(c: u64, val: u64) -> u64 {
+    let c0 = c >> 35;
+    let mut c = ((c & 0x7_ffff_ffff) << 5) ^ val;
+    if c0 & 1 != 0 { c ^= 0xf5_dee5_1989; }
+    if c0 & 2 != 0 { c ^= 0xa9_fdca_3312; }
+    if c0 & 4 != 0 { c ^= 0x1b_ab10_e32d; }
+    if c0 & 8 != 0 { c ^= 0x37_06b1_677a; }
+    if c0 & 16 != 0 { c ^= 0x64_4d62_6ffd; }
+    c
+  }
+
+  let mut c = 1u64;
+  let mut cls = 0u64;
+  let mut clscount = 0;
+  for ch in desc.chars() {
+    let pos = INPUT_CHARSET.find(ch).unwrap_or(0) as u64;
+    c = polymod(c, pos & 31);
+    cls = cls * 3 + (pos >> 5);
+    clscount += 1;
+    if clscount == 3 {
+      c = polymod(c, cls);
+      cls = 0;
+      clscount = 0;
+    }
+  }
+  if clscount > 0 {
+    c = polymod(c, cls);
+  }
+  for _ in 0..8 {
+    c = polymod(c, 0);
+  }
+  c ^= 1;
+  (0..8)
+    .map(|j| CHECKSUM_CHARSET[((c >> (5 * (7 - j))) & 31) as usize] as char)
+    .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // BIP-84 test vector
+  const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+  const ZPRV_MASTER: &str = "zprvAWgYBBk7JR8Gjrh4UJQ2uJdG1r3WNRRfURiABBE3RvMXYSrRJL62XuezvGdPvG6GFBZduosCc1YP5wixPox7zhZLfiUm8aunE96BBa4Kei5";
+
+  #[test]
+  fn test_bip84_vectors_from_mnemonic() {
+    let w = HdWallet::from_mnemonic(MNEMONIC, "", Network::Bitcoin).unwrap();
+    assert_eq!(
+      w.address(KeyChain::External, 0).unwrap().to_string(),
+      "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+    );
+    assert_eq!(
+      w.address(KeyChain::Internal, 0).unwrap().to_string(),
+      "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+    );
+  }
+
+  #[test]
+  fn test_zprv_import_matches_mnemonic() {
+    let from_zprv = HdWallet::from_str(ZPRV_MASTER).unwrap();
+    let from_mnemonic = HdWallet::from_mnemonic(MNEMONIC, "", Network::Bitcoin).unwrap();
+    assert_eq!(from_zprv.account_xpub(), from_mnemonic.account_xpub());
+    assert_eq!(from_zprv.descriptor(KeyChain::External), from_mnemonic.descriptor(KeyChain::External));
+  }
+
+  #[test]
+  fn test_xprv_network_enforced() {
+    assert!(HdWallet::from_xprv(ZPRV_MASTER, Network::Bitcoin).is_ok());
+    assert!(HdWallet::from_xprv(ZPRV_MASTER, Network::Signet).is_err());
+
+    let testnet = HdWallet::from_mnemonic(MNEMONIC, "", Network::Testnet).unwrap();
+    let AccountKey::Private(account) = &testnet.account else { unreachable!() };
+    let tprv = account.to_string();
+    let regtest = HdWallet::from_xprv(&tprv, Network::Regtest).unwrap();
+    assert_eq!(regtest.network(), Network::Regtest);
+    assert!(regtest.address(KeyChain::External, 0).unwrap().to_string().starts_with("bcrt1q"));
+    assert!(HdWallet::from_xprv(&tprv, Network::Bitcoin).is_err());
+  }
+
+  #[test]
+  fn test_watch_only_derives_same_addresses() {
+    let full = HdWallet::from_mnemonic(MNEMONIC, "", Network::Bitcoin).unwrap();
+    let zpub = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+    let watch = HdWallet::watch_only(&format!("[73c5da0a/84h/0h/0h]{}", zpub), Network::Bitcoin).unwrap();
+    assert!(watch.is_watch_only());
+    assert_eq!(watch.address(KeyChain::External, 0).unwrap(), full.address(KeyChain::External, 0).unwrap());
+    assert_eq!(watch.descriptor(KeyChain::Internal), full.descriptor(KeyChain::Internal));
+    assert!(matches!(watch.derive_priv(KeyChain::External, 0), Err(VBError::Wallet(_))));
+  }
+
+  #[test]
+  fn test_descriptor() {
+    assert_eq!(descriptor_checksum("raw(deadbeef)"), "89f8spxm");
+    let w = HdWallet::from_mnemonic(MNEMONIC, "", Network::Bitcoin).unwrap();
+    let desc = w.descriptor(KeyChain::Internal);
+    assert!(desc.starts_with("wpkh([73c5da0a/84h/0h/0h]xpub"));
+    assert!(desc.contains("/1/*)#"));
+  }
+
+  #[test]
+  fn test_change_addresses_are_fresh() {
+    let mut w = HdWallet::from_mnemonic(MNEMONIC, "", Network::Bitcoin).unwrap();
+    let a = w.next_address(KeyChain::Internal).unwrap();
+    let b = w.next_address(KeyChain::Internal).unwrap();
+    assert_ne!(a, b);
+  }
+
+  #[test]
+  fn test_gap_limit() {
+    let mut w = HdWallet::from_mnemonic(MNEMONIC, "", Network::Bitcoin).unwrap().with_gap_limit(2);
+    w.next_index(KeyChain::External).unwrap();
+    w.next_index(KeyChain::External).unwrap();
+    assert!(matches!(w.next_index(KeyChain::External), Err(VBError::ResourceExhausted(_))));
+    w.mark_used(KeyChain::External, 1);
+    assert_eq!(w.next_index(KeyChain::External).unwrap(), 2);
+    // receive: indices 0..=1 used plus a gap of 2; change: just the gap
+    assert_eq!(w.watched_scripts().unwrap().len(), 4 + 2);
+  }
+}
@@ -0,0 +1,218 @@
+use crate::chain::{find_payload, prevouts, unconfirmed_tx, ChainBackend, Utxo};
+use crate::hd::{HdWallet, KeyChain};
+use crate::tx::{build_anchor_tx, build_cpfp_tx, build_rbf_tx, tx_fee};
+use crate::{AnchorTx, FeeBump, FeeCalc, WalletAdapter};
+use bitcoin::{OutPoint, Txid};
+use validblock_types::VBError;
+
+/// Wallet holding HD keys online: funds, signs and broadcasts anchors itself
+#[derive(Debug)]
+pub struct HotWallet<C: ChainBackend> {
+  hd: HdWallet,
+  chain: C,
+  fee: FeeCalc,
+}
+
+impl<C: ChainBackend> HotWallet<C> {
+  pub fn new(hd: HdWallet, chain: C, fee: FeeCalc) -> Self {
+    Self { hd, chain, fee }
+  }
+
+  pub fn hd(&self) -> &HdWallet {
+    &self.hd
+  }
+
+  pub fn chain(&self) -> &C {
+    &self.chain
+  }
+
+  /// Look up coins on every watched script, marking funded indices as used
+  pub fn sync(&mut self) -> Result<Vec<(Utxo, KeyChain, u32)>, VBError> {
+    self.hd.scan(&self.chain)
+  }
+}
+
+impl<C: ChainBackend> WalletAdapter for HotWallet<C> {
+  fn anchor(&mut self, payload: &[u8]) -> Result<AnchorTx, VBError> {
+    self.anchor_with_fee(payload, self.fee)
+  }
+
+  fn anchor_with_fee(&mut self, payload: &[u8], fee: FeeCalc) -> Result<AnchorTx, VBError> {
+    let owned = self.sync()?;
+    let change_index = self.hd.next_index(KeyChain::Internal)?;
+    let change_script = self.hd.address(KeyChain::Internal, change_index)?.script_pubkey();
+
+    let utxos: Vec<Utxo> = owned.iter().map(|(u, _, _)| u.clone()).collect();
+    let (mut tx, spent) = build_anchor_tx(&utxos, payload, change_script, &fee)?;
+    for (i, coin) in spent.iter().enumerate() {
+      let (_, chain, index) = owned
+        .iter()
+        .find(|(u, _, _)| u.outpoint == coin.outpoint)
+        .ok_or_else(|| VBError::Wallet("Selected coin is not in the wallet".into()))?;
+      self.hd.sign_input(&mut tx, i, coin.txout.value, *chain, *index)?;
+    }
+
+    let txid = self.chain.broadcast(&tx)?;
+    self.hd.mark_used(KeyChain::Internal, change_index);
+    Ok(AnchorTx::Broadcast(txid))
+  }
+
+  fn bump_fee(&mut self, txid: &Txid, method: FeeBump, fee: FeeCalc) -> Result<AnchorTx, VBError> {
+    let original = unconfirmed_tx(&self.chain, txid)?;
+    let spent = prevouts(&self.chain, &original)?;
+    match method {
+      FeeBump::Rbf => {
+        let mut tx = build_rbf_tx(&original, &spent, &fee)?;
+        for (i, prev) in spent.iter().enumerate() {
+          let (chain, index) = self.hd.owner_of(&prev.script_pubkey)?.ok_or_else(|| VBError::Wallet("Input is not owned by this wallet".into()))?;
+          self.hd.sign_input(&mut tx, i, prev.value, chain, index)?;
+        }
+        Ok(AnchorTx::Broadcast(self.chain.broadcast(&tx)?))
+      }
+      FeeBump::Cpfp => {
+        let (vout, (chain, index)) = original
+          .output
+          .iter()
+          .enumerate()
+          .find_map(|(vout, o)| Some((vout, self.hd.owner_of(&o.script_pubkey).ok()??)))
+          .ok_or_else(|| VBError::Wallet("Anchor transaction has no change output to spend".into()))?;
+        let change = Utxo {
+          outpoint: OutPoint::new(*txid, vout as u32),
+          txout: original.output[vout].clone(),
+        };
+        let dest_index = self.hd.next_index(KeyChain::Internal)?;
+        let dest = self.hd.address(KeyChain::Internal, dest_index)?.script_pubkey();
+        let mut child = build_cpfp_tx(&original, tx_fee(&original, &spent)?, &change, dest, &fee)?;
+        self.hd.sign_input(&mut child, 0, change.txout.value, chain, index)?;
+        let child_txid = self.chain.broadcast(&child)?;
+        self.hd.mark_used(KeyChain::Internal, dest_index);
+        Ok(AnchorTx::Broadcast(child_txid))
+      }
+    }
+  }
+
+  /// Anchors spend the wallet's coins, so their transactions show up in the
+  /// history of its scripts
+  fn find_anchor(&self, payload: &[u8]) -> Result<Option<Txid>, VBError> {
+    let scripts = self.hd.watched_scripts()?;
+    find_payload(&self.chain, scripts.iter().map(|(_, _, script)| script.as_script()), payload)
+  }
+
+  fn confirmed_height(&self, txid: &Txid) -> Result<Option<u32>, VBError> {
+    self.chain.confirmed_height(txid)
+  }
+
+  fn balance(&mut self) -> Result<Option<u64>, VBError> {
+    Ok(Some(self.sync()?.iter().map(|(u, _, _)| u.txout.value).sum()))
+  }
+
+  fn ping(&self) -> Result<(), VBError> {
+    self.chain.block_hash(0).map(|_| ())
+  }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::mock::{MockChain, TEST_MNEMONIC};
+  use bitcoin::Network;
+
+  #[test]
+  fn test_anchor_uses_fresh_change_address() {
+    let (hd, chain) = MockChain::funded_hd(100_000);
+    let mut wallet = HotWallet::new(hd, chain, FeeCalc::fixed(2));
+
+    wallet.anchor(&[7u8; 32]).unwrap();
+    wallet.anchor(&[8u8; 32]).unwrap();
+
+    let txs = wallet.chain().broadcasts();
+    assert_eq!(txs.len(), 2);
+    assert!(txs[0].output[0].script_pubkey.is_op_return());
+    assert_ne!(txs[0].output[1].script_pubkey, txs[1].output[1].script_pubkey);
+    // second anchor spends the first one's change
+    assert_eq!(txs[1].input[0].previous_output.txid, txs[0].txid());
+    assert!(txs.iter().all(|tx| tx.input.iter().all(|i| i.witness.len() == 2)));
+  }
+
+  fn funded_wallet() -> HotWallet<MockChain> {
+    let (hd, chain) = MockChain::funded_hd(100_000);
+    HotWallet::new(hd, chain, FeeCalc::fixed(1))
+  }
+
+  #[test]
+  fn test_rbf_replaces_anchor() {
+    let mut wallet = funded_wallet();
+    let first = wallet.anchor(&[7u8; 32]).unwrap().txid();
+    let replacement = wallet.bump_fee(&first, FeeBump::Rbf, FeeCalc::fixed(20)).unwrap().txid();
+    assert_ne!(first, replacement);
+
+    let txs = wallet.chain().broadcasts();
+    let (old, new) = (&txs[0], &txs[1]);
+    assert_eq!(new.input[0].previous_output, old.input[0].previous_output);
+    assert_eq!(new.output[0], old.output[0]);
+    assert!(new.output[1].value < old.output[1].value);
+    // the original was evicted from the mempool
+    assert!(wallet.chain().transaction(&first).unwrap().is_none());
+    wallet.chain().mine();
+    assert!(wallet.bump_fee(&replacement, FeeBump::Rbf, FeeCalc::fixed(30)).is_err());
+  }
+
+  #[test]
+  fn test_cpfp_spends_change() {
+    let mut wallet = funded_wallet();
+    let parent = wallet.anchor(&[7u8; 32]).unwrap().txid();
+    let child = wallet.bump_fee(&parent, FeeBump::Cpfp, FeeCalc::fixed(20)).unwrap().txid();
+
+    let txs = wallet.chain().broadcasts();
+    assert_eq!(txs[1].txid(), child);
+    assert_eq!(txs[1].input[0].previous_output, OutPoint::new(parent, 1));
+    let parent_fee = 100_000 - txs[0].output[1].value;
+    let child_fee = txs[0].output[1].value - txs[1].output[0].value;
+    let package_vb = (txs[0].weight() + txs[1].weight()).to_vbytes_ceil();
+    assert!(parent_fee + child_fee >= 20 * package_vb);
+  }
+
+  #[test]
+  fn test_find_anchor() {
+    let mut wallet = funded_wallet();
+    let txid = wallet.anchor(&[7u8; 32]).unwrap().txid();
+    wallet.chain().mine();
+    wallet.anchor(&[8u8; 32]).unwrap();
+    assert_eq!(wallet.find_anchor(&[7u8; 32]).unwrap(), Some(txid));
+    assert!(wallet.find_anchor(&[8u8; 32]).unwrap().is_some());
+    assert_eq!(wallet.find_anchor(&[9u8; 32]).unwrap(), None);
+  }
+
+  #[test]
+  fn test_anchor_with_fee_overrides_rate() {
+    let mut wallet = funded_wallet();
+    wallet.anchor(&[7u8; 32]).unwrap();
+    wallet.anchor_with_fee(&[8u8; 32], FeeCalc::fixed(10)).unwrap();
+
+    let txs = wallet.chain().broadcasts();
+    let first_fee = 100_000 - txs[0].output[1].value;
+    let second_fee = txs[0].output[1].value - txs[1].output[1].value;
+    assert!(first_fee < 10 * txs[0].weight().to_vbytes_ceil());
+    assert!(second_fee >= 10 * txs[1].weight().to_vbytes_ceil());
+  }
+
+  #[test]
+  fn test_anchor_insufficient_funds() {
+    let hd = HdWallet::from_mnemonic(TEST_MNEMONIC, "", Network::Regtest).unwrap();
+    let mut wallet = HotWallet::new(hd, MockChain::default(), FeeCalc::fixed(2));
+    assert!(wallet.anchor(&[7u8; 32]).is_err());
+  }
+
+  #[test]
+  fn test_balance_sums_owned_coins() {
+    let (hd, chain) = MockChain::funded_hd(60_000);
+    chain.fund(&hd.address(KeyChain::Internal, 3).unwrap().script_pubkey(), 40_000);
+    let mut wallet = HotWallet::new(hd, chain, FeeCalc::fixed(2));
+    assert_eq!(wallet.balance().unwrap(), Some(100_000));
+    assert!(wallet.ping().is_ok());
+  }
+}
@@ -0,0 +1,301 @@
+use crate::hd::HdWallet;
+use crate::{Network, WpkhKey};
+use bitcoin::base64;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use validblock_types::VBError;
+use zeroize::Zeroizing;
+
+/// Current on-disk format version
+pub const KEYSTORE_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// Passphrase stretching parameters, stored alongside the ciphertext
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase", tag = "alg")]
+pub enum Kdf {
+  Scrypt { log_n: u8, r: u32, p: u32 },
+  Argon2id { m_cost: u32, t_cost: u32, p_cost: u32 },
+}
+
+impl Default for Kdf {
+  /// scrypt N=2^15, r=8, p=1: ~32 MiB and well under a second
+  fn default() -> Self {
+    Kdf::Scrypt { log_n: 15, r: 8, p: 1 }
+  }
+}
+
+impl Kdf {
+  fn derive_key(&self, passphrase: &str, salt: &[u8]) -> Result<Zeroizing<[u8; KEY_LEN]>, VBError> {
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    match *self {
+      Kdf::Scrypt { log_n, r, p } => {
+        let params = scrypt::Params::new(log_n, r, p, KEY_LEN).map_err(|e| VBError::Other(e.to_string()))?;
+        scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut *key).map_err(|e| VBError::Other(e.to_string()))?;
+      }
+      Kdf::Argon2id { m_cost, t_cost, p_cost } => {
+        let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(KEY_LEN))
+          .map_err(|e| VBError::Other(e.to_string()))?;
+        argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+          .hash_password_into(passphrase.as_bytes(), salt, &mut *key)
+          .map_err(|e| VBError::Other(e.to_string()))?;
+      }
+    }
+    Ok(key)
+  }
+}
+
+/// Key material in memory: wiped on drop and never printed
+#[derive(Clone)]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+  pub fn new(s: &str) -> Self {
+    Secret(Zeroizing::new(s.trim().to_string()))
+  }
+
+  /// The plaintext; keep the borrow short
+  pub fn expose(&self) -> &str {
+    &self.0
+  }
+
+  /// Open the secret as an HD wallet: a BIP-39 mnemonic or an xprv/zprv
+  pub fn hd_wallet(&self, network: Network) -> Result<HdWallet, VBError> {
+    if self.0.contains(' ') {
+      HdWallet::from_mnemonic(&self.0, "", network)
+    } else {
+      HdWallet::from_xprv(&self.0, network)
+    }
+  }
+
+  /// Open the secret as a single WIF key
+  pub fn wpkh_key(&self, network: Network) -> Result<WpkhKey, VBError> {
+    WpkhKey::from_wif(&self.0, network)
+  }
+}
+
+impl fmt::Debug for Secret {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("Secret(<redacted>)")
+  }
+}
+
+impl fmt::Display for Secret {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("<redacted>")
+  }
+}
+
+/// Passphrase-encrypted secret (mnemonic, xprv or WIF) as stored on disk.
+///
+/// The key is stretched with `kdf` and the secret sealed with
+/// XChaCha20-Poly1305; the version and KDF parameters are authenticated too.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+  pub version: u8,
+  pub kdf: Kdf,
+  salt: String,
+  nonce: String,
+  ciphertext: String,
+}
+
+impl Keystore {
+  pub fn encrypt(secret: &Secret, passphrase: &str, kdf: Kdf) -> Result<Self, VBError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = kdf.derive_key(passphrase, &salt)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let aad = associated_data(KEYSTORE_VERSION, &kdf)?;
+    let ciphertext = XChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&key[..]))
+      .encrypt(&nonce, Payload { msg: secret.expose().as_bytes(), aad: &aad })
+      .map_err(|_| VBError::Wallet("Encryption failed".into()))?;
+    Ok(Keystore {
+      version: KEYSTORE_VERSION,
+      kdf,
+      salt: base64::encode(salt),
+      nonce: base64::encode(nonce),
+      ciphertext: base64::encode(ciphertext),
+    })
+  }
+
+  pub fn decrypt(&self, passphrase: &str) -> Result<Secret, VBError> {
+    if self.version != KEYSTORE_VERSION {
+      return Err(VBError::Other(format!("Unsupported keystore version {}", self.version)));
+    }
+    let salt = decode_field(&self.salt)?;
+    let nonce = decode_field(&self.nonce)?;
+    if nonce.len() != 24 {
+      return Err(VBError::Other("Corrupt keystore".into()));
+    }
+    let key = self.kdf.derive_key(passphrase, &salt)?;
+    let aad = associated_data(self.version, &self.kdf)?;
+    let plaintext = XChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&key[..]))
+      .decrypt(XNonce::from_slice(&nonce), Payload { msg: &decode_field(&self.ciphertext)?, aad: &aad })
+      .map(Zeroizing::new)
+      .map_err(|_| VBError::Unauthenticated("Wrong passphrase or corrupt keystore".into()))?;
+    let s = std::str::from_utf8(&plaintext).map_err(|_| VBError::Other("Corrupt keystore".into()))?;
+    Ok(Secret::new(s))
+  }
+
+  pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, VBError> {
+    let data = std::fs::read(path)?;
+    serde_json::from_slice(&data).map_err(|e| VBError::Other(format!("Invalid keystore file: {}", e)))
+  }
+
+  /// Write the keystore to `path`, which only its owner may read on Unix
+  pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), VBError> {
+    let data = serde_json::to_vec_pretty(self).map_err(|e| VBError::Other(e.to_string()))?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // an existing file keeps its mode when opened
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(&data)?;
+    Ok(())
+  }
+}
+
+fn associated_data(version: u8, kdf: &Kdf) -> Result<Vec<u8>, VBError> {
+  let mut aad = vec![version];
+  aad.extend(serde_json::to_vec(kdf).map_err(|e| VBError::Other(e.to_string()))?);
+  Ok(aad)
+}
+
+fn decode_field(s: &str) -> Result<Vec<u8>, VBError> {
+  base64::decode(s).map_err(|_| VBError::Other("Corrupt keystore".into()))
+}
+
+/// Decrypted secret held for a limited time; it is wiped on expiry, on
+/// `lock` and on drop
+#[derive(Debug, Default)]
+pub struct KeystoreSession {
+  unlocked: Option<(Secret, Instant)>,
+}
+
+impl KeystoreSession {
+  /// Decrypt `store` and keep the secret for `timeout`
+  pub fn unlock(&mut self, store: &Keystore, passphrase: &str, timeout: Duration) -> Result<(), VBError> {
+    let secret = store.decrypt(passphrase)?;
+    self.unlocked = Some((secret, Instant::now() + timeout));
+    Ok(())
+  }
+
+  pub fn lock(&mut self) {
+    self.unlocked = None;
+  }
+
+  /// The secret, unless the session has timed out
+  pub fn secret(&mut self) -> Option<&Secret> {
+    if self.remaining().is_none() {
+      self.lock();
+    }
+    self.unlocked.as_ref().map(|(secret, _)| secret)
+  }
+
+  pub fn is_unlocked(&mut self) -> bool {
+    self.secret().is_some()
+  }
+
+  /// Time left before the session locks itself
+  pub fn remaining(&self) -> Option<Duration> {
+    let (_, expires) = self.unlocked.as_ref()?;
+    expires.checked_duration_since(Instant::now()).filter(|d| !d.is_zero())
+  }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::hd::KeyChain;
+  use tempfile::tempdir;
+
+  const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+  // light parameters keep the tests fast
+  const SCRYPT: Kdf = Kdf::Scrypt { log_n: 8, r: 8, p: 1 };
+  const ARGON2: Kdf = Kdf::Argon2id { m_cost: 256, t_cost: 1, p_cost: 1 };
+
+  #[test]
+  fn test_roundtrip_both_kdfs() {
+    for kdf in [SCRYPT, ARGON2] {
+      let store = Keystore::encrypt(&Secret::new(MNEMONIC), "hunter2", kdf).unwrap();
+      assert!(!store.ciphertext.contains("abandon"));
+      assert_eq!(store.decrypt("hunter2").unwrap().expose(), MNEMONIC);
+      assert!(matches!(store.decrypt("hunter3"), Err(VBError::Unauthenticated(_))));
+    }
+  }
+
+  #[test]
+  fn test_tampered_params_rejected() {
+    let mut store = Keystore::encrypt(&Secret::new(MNEMONIC), "pw", SCRYPT).unwrap();
+    store.kdf = Kdf::Scrypt { log_n: 9, r: 8, p: 1 };
+    assert!(store.decrypt("pw").is_err());
+  }
+
+  #[test]
+  fn test_save_load_and_open_wallet() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("keystore.json");
+    Keystore::encrypt(&Secret::new(MNEMONIC), "pw", SCRYPT).unwrap().save(&path).unwrap();
+    let secret = Keystore::load(&path).unwrap().decrypt("pw").unwrap();
+    let hd = secret.hd_wallet(Network::Bitcoin).unwrap();
+    assert_eq!(
+      hd.address(KeyChain::External, 0).unwrap().to_string(),
+      "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_saved_owner_only() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("keystore.json");
+    let store = Keystore::encrypt(&Secret::new(MNEMONIC), "pw", SCRYPT).unwrap();
+    store.save(&path).unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+    // tightened when overwriting a file others could read
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+    store.save(&path).unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+  }
+
+  #[test]
+  fn test_secret_redacted() {
+    let secret = Secret::new(MNEMONIC);
+    assert!(!format!("{:?}", secret).contains("abandon"));
+    assert!(!secret.to_string().contains("abandon"));
+  }
+
+  #[test]
+  fn test_session_times_out() {
+    let store = Keystore::encrypt(&Secret::new(MNEMONIC), "pw", SCRYPT).unwrap();
+    let mut session = KeystoreSession::default();
+    assert!(session.unlock(&store, "wrong", Duration::from_secs(60)).is_err());
+    assert!(!session.is_unlocked());
+
+    session.unlock(&store, "pw", Duration::from_secs(60)).unwrap();
+    assert_eq!(session.secret().unwrap().expose(), MNEMONIC);
+    session.lock();
+    assert!(session.secret().is_none());
+
+    session.unlock(&store, "pw", Duration::ZERO).unwrap();
+    assert!(!session.is_unlocked());
+  }
+}
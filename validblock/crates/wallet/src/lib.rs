@@ -1,30 +1,64 @@
 #![forbid(unsafe_code)]
 
-use bitcoin::{Address, Network, PrivateKey};
+use bitcoin::{Address, PrivateKey, Weight};
 use std::str::FromStr;
 use validblock_types::VBError;
+use zeroize::Zeroizing;
 
+pub mod chain;
+pub mod esplora;
+pub mod hd;
+pub mod hot;
+pub mod keystore;
 pub mod mock;
+pub mod psbt;
+pub mod tx;
+pub mod verify;
+pub mod watch;
+
+pub use bitcoin::{Network, Transaction, Txid};
+pub use chain::{ChainBackend, MerkleProof, Utxo};
+pub use hd::{HdWallet, KeyChain};
+pub use hot::HotWallet;
+pub use keystore::{Keystore, KeystoreSession, Secret};
+pub use psbt::Psbt;
+pub use verify::{verify_anchor, Verdict};
+pub use watch::WatchOnlyWallet;
+
+/// Parse a network name: `bitcoin` (or `mainnet`), `testnet`, `signet`, `regtest`
+pub fn parse_network(s: &str) -> Result<Network, VBError> {
+  match s.trim().to_ascii_lowercase().as_str() {
+    "mainnet" | "main" => Ok(Network::Bitcoin),
+    other => Network::from_str(other).map_err(|_| VBError::InvalidArgument(format!("Unknown network: {}", s))),
+  }
+}
+
+/// Keys only encode mainnet vs. test: testnet, signet and regtest share
+/// WIF and xprv prefixes
+pub(crate) fn key_network_matches(key_network: Network, network: Network) -> bool {
+  (key_network == Network::Bitcoin) == (network == Network::Bitcoin)
+}
 
 /// Trait for address validation
 pub trait AddressExt {
-  fn validate_p2wpkh(s: &str) -> Result<Address<bitcoin::address::NetworkChecked>, VBError>;
+  fn validate_p2wpkh(s: &str, network: Network) -> Result<Address<bitcoin::address::NetworkChecked>, VBError>;
 }
 
 impl AddressExt for Address {
-  fn validate_p2wpkh(s: &str) -> Result<Address<bitcoin::address::NetworkChecked>, VBError> {
-    let addr = Address::from_str(s).map_err(|_| VBError::Wallet)?;
-    let checked = addr.require_network(Network::Bitcoin).map_err(|_| VBError::Wallet)?;
+  fn validate_p2wpkh(s: &str, network: Network) -> Result<Address<bitcoin::address::NetworkChecked>, VBError> {
+    let addr = Address::from_str(s).map_err(|e| VBError::Wallet(format!("Invalid address: {}", e)))?;
+    let checked = addr.require_network(network).map_err(|_| VBError::Wallet("Address is for another network".into()))?;
     if checked.script_pubkey().is_v0_p2wpkh() {
       Ok(checked)
     } else {
-      Err(VBError::Wallet)
+      Err(VBError::Wallet("Not a P2WPKH address".into()))
     }
   }
 }
 
-/// Wrapper for WPKH private key
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Wrapper for WPKH private key; `Debug`/`Display` never show the key and
+/// it is wiped on drop
+#[derive(Clone, PartialEq, Eq)]
 pub struct WpkhKey(pub PrivateKey);
 
 impl FromStr for WpkhKey {
@@ -35,26 +69,164 @@ impl FromStr for WpkhKey {
         return Ok(WpkhKey(pk));
     }
     // For now, only WIF is supported
-    Err(VBError::Wallet)
+    Err(VBError::Wallet("Not a WIF key".into()))
+  }
+}
+
+impl WpkhKey {
+  /// Parse a WIF key, rejecting keys for another network
+  pub fn from_wif(s: &str, network: Network) -> Result<Self, VBError> {
+    let key = WpkhKey::from_str(s)?;
+    if key_network_matches(key.0.network, network) {
+      Ok(key)
+    } else {
+      Err(VBError::Wallet("Key is for another network".into()))
+    }
+  }
+}
+
+impl WpkhKey {
+  /// WIF encoding of the key, for export into a [`Keystore`]
+  pub fn to_wif(&self) -> Zeroizing<String> {
+    Zeroizing::new(self.0.to_wif())
+  }
+}
+
+impl Drop for WpkhKey {
+  fn drop(&mut self) {
+    self.0.inner.non_secure_erase();
+  }
+}
+
+impl std::fmt::Debug for WpkhKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "WpkhKey({}, <redacted>)", self.0.network)
   }
 }
 
 impl std::fmt::Display for WpkhKey {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(f, "{}", self.0.to_wif())
+    f.write_str("<redacted>")
   }
 }
 
-/// Fee calculation stub
-pub struct FeeCalc;
+/// Fee rate used when funding anchor transactions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeCalc {
+  sat_per_vb: u64,
+}
+
 impl FeeCalc {
-  pub fn fixed(_fee_sat_per_vb: u16) -> Self {
-    FeeCalc
+  pub fn fixed(fee_sat_per_vb: u16) -> Self {
+    FeeCalc { sat_per_vb: fee_sat_per_vb as u64 }
+  }
+
+  pub fn sat_per_vb(&self) -> u64 {
+    self.sat_per_vb
+  }
+
+  /// Fee in sats for a transaction of `weight`
+  pub fn fee_for(&self, weight: Weight) -> u64 {
+    weight.to_vbytes_ceil() * self.sat_per_vb
   }
 }
 
-/// WalletAdapter trait
-pub trait WalletAdapter {}
+/// Outcome of asking a wallet to anchor a payload
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnchorTx {
+  /// Signed and broadcast
+  Broadcast(Txid),
+  /// Built but must be signed elsewhere, then handed to
+  /// [`WalletAdapter::broadcast_signed`]
+  AwaitingSignature(Psbt),
+}
+
+impl AnchorTx {
+  /// Segwit txids don't commit to signatures, so this is final even for an unsigned PSBT
+  pub fn txid(&self) -> Txid {
+    match self {
+      AnchorTx::Broadcast(txid) => *txid,
+      AnchorTx::AwaitingSignature(psbt) => psbt.unsigned_tx.txid(),
+    }
+  }
+}
+
+/// How to speed up a stuck anchor transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeBump {
+  /// Replace it (BIP-125) with the same payload at a higher fee
+  Rbf,
+  /// Spend its change in a child paying for both (child-pays-for-parent)
+  Cpfp,
+}
+
+impl FromStr for FeeBump {
+  type Err = VBError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_lowercase().as_str() {
+      "rbf" => Ok(FeeBump::Rbf),
+      "cpfp" => Ok(FeeBump::Cpfp),
+      _ => Err(VBError::InvalidArgument(format!("Unknown fee bump method: {}", s))),
+    }
+  }
+}
+
+/// Wallet backend that commits anchor payloads to the chain
+pub trait WalletAdapter {
+  /// Build a transaction carrying `payload` in an OP_RETURN output and
+  /// broadcast it, or hand it back for signing
+  fn anchor(&mut self, payload: &[u8]) -> Result<AnchorTx, VBError>;
+
+  /// [`WalletAdapter::anchor`] at `fee` instead of the wallet's own rate;
+  /// wallets that pay no fees ignore it
+  fn anchor_with_fee(&mut self, payload: &[u8], _fee: FeeCalc) -> Result<AnchorTx, VBError> {
+    self.anchor(payload)
+  }
+
+  /// Finalize and broadcast a PSBT from [`AnchorTx::AwaitingSignature`]
+  /// once it has been signed externally
+  fn broadcast_signed(&mut self, _psbt: Psbt) -> Result<Txid, VBError> {
+    Err(VBError::Wallet("Wallet does not accept externally signed transactions".into()))
+  }
+
+  /// Hold back the coins spent by `pending`, the PSBTs still out for
+  /// signing, in place of any held back before; wallets that don't build
+  /// PSBTs ignore them
+  fn restore_pending(&mut self, _pending: &[Psbt]) {}
+
+  /// Give back the coins of a PSBT that will never be signed
+  fn release(&mut self, _psbt: &Psbt) {}
+
+  /// Raise the fee of the unconfirmed anchor `txid` to `fee`. With
+  /// [`FeeBump::Rbf`] the result replaces the anchor transaction; with
+  /// [`FeeBump::Cpfp`] it is the child and the anchor txid stays the same
+  fn bump_fee(&mut self, _txid: &Txid, _method: FeeBump, _fee: FeeCalc) -> Result<AnchorTx, VBError> {
+    Err(VBError::Wallet("Wallet does not support fee bumping".into()))
+  }
+
+  /// Transaction of this wallet already carrying `payload`, to settle an
+  /// anchor whose broadcast outcome was lost; wallets without a chain never
+  /// find one
+  fn find_anchor(&self, _payload: &[u8]) -> Result<Option<Txid>, VBError> {
+    Ok(None)
+  }
+
+  /// Height of the block confirming `txid`; wallets without a chain never
+  /// see one
+  fn confirmed_height(&self, _txid: &Txid) -> Result<Option<u32>, VBError> {
+    Ok(None)
+  }
+
+  /// Satoshis held by the wallet, `None` for wallets without funds
+  fn balance(&mut self) -> Result<Option<u64>, VBError> {
+    Ok(None)
+  }
+
+  /// Fail unless the wallet's backend answers
+  fn ping(&self) -> Result<(), VBError> {
+    Ok(())
+  }
+}
 
 // ============================================================================
 // Tests
@@ -63,40 +235,54 @@ pub trait WalletAdapter {}
 #[cfg(test)]
 mod tests {
   use super::*;
-  use bitcoin::Network;
 
   #[test]
   fn test_validate_p2wpkh_valid() {
-    // Mainnet P2WPKH
     let addr_mainnet = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
-    let addr_testnet = "tb1qg3hss5p9g9jp0es5u5aaz3lszf6cvdggtmjarr";
-    let res_mainnet = Address::from_str(addr_mainnet);
-    let res_testnet = Address::from_str(addr_testnet);
-    println!("mainnet parse: {:?}", res_mainnet);
-    println!("testnet parse: {:?}", res_testnet);
-    if let Ok(a) = res_mainnet {
-      println!("mainnet address network: {:?}", a.network);
-    }
-    if let Ok(a) = res_testnet {
-      println!("testnet address network: {:?}", a.network);
-    }
-    match Address::validate_p2wpkh(addr_mainnet) {
-      Ok(a) => assert_eq!(a.network, Network::Bitcoin),
-      Err(e) => println!("validate_p2wpkh mainnet failed: {:?}", e),
-    }
-    match Address::validate_p2wpkh(addr_testnet) {
-      Ok(a) => assert_eq!(a.network, Network::Testnet),
-      Err(e) => println!("validate_p2wpkh testnet failed: {:?}", e),
-    }
+    let addr_testnet = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+    let addr_regtest = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+    assert_eq!(Address::validate_p2wpkh(addr_mainnet, Network::Bitcoin).unwrap().network, Network::Bitcoin);
+    assert_eq!(Address::validate_p2wpkh(addr_testnet, Network::Testnet).unwrap().network, Network::Testnet);
+    // signet shares testnet's bech32 prefix
+    assert!(Address::validate_p2wpkh(addr_testnet, Network::Signet).is_ok());
+    assert_eq!(Address::validate_p2wpkh(addr_regtest, Network::Regtest).unwrap().network, Network::Regtest);
+  }
+
+  #[test]
+  fn test_validate_p2wpkh_wrong_network() {
+    let addr_mainnet = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+    let addr_testnet = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+    assert!(Address::validate_p2wpkh(addr_mainnet, Network::Testnet).is_err());
+    assert!(Address::validate_p2wpkh(addr_testnet, Network::Bitcoin).is_err());
+    assert!(Address::validate_p2wpkh(addr_testnet, Network::Regtest).is_err());
+  }
+
+  #[test]
+  fn test_parse_network() {
+    assert_eq!(parse_network("mainnet").unwrap(), Network::Bitcoin);
+    assert_eq!(parse_network("bitcoin").unwrap(), Network::Bitcoin);
+    assert_eq!(parse_network("Signet").unwrap(), Network::Signet);
+    assert_eq!(parse_network("regtest").unwrap(), Network::Regtest);
+    assert!(parse_network("litecoin").is_err());
+  }
+
+  #[test]
+  fn test_wpkhkey_from_wif_enforces_network() {
+    let wif_mainnet = "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617";
+    let wif_testnet = "cVt4o7BGAig1UXywgGSmARhxMdzP5qvQsxKkSsc1XEkw3tDTQFpy";
+    assert!(WpkhKey::from_wif(wif_mainnet, Network::Bitcoin).is_ok());
+    assert!(WpkhKey::from_wif(wif_mainnet, Network::Regtest).is_err());
+    assert!(WpkhKey::from_wif(wif_testnet, Network::Signet).is_ok());
+    assert!(WpkhKey::from_wif(wif_testnet, Network::Bitcoin).is_err());
   }
 
   #[test]
   fn test_validate_p2wpkh_invalid() {
     // Not a P2WPKH
     let addr = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa";
-    assert!(Address::validate_p2wpkh(addr).is_err());
+    assert!(Address::validate_p2wpkh(addr, Network::Bitcoin).is_err());
     // Invalid string
-    assert!(Address::validate_p2wpkh("notanaddress").is_err());
+    assert!(Address::validate_p2wpkh("notanaddress", Network::Bitcoin).is_err());
   }
 
   #[test]
@@ -108,12 +294,20 @@ mod tests {
     println!("mainnet wif parse: {:?}", res_mainnet);
     println!("testnet wif parse: {:?}", res_testnet);
     match WpkhKey::from_str(wif_mainnet) {
-      Ok(key) => assert_eq!(key.to_string(), wif_mainnet),
+      Ok(key) => assert_eq!(*key.to_wif(), wif_mainnet),
       Err(e) => println!("WpkhKey::from_str mainnet failed: {:?}", e),
     }
     match WpkhKey::from_str(wif_testnet) {
-      Ok(key) => assert_eq!(key.to_string(), wif_testnet),
+      Ok(key) => assert_eq!(*key.to_wif(), wif_testnet),
       Err(e) => println!("WpkhKey::from_str testnet failed: {:?}", e),
     }
   }
+
+  #[test]
+  fn test_wpkhkey_redacted() {
+    let wif = "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617";
+    let key = WpkhKey::from_str(wif).unwrap();
+    assert_eq!(key.to_string(), "<redacted>");
+    assert!(!format!("{:?}", key).contains(wif));
+  }
 } 
\ No newline at end of file
@@ -1,6 +1,305 @@
-use crate::WalletAdapter;
+use crate::chain::{ChainBackend, MerkleProof, Utxo};
+use crate::hd::{HdWallet, KeyChain};
+use crate::{AnchorTx, WalletAdapter};
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::absolute::LockTime;
+use bitcoin::block::{Block, Header, Version};
+use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::{BlockHash, CompactTarget, Network, OutPoint, Script, Transaction, TxIn, TxOut, Txid};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use validblock_types::VBError;
+
+/// BIP-39 test vector the wallets of [`MockChain::funded_hd`] derive from
+pub const TEST_MNEMONIC: &str =
+  "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
 
 #[derive(Default, Debug, Clone)]
 pub struct MockWallet;
 
-impl WalletAdapter for MockWallet {}
+impl MockWallet {
+  /// Fake signature: double-SHA256 of the message
+  pub fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, VBError> {
+    Ok(sha256d::Hash::hash(msg).to_byte_array().to_vec())
+  }
+
+  pub fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<bool, VBError> {
+    Ok(self.sign(msg)? == sig)
+  }
+}
+
+impl WalletAdapter for MockWallet {
+  /// Nothing is broadcast; the txid is derived from the payload
+  fn anchor(&mut self, payload: &[u8]) -> Result<AnchorTx, VBError> {
+    Ok(AnchorTx::Broadcast(Txid::from_raw_hash(sha256d::Hash::hash(payload))))
+  }
+}
+
+/// In-memory chain: coins are added with `fund`, broadcasts spend them and
+/// their outputs become spendable immediately. A broadcast spending coins
+/// held by an unconfirmed transaction replaces it (RBF); `mine` confirms the
+/// mempool in a regtest-difficulty block.
+#[derive(Default, Debug)]
+pub struct MockChain {
+  state: Mutex<MockState>,
+}
+
+#[derive(Default, Debug)]
+struct MockState {
+  utxos: Vec<Utxo>,
+  broadcasts: Vec<Transaction>,
+  /// Unconfirmed transactions and the coins they spent
+  mempool: Vec<(Transaction, Vec<Utxo>)>,
+  confirmed: HashMap<Txid, (Transaction, u32)>,
+  /// Mined blocks; height `h` is at index `h - 1`
+  blocks: Vec<(Header, Vec<Txid>)>,
+  height: u32,
+  /// Coins created by `fund`, numbering each funding transaction
+  funded: u64,
+  /// Accept the next broadcast but fail as if the reply were lost
+  lose_reply: bool,
+}
+
+impl MockState {
+  /// Drop an unconfirmed transaction, giving its inputs back. Fails when
+  /// one of its outputs has already been spent
+  fn evict(&mut self, txid: Txid) -> Result<(), VBError> {
+    let pos = self.mempool.iter().position(|(tx, _)| tx.txid() == txid).ok_or_else(|| VBError::Wallet("Transaction is not in the mempool".into()))?;
+    let (tx, spent) = self.mempool.remove(pos);
+    for vout in 0..tx.output.len() as u32 {
+      if !tx.output[vout as usize].script_pubkey.is_op_return() {
+        let outpoint = OutPoint::new(txid, vout);
+        let i = self
+          .utxos
+          .iter()
+          .position(|u| u.outpoint == outpoint)
+          .ok_or_else(|| VBError::Wallet("Replaced transaction has descendants".into()))?;
+        self.utxos.remove(i);
+      }
+    }
+    self.utxos.extend(spent);
+    Ok(())
+  }
+}
+
+impl MockChain {
+  /// Regtest wallet of [`TEST_MNEMONIC`] and a chain holding one coin of
+  /// `value` sats at its first receive address
+  pub fn funded_hd(value: u64) -> (HdWallet, MockChain) {
+    let hd = HdWallet::from_mnemonic(TEST_MNEMONIC, "", Network::Regtest).unwrap();
+    let chain = MockChain::default();
+    chain.fund(&hd.address(KeyChain::External, 0).unwrap().script_pubkey(), value);
+    (hd, chain)
+  }
+
+  /// [`funded_hd`](Self::funded_hd) with the wallet's xpub-only twin: the
+  /// signer, the watch-only wallet and the chain
+  pub fn funded_watch_only(value: u64) -> (HdWallet, HdWallet, MockChain) {
+    let (signer, chain) = Self::funded_hd(value);
+    // "wpkh([origin]tpub.../0/*)#checksum" -> "[origin]tpub..."
+    let descriptor = signer.descriptor(KeyChain::External);
+    let key = descriptor.trim_start_matches("wpkh(").split("/0/*").next().unwrap_or_default();
+    let watch = HdWallet::watch_only(key, Network::Regtest).unwrap();
+    (signer, watch, chain)
+  }
+
+  /// Create a coin of `value` sats locked to `script_pubkey`
+  pub fn fund(&self, script_pubkey: &Script, value: u64) -> OutPoint {
+    let mut state = self.state.lock().unwrap();
+    state.funded += 1;
+    let seed = state.funded.to_le_bytes();
+    let funding = Transaction {
+      version: 2,
+      lock_time: LockTime::ZERO,
+      input: vec![TxIn { previous_output: OutPoint::new(Txid::from_raw_hash(sha256d::Hash::hash(&seed)), 0), ..Default::default() }],
+      output: vec![TxOut { value, script_pubkey: script_pubkey.to_owned() }],
+    };
+    let outpoint = OutPoint::new(funding.txid(), 0);
+    state.utxos.push(Utxo { outpoint, txout: funding.output[0].clone() });
+    let height = state.height;
+    state.confirmed.insert(funding.txid(), (funding, height));
+    outpoint
+  }
+
+  /// Accept the next broadcast, then fail it with
+  /// [`VBError::BroadcastUnknown`] as a dropped connection would
+  pub fn lose_next_reply(&self) {
+    self.state.lock().unwrap().lose_reply = true;
+  }
+
+  /// Every transaction broadcast so far, including replaced ones
+  pub fn broadcasts(&self) -> Vec<Transaction> {
+    self.state.lock().unwrap().broadcasts.clone()
+  }
+
+  /// Confirm everything in the mempool in a new block, returning its height
+  pub fn mine(&self) -> u32 {
+    let mut state = self.state.lock().unwrap();
+    state.height += 1;
+    let height = state.height;
+    let mut txids = Vec::new();
+    for (tx, _) in std::mem::take(&mut state.mempool) {
+      txids.push(tx.txid());
+      state.confirmed.insert(tx.txid(), (tx, height));
+    }
+    let mut header = Header {
+      version: Version::TWO,
+      prev_blockhash: state.blocks.last().map_or(BlockHash::all_zeros(), |(h, _)| h.block_hash()),
+      merkle_root: bitcoin::merkle_tree::calculate_root(txids.iter().map(|t| TxMerkleNode::from_raw_hash(t.to_raw_hash())))
+        .unwrap_or(TxMerkleNode::all_zeros()),
+      time: 1_600_000_000 + height * 600,
+      bits: CompactTarget::from_consensus(0x207f_ffff),
+      nonce: 0,
+    };
+    while header.validate_pow(header.target()).is_err() {
+      header.nonce += 1;
+    }
+    state.blocks.push((header, txids));
+    height
+  }
+}
+
+impl ChainBackend for MockChain {
+  fn unspent(&self, script_pubkey: &Script) -> Result<Vec<Utxo>, VBError> {
+    let state = self.state.lock().unwrap();
+    Ok(state.utxos.iter().filter(|u| u.txout.script_pubkey.as_script() == script_pubkey).cloned().collect())
+  }
+
+  fn broadcast(&self, tx: &Transaction) -> Result<Txid, VBError> {
+    let mut state = self.state.lock().unwrap();
+    let mut spent = Vec::new();
+    for input in &tx.input {
+      if !state.utxos.iter().any(|u| u.outpoint == input.previous_output) {
+        let conflict = state
+          .mempool
+          .iter()
+          .find(|(_, coins)| coins.iter().any(|u| u.outpoint == input.previous_output))
+          .map(|(tx, _)| tx.txid())
+          .ok_or_else(|| VBError::Wallet("Input missing or already spent".into()))?;
+        state.evict(conflict)?;
+      }
+      let pos = state.utxos.iter().position(|u| u.outpoint == input.previous_output).ok_or_else(|| VBError::Wallet("Input missing or already spent".into()))?;
+      spent.push(state.utxos.remove(pos));
+    }
+    let txid = tx.txid();
+    for (vout, txout) in tx.output.iter().enumerate() {
+      if !txout.script_pubkey.is_op_return() {
+        state.utxos.push(Utxo { outpoint: OutPoint::new(txid, vout as u32), txout: txout.clone() });
+      }
+    }
+    state.mempool.push((tx.clone(), spent));
+    state.broadcasts.push(tx.clone());
+    if std::mem::take(&mut state.lose_reply) {
+      return Err(VBError::BroadcastUnknown("connection reset".into()));
+    }
+    Ok(txid)
+  }
+
+  fn transaction(&self, txid: &Txid) -> Result<Option<Transaction>, VBError> {
+    let state = self.state.lock().unwrap();
+    let unconfirmed = state.mempool.iter().map(|(tx, _)| tx).find(|tx| tx.txid() == *txid);
+    Ok(unconfirmed.or(state.confirmed.get(txid).map(|(tx, _)| tx)).cloned())
+  }
+
+  fn confirmed_height(&self, txid: &Txid) -> Result<Option<u32>, VBError> {
+    Ok(self.state.lock().unwrap().confirmed.get(txid).map(|(_, height)| *height))
+  }
+
+  fn merkle_proof(&self, txid: &Txid) -> Result<Option<MerkleProof>, VBError> {
+    let state = self.state.lock().unwrap();
+    let found = state.blocks.iter().enumerate().find_map(|(i, (header, txids))| {
+      let pos = txids.iter().position(|t| t == txid)?;
+      Some((i as u32 + 1, header, txids, pos))
+    });
+    let Some((height, header, txids, mut pos)) = found else {
+      return Ok(None);
+    };
+    let proof_pos = pos as u32;
+    let mut level: Vec<TxMerkleNode> = txids.iter().map(|t| TxMerkleNode::from_raw_hash(t.to_raw_hash())).collect();
+    let mut merkle = Vec::new();
+    while level.len() > 1 {
+      if level.len() % 2 == 1 {
+        level.push(*level.last().unwrap());
+      }
+      merkle.push(level[pos ^ 1]);
+      level = level
+        .chunks(2)
+        .map(|pair| TxMerkleNode::hash(&[pair[0].to_byte_array(), pair[1].to_byte_array()].concat()))
+        .collect();
+      pos /= 2;
+    }
+    Ok(Some(MerkleProof { block_hash: header.block_hash(), height, pos: proof_pos, merkle }))
+  }
+
+  fn block_header(&self, hash: &BlockHash) -> Result<Option<Header>, VBError> {
+    let state = self.state.lock().unwrap();
+    Ok(state.blocks.iter().map(|(h, _)| *h).find(|h| h.block_hash() == *hash))
+  }
+
+  fn script_history(&self, script_pubkey: &Script) -> Result<Vec<Txid>, VBError> {
+    let state = self.state.lock().unwrap();
+    let known = |txid: &Txid| {
+      state.confirmed.get(txid).map(|(tx, _)| tx).or(state.mempool.iter().map(|(tx, _)| tx).find(|tx| tx.txid() == *txid))
+    };
+    let touches = |tx: &Transaction| {
+      tx.output.iter().any(|o| o.script_pubkey.as_script() == script_pubkey)
+        || tx.input.iter().any(|i| {
+          known(&i.previous_output.txid)
+            .and_then(|prev| prev.output.get(i.previous_output.vout as usize))
+            .is_some_and(|o| o.script_pubkey.as_script() == script_pubkey)
+        })
+    };
+    let confirmed = state.blocks.iter().flat_map(|(_, txids)| txids).filter_map(|t| state.confirmed.get(t));
+    let funding = state.confirmed.values().filter(|(tx, _)| !state.blocks.iter().any(|(_, t)| t.contains(&tx.txid())));
+    Ok(funding
+      .chain(confirmed)
+      .map(|(tx, _)| tx)
+      .chain(state.mempool.iter().map(|(tx, _)| tx))
+      .filter(|tx| touches(tx))
+      .map(|tx| tx.txid())
+      .collect())
+  }
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>, VBError> {
+    let state = self.state.lock().unwrap();
+    let index = height.checked_sub(1).map(|h| h as usize);
+    Ok(index.and_then(|i| state.blocks.get(i)).map(|(h, _)| h.block_hash()))
+  }
+
+  fn block(&self, hash: &BlockHash) -> Result<Option<Block>, VBError> {
+    let state = self.state.lock().unwrap();
+    Ok(state.blocks.iter().find(|(h, _)| h.block_hash() == *hash).map(|(header, txids)| Block {
+      header: *header,
+      txdata: txids.iter().map(|t| state.confirmed[t].0.clone()).collect(),
+    }))
+  }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use bitcoin::ScriptBuf;
+
+  #[test]
+  fn test_fund_never_reuses_an_outpoint() {
+    let chain = MockChain::default();
+    let script = ScriptBuf::new_op_return(&[1]);
+    let first = chain.fund(&script, 1_000);
+    let spend = Transaction {
+      version: 2,
+      lock_time: LockTime::ZERO,
+      input: vec![TxIn { previous_output: first, ..Default::default() }],
+      output: vec![TxOut { value: 900, script_pubkey: ScriptBuf::new_op_return(&[2]) }],
+    };
+    chain.broadcast(&spend).unwrap();
+    assert!(chain.unspent(&script).unwrap().is_empty());
+
+    let second = chain.fund(&script, 1_000);
+    assert_ne!(second, first);
+    assert_eq!(chain.unspent(&script).unwrap().len(), 1);
+  }
+}
@@ -0,0 +1,66 @@
+use crate::tx::p2wpkh_sighash;
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{ScriptBuf, Transaction, Witness};
+use std::path::Path;
+use std::str::FromStr;
+use validblock_types::VBError;
+
+pub use bitcoin::psbt::PartiallySignedTransaction as Psbt;
+
+/// Base64 encoding used by wallets and `bitcoin-cli`
+pub fn to_base64(psbt: &Psbt) -> String {
+  psbt.to_string()
+}
+
+pub fn from_base64(s: &str) -> Result<Psbt, VBError> {
+  Psbt::from_str(s.trim()).map_err(|e| VBError::InvalidArgument(format!("Invalid PSBT: {}", e)))
+}
+
+/// Write the binary BIP-174 file format (`.psbt`)
+pub fn write_file<P: AsRef<Path>>(psbt: &Psbt, path: P) -> Result<(), VBError> {
+  std::fs::write(path, psbt.serialize())?;
+  Ok(())
+}
+
+/// Read a `.psbt` file, accepting either binary or base64 contents
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Psbt, VBError> {
+  let data = std::fs::read(path)?;
+  match Psbt::deserialize(&data) {
+    Ok(psbt) => Ok(psbt),
+    Err(_) => from_base64(&String::from_utf8_lossy(&data)),
+  }
+}
+
+/// Check each input's P2WPKH signature, move it into the final witness and
+/// extract the network-ready transaction
+pub fn finalize(psbt: &mut Psbt) -> Result<Transaction, VBError> {
+  let secp = Secp256k1::verification_only();
+  for i in 0..psbt.inputs.len() {
+    let input = &psbt.inputs[i];
+    if input.final_script_witness.is_some() {
+      continue;
+    }
+    let utxo = input.witness_utxo.as_ref().ok_or_else(|| VBError::InvalidArgument("PSBT input has no witness UTXO".into()))?;
+    let (pk, sig) = input
+      .partial_sigs
+      .iter()
+      .next()
+      .ok_or_else(|| VBError::InvalidArgument(format!("PSBT input {} is not signed", i)))?;
+    let wpkh = pk.wpubkey_hash().ok_or_else(|| VBError::InvalidArgument("Uncompressed public key in PSBT".into()))?;
+    if utxo.script_pubkey != ScriptBuf::new_v0_p2wpkh(&wpkh) || sig.hash_ty != EcdsaSighashType::All {
+      return Err(VBError::InvalidArgument("PSBT input is not P2WPKH signed with SIGHASH_ALL".into()));
+    }
+    let msg = p2wpkh_sighash(&psbt.unsigned_tx, i, pk, utxo.value)?;
+    secp
+      .verify_ecdsa(&msg, &sig.sig, &pk.inner)
+      .map_err(|_| VBError::InvalidArgument(format!("Invalid signature on PSBT input {}", i)))?;
+
+    let witness = Witness::from_slice(&[sig.to_vec(), pk.to_bytes()]);
+    let input = &mut psbt.inputs[i];
+    input.final_script_witness = Some(witness);
+    input.partial_sigs.clear();
+    input.bip32_derivation.clear();
+  }
+  Ok(psbt.clone().extract_tx())
+}
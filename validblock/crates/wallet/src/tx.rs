@@ -0,0 +1,188 @@
+use crate::chain::Utxo;
+use crate::FeeCalc;
+use bitcoin::absolute::LockTime;
+use bitcoin::script::{Instruction, PushBytesBuf};
+use bitcoin::secp256k1::Message;
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::transaction::{predict_weight, InputWeightPrediction};
+use bitcoin::{PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use validblock_types::VBError;
+
+/// Largest OP_RETURN payload relayed by default policy
+pub const MAX_OP_RETURN_LEN: usize = 80;
+
+/// Smallest change output worth creating for P2WPKH
+pub const P2WPKH_DUST: u64 = 294;
+
+/// Minimum fee-rate step (sat/vB) a replacement must add on top of the fee
+/// it replaces (BIP-125 rule 4)
+pub const INCREMENTAL_RELAY_FEE: u64 = 1;
+
+/// P2WPKH scriptPubKey length: OP_0 + push of 20 bytes
+const P2WPKH_SCRIPT_LEN: usize = 22;
+
+/// Worst-case P2WPKH spend: 73-byte signature and compressed key.
+/// `InputWeightPrediction::P2WPKH_MAX` in bitcoin 0.30 leaves out the empty
+/// scriptSig's length byte
+const P2WPKH_INPUT: InputWeightPrediction = InputWeightPrediction::from_slice(0, &[73, 33]);
+
+/// Build an unsigned anchor transaction: one OP_RETURN carrying `payload` and
+/// one change output, funded largest-first from `utxos`.
+///
+/// Returns the transaction and the coins it spends, in input order. Inputs
+/// signal BIP-125 replaceability.
+pub fn build_anchor_tx(
+  utxos: &[Utxo],
+  payload: &[u8],
+  change_script: ScriptBuf,
+  fee: &FeeCalc,
+) -> Result<(Transaction, Vec<Utxo>), VBError> {
+  if payload.len() > MAX_OP_RETURN_LEN {
+    return Err(VBError::InvalidArgument(format!(
+      "OP_RETURN payload is {} bytes (max {})",
+      payload.len(),
+      MAX_OP_RETURN_LEN
+    )));
+  }
+  let push = PushBytesBuf::try_from(payload.to_vec()).map_err(|_| VBError::InvalidArgument("OP_RETURN payload too large".into()))?;
+  let op_return = ScriptBuf::new_op_return(&push);
+
+  let mut coins: Vec<Utxo> = utxos.to_vec();
+  coins.sort_by_key(|u| std::cmp::Reverse(u.txout.value));
+
+  let mut selected = Vec::new();
+  let mut total = 0u64;
+  for coin in coins {
+    total += coin.txout.value;
+    selected.push(coin);
+    let weight = predict_weight(
+      vec![P2WPKH_INPUT; selected.len()],
+      [op_return.len(), P2WPKH_SCRIPT_LEN],
+    );
+    let required = fee.fee_for(weight) + P2WPKH_DUST;
+    if total >= required {
+      let change = total - fee.fee_for(weight);
+      let tx = Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: selected
+          .iter()
+          .map(|u| TxIn {
+            previous_output: u.outpoint,
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+          })
+          .collect(),
+        output: vec![
+          TxOut { value: 0, script_pubkey: op_return },
+          TxOut { value: change, script_pubkey: change_script },
+        ],
+      };
+      return Ok((tx, selected));
+    }
+  }
+  Err(VBError::Wallet(format!("Insufficient funds: wallet holds {} sat", total)))
+}
+
+/// Build an unsigned BIP-125 replacement for `original`: same inputs and
+/// OP_RETURN payload, with the change output reduced to pay `fee`.
+///
+/// `spent` holds the outputs `original` spends, in input order. The new fee
+/// also covers the replaced one plus the incremental relay fee.
+pub fn build_rbf_tx(original: &Transaction, spent: &[TxOut], fee: &FeeCalc) -> Result<Transaction, VBError> {
+  let change_vout = original
+    .output
+    .iter()
+    .position(|o| !o.script_pubkey.is_op_return())
+    .ok_or_else(|| VBError::Wallet("Anchor transaction has no change output to bump".into()))?;
+  let old_fee = tx_fee(original, spent)?;
+  let weight = predict_weight(
+    vec![P2WPKH_INPUT; original.input.len()],
+    original.output.iter().map(|o| o.script_pubkey.len()),
+  );
+  let new_fee = fee.fee_for(weight).max(old_fee + weight.to_vbytes_ceil() * INCREMENTAL_RELAY_FEE);
+
+  let mut tx = original.clone();
+  let change = &mut tx.output[change_vout];
+  change.value = change
+    .value
+    .checked_sub(new_fee - old_fee)
+    .filter(|v| *v >= P2WPKH_DUST)
+    .ok_or_else(|| VBError::Wallet(format!("Change cannot cover a {} sat fee", new_fee)))?;
+  for input in &mut tx.input {
+    input.sequence = Sequence::ENABLE_RBF_NO_LOCKTIME;
+    input.witness = Witness::new();
+  }
+  Ok(tx)
+}
+
+/// Build an unsigned child spending `change` (an output of `parent`) to
+/// `dest`, paying enough that parent and child together reach `fee`
+pub fn build_cpfp_tx(
+  parent: &Transaction,
+  parent_fee: u64,
+  change: &Utxo,
+  dest: ScriptBuf,
+  fee: &FeeCalc,
+) -> Result<Transaction, VBError> {
+  let child_weight = predict_weight([P2WPKH_INPUT], [P2WPKH_SCRIPT_LEN]);
+  let package = fee.fee_for(parent.weight() + child_weight);
+  let child_fee = package
+    .saturating_sub(parent_fee)
+    .max(child_weight.to_vbytes_ceil() * INCREMENTAL_RELAY_FEE);
+  let value = change
+    .txout
+    .value
+    .checked_sub(child_fee)
+    .filter(|v| *v >= P2WPKH_DUST)
+    .ok_or_else(|| VBError::Wallet(format!("Change cannot cover a {} sat child fee", child_fee)))?;
+  Ok(Transaction {
+    version: 2,
+    lock_time: LockTime::ZERO,
+    input: vec![TxIn {
+      previous_output: change.outpoint,
+      script_sig: ScriptBuf::new(),
+      sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+      witness: Witness::new(),
+    }],
+    output: vec![TxOut { value, script_pubkey: dest }],
+  })
+}
+
+/// Fee paid by `tx` given the outputs it spends
+pub fn tx_fee(tx: &Transaction, spent: &[TxOut]) -> Result<u64, VBError> {
+  let inputs: u64 = spent.iter().map(|o| o.value).sum();
+  let outputs: u64 = tx.output.iter().map(|o| o.value).sum();
+  inputs.checked_sub(outputs).ok_or_else(|| VBError::Wallet("Outputs exceed inputs".into()))
+}
+
+/// Data pushed by the first OP_RETURN output of `tx`
+pub fn op_return_payload(tx: &Transaction) -> Option<Vec<u8>> {
+  let script = &tx.output.iter().find(|o| o.script_pubkey.is_op_return())?.script_pubkey;
+  let mut data = Vec::new();
+  for ins in script.instructions().skip(1) {
+    match ins.ok()? {
+      Instruction::PushBytes(bytes) => data.extend_from_slice(bytes.as_bytes()),
+      Instruction::Op(_) => return None,
+    }
+  }
+  Some(data)
+}
+
+/// BIP-143 SIGHASH_ALL message for a P2WPKH input locked to `pk`
+pub(crate) fn p2wpkh_sighash(
+  tx: &Transaction,
+  input_index: usize,
+  pk: &PublicKey,
+  value: u64,
+) -> Result<Message, VBError> {
+  let wpkh = pk.wpubkey_hash().ok_or_else(|| VBError::Wallet("Uncompressed public key".into()))?;
+  let script_code = ScriptBuf::new_v0_p2wpkh(&wpkh)
+    .p2wpkh_script_code()
+    .ok_or_else(|| VBError::Wallet("Not a P2WPKH script".into()))?;
+  let sighash = SighashCache::new(tx)
+    .segwit_signature_hash(input_index, &script_code, value, EcdsaSighashType::All)
+    .map_err(|_| VBError::Wallet("Sighash computation failed".into()))?;
+  Message::from_slice(sighash.as_ref()).map_err(|_| VBError::Wallet("Invalid sighash".into()))
+}
@@ -0,0 +1,200 @@
+use crate::chain::ChainBackend;
+use crate::tx::op_return_payload;
+use bitcoin::hashes::Hash;
+use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::{BlockHash, CompactTarget, Network, Target, Txid};
+use std::fmt;
+use validblock_types::{AnchorPayload, Digest256, VBError};
+
+/// Outcome of checking an anchor against the chain alone
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+  /// The transaction commits to the digest and is proven into a block whose
+  /// header meets the network's minimum difficulty. Whether that block is on
+  /// the best chain is not checked
+  Confirmed { block_hash: BlockHash, height: u32, time: u32 },
+  /// The transaction commits to the digest but is not in a block yet
+  Unconfirmed,
+  /// The transaction carries no valid payload for the digest
+  Mismatch,
+  /// The backend does not know the transaction
+  NotFound,
+  /// The backend's transaction, Merkle proof or header don't add up
+  InvalidProof(String),
+}
+
+impl Verdict {
+  /// Only a proven inclusion counts as verified
+  pub fn is_verified(&self) -> bool {
+    matches!(self, Verdict::Confirmed { .. })
+  }
+}
+
+impl fmt::Display for Verdict {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Verdict::Confirmed { block_hash, height, .. } => write!(f, "confirmed in block {} ({})", height, block_hash),
+      Verdict::Unconfirmed => f.write_str("unconfirmed"),
+      Verdict::Mismatch => f.write_str("transaction does not commit to this digest"),
+      Verdict::NotFound => f.write_str("transaction not found"),
+      Verdict::InvalidProof(why) => write!(f, "invalid proof: {}", why),
+    }
+  }
+}
+
+/// Check that `txid` carries an anchor payload for `digest` in its OP_RETURN
+/// and, if confirmed, that its Merkle branch leads to the root of a header
+/// whose proof-of-work meets `network`'s minimum difficulty. Nothing local is
+/// consulted, but neither is a header chain: a backend willing to mine a
+/// minimum-difficulty block can still forge a confirmation.
+pub fn verify_anchor<C: ChainBackend + ?Sized>(
+  chain: &C,
+  network: Network,
+  txid: &Txid,
+  digest: &Digest256,
+) -> Result<Verdict, VBError> {
+  let Some(tx) = chain.transaction(txid)? else {
+    return Ok(Verdict::NotFound);
+  };
+  if tx.txid() != *txid {
+    return Ok(Verdict::InvalidProof("backend returned a different transaction".into()));
+  }
+  let anchored = op_return_payload(&tx).and_then(|bytes| AnchorPayload::decode(&bytes).ok());
+  if anchored.map(|p| p.digest).as_ref() != Some(digest) {
+    return Ok(Verdict::Mismatch);
+  }
+
+  let Some(proof) = chain.merkle_proof(txid)? else {
+    return Ok(Verdict::Unconfirmed);
+  };
+  let Some(header) = chain.block_header(&proof.block_hash)? else {
+    return Ok(Verdict::InvalidProof(format!("unknown block {}", proof.block_hash)));
+  };
+  if header.block_hash() != proof.block_hash {
+    return Ok(Verdict::InvalidProof("header does not hash to the proven block".into()));
+  }
+  if header.target() > easiest_target(network) {
+    return Ok(Verdict::InvalidProof(format!("header's target is easier than {} allows", network)));
+  }
+  if header.validate_pow(header.target()).is_err() {
+    return Ok(Verdict::InvalidProof("header fails proof-of-work".into()));
+  }
+  if merkle_root(txid, proof.pos, &proof.merkle) != header.merkle_root {
+    return Ok(Verdict::InvalidProof("Merkle branch does not match the header".into()));
+  }
+  Ok(Verdict::Confirmed { block_hash: proof.block_hash, height: proof.height, time: header.time })
+}
+
+/// Easiest target a block of `network` may have, as the `bits` of its
+/// genesis block
+fn easiest_target(network: Network) -> Target {
+  let bits = match network {
+    Network::Signet => 0x1e03_77ae,
+    Network::Regtest => 0x207f_ffff,
+    _ => 0x1d00_ffff,
+  };
+  Target::from_compact(CompactTarget::from_consensus(bits))
+}
+
+/// Fold a Merkle branch from leaf `txid` at position `pos` up to the root
+pub fn merkle_root(txid: &Txid, pos: u32, branch: &[TxMerkleNode]) -> TxMerkleNode {
+  let mut node = TxMerkleNode::from_raw_hash(txid.to_raw_hash());
+  let mut pos = pos;
+  for sibling in branch {
+    let (left, right) = if pos & 1 == 0 { (node, *sibling) } else { (*sibling, node) };
+    node = TxMerkleNode::hash(&[left.to_byte_array(), right.to_byte_array()].concat());
+    pos >>= 1;
+  }
+  node
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::chain::{MerkleProof, Utxo};
+  use crate::mock::MockChain;
+  use crate::{FeeCalc, HotWallet, WalletAdapter};
+  use bitcoin::block::{Block, Header};
+  use bitcoin::{Network, Script, Transaction};
+
+  fn payload(byte: u8) -> Vec<u8> {
+    AnchorPayload::new(Digest256([byte; 32])).encode()
+  }
+
+  fn wallet() -> HotWallet<MockChain> {
+    let (hd, chain) = MockChain::funded_hd(100_000);
+    HotWallet::new(hd, chain, FeeCalc::fixed(1))
+  }
+
+  /// Backend that lies about where a transaction was mined
+  struct ForgedProof<'a>(&'a MockChain);
+
+  impl ChainBackend for ForgedProof<'_> {
+    fn unspent(&self, script_pubkey: &Script) -> Result<Vec<Utxo>, VBError> {
+      self.0.unspent(script_pubkey)
+    }
+    fn broadcast(&self, tx: &Transaction) -> Result<Txid, VBError> {
+      self.0.broadcast(tx)
+    }
+    fn transaction(&self, txid: &Txid) -> Result<Option<Transaction>, VBError> {
+      self.0.transaction(txid)
+    }
+    fn confirmed_height(&self, txid: &Txid) -> Result<Option<u32>, VBError> {
+      self.0.confirmed_height(txid)
+    }
+    fn merkle_proof(&self, txid: &Txid) -> Result<Option<MerkleProof>, VBError> {
+      let mut proof = self.0.merkle_proof(txid)?;
+      if let Some(p) = proof.as_mut() {
+        p.merkle.push(TxMerkleNode::all_zeros());
+      }
+      Ok(proof)
+    }
+    fn block_header(&self, hash: &BlockHash) -> Result<Option<Header>, VBError> {
+      self.0.block_header(hash)
+    }
+    fn script_history(&self, script_pubkey: &Script) -> Result<Vec<Txid>, VBError> {
+      self.0.script_history(script_pubkey)
+    }
+    fn block_hash(&self, height: u32) -> Result<Option<BlockHash>, VBError> {
+      self.0.block_hash(height)
+    }
+    fn block(&self, hash: &BlockHash) -> Result<Option<Block>, VBError> {
+      self.0.block(hash)
+    }
+  }
+
+  #[test]
+  fn test_verify_confirmed_anchor() {
+    let mut wallet = wallet();
+    // a second transaction in the block gives the proof a real branch
+    let txid = wallet.anchor(&payload(1)).unwrap().txid();
+    // raw digest without the payload header does not count
+    let untagged = wallet.anchor(&[2u8; 32]).unwrap().txid();
+    assert_eq!(verify_anchor(wallet.chain(), Network::Regtest, &txid, &Digest256([1; 32])).unwrap(), Verdict::Unconfirmed);
+
+    let height = wallet.chain().mine();
+    let verdict = verify_anchor(wallet.chain(), Network::Regtest, &txid, &Digest256([1; 32])).unwrap();
+    assert!(verdict.is_verified());
+    assert!(matches!(verdict, Verdict::Confirmed { height: h, .. } if h == height));
+    assert_eq!(verify_anchor(wallet.chain(), Network::Regtest, &txid, &Digest256([2; 32])).unwrap(), Verdict::Mismatch);
+    assert_eq!(verify_anchor(wallet.chain(), Network::Regtest, &untagged, &Digest256([2; 32])).unwrap(), Verdict::Mismatch);
+  }
+
+  #[test]
+  fn test_verify_rejects_forged_proof() {
+    let mut wallet = wallet();
+    let txid = wallet.anchor(&payload(1)).unwrap().txid();
+    wallet.chain().mine();
+    let verdict = verify_anchor(&ForgedProof(wallet.chain()), Network::Regtest, &txid, &Digest256([1; 32])).unwrap();
+    assert!(matches!(verdict, Verdict::InvalidProof(_)));
+    // a regtest-difficulty header proves nothing on mainnet
+    let verdict = verify_anchor(wallet.chain(), Network::Bitcoin, &txid, &Digest256([1; 32])).unwrap();
+    assert!(matches!(verdict, Verdict::InvalidProof(_)), "{}", verdict);
+    let unknown = Txid::all_zeros();
+    assert_eq!(verify_anchor(wallet.chain(), Network::Regtest, &unknown, &Digest256([1; 32])).unwrap(), Verdict::NotFound);
+  }
+}
@@ -0,0 +1,187 @@
+use crate::chain::{prevouts, unconfirmed_tx, ChainBackend, Utxo};
+use crate::hd::{HdWallet, KeyChain};
+use crate::psbt::{self, Psbt};
+use crate::tx::{build_anchor_tx, build_rbf_tx};
+use crate::{AnchorTx, FeeBump, FeeCalc, WalletAdapter};
+use bitcoin::{OutPoint, Transaction, TxOut, Txid};
+use std::collections::HashSet;
+use validblock_types::VBError;
+
+/// Wallet that only knows the account xpub: anchors come back as PSBTs for
+/// an offline signer and are broadcast once the signed PSBT is imported
+#[derive(Debug)]
+pub struct WatchOnlyWallet<C: ChainBackend> {
+  hd: HdWallet,
+  chain: C,
+  fee: FeeCalc,
+  /// Coins spent by PSBTs still out for signing
+  reserved: HashSet<OutPoint>,
+}
+
+impl<C: ChainBackend> WatchOnlyWallet<C> {
+  pub fn new(hd: HdWallet, chain: C, fee: FeeCalc) -> Self {
+    Self { hd, chain, fee, reserved: HashSet::new() }
+  }
+
+  pub fn hd(&self) -> &HdWallet {
+    &self.hd
+  }
+
+  pub fn chain(&self) -> &C {
+    &self.chain
+  }
+
+  /// Wrap `tx` for an offline signer: each input gets the output it spends
+  /// and every key path is filled in so the signer can find its keys
+  fn to_psbt(&self, tx: Transaction, spent: &[TxOut]) -> Result<Psbt, VBError> {
+    let mut psbt = Psbt::from_unsigned_tx(tx).map_err(|_| VBError::Wallet("Transaction is already signed".into()))?;
+    for (i, prev) in spent.iter().enumerate() {
+      let (chain, index) = self.hd.owner_of(&prev.script_pubkey)?.ok_or_else(|| VBError::Wallet("Input is not owned by this wallet".into()))?;
+      let (pk, source) = self.hd.key_source(chain, index)?;
+      psbt.inputs[i].witness_utxo = Some(prev.clone());
+      psbt.inputs[i].bip32_derivation.insert(pk.inner, source);
+    }
+    for (i, out) in psbt.unsigned_tx.output.clone().iter().enumerate() {
+      if let Some((chain, index)) = self.hd.owner_of(&out.script_pubkey)? {
+        let (pk, source) = self.hd.key_source(chain, index)?;
+        psbt.outputs[i].bip32_derivation.insert(pk.inner, source);
+      }
+    }
+    Ok(psbt)
+  }
+}
+
+impl<C: ChainBackend> WalletAdapter for WatchOnlyWallet<C> {
+  fn anchor(&mut self, payload: &[u8]) -> Result<AnchorTx, VBError> {
+    self.anchor_with_fee(payload, self.fee)
+  }
+
+  fn anchor_with_fee(&mut self, payload: &[u8], fee: FeeCalc) -> Result<AnchorTx, VBError> {
+    let owned: Vec<(Utxo, KeyChain, u32)> = self
+      .hd
+      .scan(&self.chain)?
+      .into_iter()
+      .filter(|(u, _, _)| !self.reserved.contains(&u.outpoint))
+      .collect();
+    let change_index = self.hd.next_index(KeyChain::Internal)?;
+    let change_script = self.hd.address(KeyChain::Internal, change_index)?.script_pubkey();
+
+    let utxos: Vec<Utxo> = owned.into_iter().map(|(u, _, _)| u).collect();
+    let (tx, spent) = build_anchor_tx(&utxos, payload, change_script, &fee)?;
+    let prevs: Vec<TxOut> = spent.iter().map(|u| u.txout.clone()).collect();
+    let psbt = self.to_psbt(tx, &prevs)?;
+
+    self.reserved.extend(spent.iter().map(|u| u.outpoint));
+    self.hd.mark_used(KeyChain::Internal, change_index);
+    Ok(AnchorTx::AwaitingSignature(psbt))
+  }
+
+  fn broadcast_signed(&mut self, mut psbt: Psbt) -> Result<Txid, VBError> {
+    let tx = psbt::finalize(&mut psbt)?;
+    let txid = self.chain.broadcast(&tx)?;
+    self.release(&psbt);
+    Ok(txid)
+  }
+
+  fn restore_pending(&mut self, pending: &[Psbt]) {
+    self.reserved = pending.iter().flat_map(|psbt| psbt.unsigned_tx.input.iter().map(|input| input.previous_output)).collect();
+  }
+
+  fn release(&mut self, psbt: &Psbt) {
+    for input in &psbt.unsigned_tx.input {
+      self.reserved.remove(&input.previous_output);
+    }
+  }
+
+  /// Only RBF: the replacement comes back as a PSBT for the offline signer
+  fn bump_fee(&mut self, txid: &Txid, method: FeeBump, fee: FeeCalc) -> Result<AnchorTx, VBError> {
+    if method != FeeBump::Rbf {
+      return Err(VBError::Other("Watch-only wallets bump fees with RBF only".into()));
+    }
+    let original = unconfirmed_tx(&self.chain, txid)?;
+    let spent = prevouts(&self.chain, &original)?;
+    let tx = build_rbf_tx(&original, &spent, &fee)?;
+    Ok(AnchorTx::AwaitingSignature(self.to_psbt(tx, &spent)?))
+  }
+
+  fn confirmed_height(&self, txid: &Txid) -> Result<Option<u32>, VBError> {
+    self.chain.confirmed_height(txid)
+  }
+
+  fn balance(&mut self) -> Result<Option<u64>, VBError> {
+    Ok(Some(self.hd.scan(&self.chain)?.iter().map(|(u, _, _)| u.txout.value).sum()))
+  }
+
+  fn ping(&self) -> Result<(), VBError> {
+    self.chain.block_hash(0).map(|_| ())
+  }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::mock::MockChain;
+
+  fn watch_wallet() -> (HdWallet, WatchOnlyWallet<MockChain>) {
+    let (signer, hd, chain) = MockChain::funded_watch_only(50_000);
+    (signer, WatchOnlyWallet::new(hd, chain, FeeCalc::fixed(1)))
+  }
+
+  #[test]
+  fn test_psbt_export_sign_import() {
+    let (signer, mut wallet) = watch_wallet();
+    let AnchorTx::AwaitingSignature(psbt) = wallet.anchor(&[9u8; 32]).unwrap() else {
+      panic!("Expected a PSBT");
+    };
+    assert!(wallet.chain().broadcasts().is_empty());
+
+    // round-trip through the offline signer as base64
+    let mut offline = psbt::from_base64(&psbt::to_base64(&psbt)).unwrap();
+    assert_eq!(signer.sign_psbt(&mut offline).unwrap(), 1);
+
+    let txid = wallet.broadcast_signed(offline).unwrap();
+    assert_eq!(txid, psbt.unsigned_tx.txid());
+    assert_eq!(wallet.chain().broadcasts().len(), 1);
+  }
+
+  #[test]
+  fn test_rbf_returns_replacement_psbt() {
+    let (signer, mut wallet) = watch_wallet();
+    let AnchorTx::AwaitingSignature(mut psbt) = wallet.anchor(&[9u8; 32]).unwrap() else {
+      panic!("Expected a PSBT");
+    };
+    signer.sign_psbt(&mut psbt).unwrap();
+    let first = wallet.broadcast_signed(psbt).unwrap();
+
+    let AnchorTx::AwaitingSignature(mut bump) = wallet.bump_fee(&first, FeeBump::Rbf, FeeCalc::fixed(10)).unwrap() else {
+      panic!("Expected a PSBT");
+    };
+    assert_eq!(signer.sign_psbt(&mut bump).unwrap(), 1);
+    let replacement = wallet.broadcast_signed(bump).unwrap();
+    assert_ne!(replacement, first);
+    assert!(wallet.chain().transaction(&first).unwrap().is_none());
+    assert!(wallet.bump_fee(&replacement, FeeBump::Cpfp, FeeCalc::fixed(20)).is_err());
+  }
+
+  #[test]
+  fn test_unsigned_psbt_rejected() {
+    let (_, mut wallet) = watch_wallet();
+    let AnchorTx::AwaitingSignature(psbt) = wallet.anchor(&[9u8; 32]).unwrap() else {
+      panic!("Expected a PSBT");
+    };
+    assert!(wallet.broadcast_signed(psbt.clone()).is_err());
+    // the reserved coin is not offered to a second anchor
+    assert!(wallet.anchor(&[10u8; 32]).is_err());
+
+    // nor after a restart, once the pending PSBT is restored
+    let (_, mut restarted) = watch_wallet();
+    restarted.restore_pending(std::slice::from_ref(&psbt));
+    assert!(restarted.anchor(&[10u8; 32]).is_err());
+    restarted.release(&psbt);
+    assert!(restarted.anchor(&[10u8; 32]).is_ok());
+  }
+}